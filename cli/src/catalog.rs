@@ -0,0 +1,31 @@
+//! Product catalogs for `marketplace-cli import`.
+//!
+//! A catalog is a CSV file with a header row naming the `ProductInput`
+//! fields, in any order:
+//!
+//! ```text
+//! title,description,category,expiry,image,price,target
+//! Hat,A warm hat,Clothes,1767225600,hat.png,500,100
+//! ```
+//!
+//! or a JSON array of objects with the same fields, such as the output of
+//! `export_products`.
+
+use std::path::Path;
+
+use marketplace_sdk::ProductInput;
+
+use crate::Result;
+
+/// Parses `text` as a CSV or JSON catalog depending on the extension of
+/// `path`.
+pub fn parse(path: &Path, text: &str) -> Result<Vec<ProductInput>> {
+    let products = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => csv::Reader::from_reader(text.as_bytes())
+            .deserialize()
+            .collect::<std::result::Result<_, _>>()?,
+        Some("json") => serde_json::from_str(text)?,
+        _ => return Err(format!("{}: expected a .csv or .json file", path.display()).into()),
+    };
+    Ok(products)
+}
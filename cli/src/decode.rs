@@ -0,0 +1,24 @@
+//! Decoding of XDR pulled from transaction results and meta.
+
+use marketplace_sdk::stellar_xdr::curr::{ContractEvent, DiagnosticEvent, Limits, ReadXdr, ScVal};
+use marketplace_sdk::{Event, FromScVal, Product};
+
+use crate::Result;
+
+/// Decodes a base64 `ScVal` holding a `Product`, such as the return value of
+/// `create_product` or `get_product`.
+pub fn product(xdr: &str) -> Result<Product> {
+    let val = ScVal::from_xdr_base64(xdr.trim(), Limits::none())?;
+    Ok(Product::from_scval(&val)?)
+}
+
+/// Decodes a base64 `ContractEvent`, or a `DiagnosticEvent` wrapping one, as
+/// found in transaction meta.
+pub fn event(xdr: &str) -> Result<Event> {
+    let xdr = xdr.trim();
+    let event = match ContractEvent::from_xdr_base64(xdr, Limits::none()) {
+        Ok(event) => event,
+        Err(_) => DiagnosticEvent::from_xdr_base64(xdr, Limits::none())?.event,
+    };
+    Ok(Event::from_contract_event(&event)?)
+}
@@ -0,0 +1,381 @@
+//! Input files for `marketplace-cli build`.
+//!
+//! A file describes one transaction carrying one contract call, in TOML or
+//! JSON (picked by extension). Addresses are strkeys:
+//!
+//! ```toml
+//! source = "GA...."
+//! sequence = 4294967297
+//! fee = 1000000
+//! network_passphrase = "Test SDF Network ; September 2015"
+//! contract = "CA...."
+//!
+//! [call.propose]
+//! proposer = "GA...."
+//! action = { SetSplit = { reserve = 50, launchpad = 20, dev = 30 } }
+//! ```
+//!
+//! `marketplace-cli import` takes the same file without `[call]` and builds
+//! one `create_products_batch` transaction per batch of the catalog.
+//!
+//! `soroban_data` and `auth` take the base64 XDR returned by simulating the
+//! transaction; without them the envelope still needs a simulation pass
+//! before it can be signed and submitted.
+
+use std::path::Path;
+
+use marketplace_sdk::stellar_xdr::curr::{HostFunction, ScAddress};
+use marketplace_sdk::{
+    Client, Coupon, DisputeConfig, LoyaltyConfig, PayoutMode, ProductInput, ProductLimits,
+    ProductMetadata, ProposalAction, ReceiptMode, ReferralConfig, Ruling, SalePhase,
+};
+use serde::{Deserialize, Deserializer};
+
+use crate::Result;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TxInput {
+    /// Account that submits and pays for the transaction.
+    pub source: String,
+    /// Sequence number of the transaction, i.e. the account's current
+    /// sequence number plus one.
+    pub sequence: i64,
+    /// Total fee in stroops, including the resource fee from simulation.
+    pub fee: u32,
+    pub network_passphrase: String,
+    pub contract: ScAddress,
+    /// Base64 `SorobanTransactionData` from simulation.
+    #[serde(default)]
+    pub soroban_data: Option<String>,
+    /// Base64 `SorobanAuthorizationEntry`s from simulation.
+    #[serde(default)]
+    pub auth: Vec<String>,
+    /// Required by `build`, left out for `import`.
+    #[serde(default)]
+    pub call: Option<Call>,
+}
+
+/// The contract calls the tool can build, keyed by entrypoint name.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Call {
+    Initialize {
+        reserve_acc: ScAddress,
+        dev_acc: ScAddress,
+        launchpad_acc: ScAddress,
+        admin: ScAddress,
+        signers: Vec<ScAddress>,
+        threshold: u32,
+    },
+    CreateProduct {
+        title: String,
+        description: String,
+        category: String,
+        expiry: u64,
+        image: String,
+        price: i128,
+        target: i128,
+    },
+    /// `metadata_hash` is the hex SHA-256 of the blob at `metadata_uri`.
+    CreateProductWithMetadata {
+        title: String,
+        category: String,
+        expiry: u64,
+        price: i128,
+        target: i128,
+        #[serde(deserialize_with = "hex_hash")]
+        metadata_hash: [u8; 32],
+        metadata_uri: String,
+    },
+    CreateProductsBatch {
+        products: Vec<ProductInput>,
+    },
+    CreateBundle {
+        title: String,
+        product_ids: Vec<u32>,
+        price: i128,
+    },
+    AddVariant {
+        product_id: u32,
+        name: String,
+        price_delta: i128,
+        stock: i128,
+    },
+    SetSalePhases {
+        product_id: u32,
+        phases: Vec<SalePhase>,
+    },
+    SetAllowlist {
+        product_id: u32,
+        accounts: Vec<ScAddress>,
+        allowed: bool,
+    },
+    SetReceiptMode {
+        product_id: u32,
+        mode: ReceiptMode,
+    },
+    SetVoucherValidity {
+        product_id: u32,
+        validity: u64,
+    },
+    DisableVouchers {
+        product_id: u32,
+    },
+    SetRedeemer {
+        product_id: u32,
+        redeemer: ScAddress,
+        allowed: bool,
+    },
+    Redeem {
+        redeemer: ScAddress,
+        voucher_id: u32,
+    },
+    /// Needs the reserve account's authorization as well as the admin's.
+    RefundVoucher {
+        voucher_id: u32,
+    },
+    ReleaseEscrow {
+        order_id: u32,
+    },
+    /// Signed by the reserve account; `response` is the hex SHA-256 of the
+    /// seller's statement.
+    RespondDispute {
+        order_id: u32,
+        #[serde(deserialize_with = "hex_hash")]
+        response: [u8; 32],
+    },
+    /// Signed by the arbiter.
+    ResolveDispute {
+        order_id: u32,
+        ruling: Ruling,
+    },
+    SetReserveAcc {
+        account: ScAddress,
+        approvers: Vec<ScAddress>,
+    },
+    SetDevAcc {
+        account: ScAddress,
+        approvers: Vec<ScAddress>,
+    },
+    SetLaunchpadAcc {
+        account: ScAddress,
+        approvers: Vec<ScAddress>,
+    },
+    SetPayoutMode {
+        mode: PayoutMode,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    SetProductLimits {
+        limits: ProductLimits,
+    },
+    SetReferralConfig {
+        config: ReferralConfig,
+    },
+    SetLoyaltyConfig {
+        config: LoyaltyConfig,
+    },
+    SetDisputeConfig {
+        config: DisputeConfig,
+    },
+    SetArbiter {
+        arbiter: ScAddress,
+    },
+    SetCoupon {
+        code: String,
+        coupon: Coupon,
+    },
+    RemoveCoupon {
+        code: String,
+    },
+    /// Split updates, account changes, vesting schedules and upgrades go
+    /// through a proposal.
+    Propose {
+        proposer: ScAddress,
+        action: ProposalAction,
+    },
+    Approve {
+        signer: ScAddress,
+        proposal_id: u32,
+    },
+    Execute {
+        proposal_id: u32,
+    },
+}
+
+impl Call {
+    pub fn host_function(&self, client: &Client) -> HostFunction {
+        match self {
+            Call::Initialize {
+                reserve_acc,
+                dev_acc,
+                launchpad_acc,
+                admin,
+                signers,
+                threshold,
+            } => client
+                .initialize(
+                    reserve_acc,
+                    dev_acc,
+                    launchpad_acc,
+                    admin,
+                    signers,
+                    *threshold,
+                )
+                .host_function(),
+            Call::CreateProduct {
+                title,
+                description,
+                category,
+                expiry,
+                image,
+                price,
+                target,
+            } => client
+                .create_product(
+                    title,
+                    description,
+                    category,
+                    *expiry,
+                    image,
+                    *price,
+                    *target,
+                )
+                .host_function(),
+            Call::CreateProductWithMetadata {
+                title,
+                category,
+                expiry,
+                price,
+                target,
+                metadata_hash,
+                metadata_uri,
+            } => client
+                .create_product_with_metadata(
+                    title,
+                    category,
+                    *expiry,
+                    *price,
+                    *target,
+                    &ProductMetadata {
+                        hash: *metadata_hash,
+                        uri: metadata_uri.clone(),
+                    },
+                )
+                .host_function(),
+            Call::CreateProductsBatch { products } => {
+                client.create_products_batch(products).host_function()
+            }
+            Call::CreateBundle {
+                title,
+                product_ids,
+                price,
+            } => client
+                .create_bundle(title, product_ids, *price)
+                .host_function(),
+            Call::AddVariant {
+                product_id,
+                name,
+                price_delta,
+                stock,
+            } => client
+                .add_variant(*product_id, name, *price_delta, *stock)
+                .host_function(),
+            Call::SetSalePhases { product_id, phases } => {
+                client.set_sale_phases(*product_id, phases).host_function()
+            }
+            Call::SetAllowlist {
+                product_id,
+                accounts,
+                allowed,
+            } => client
+                .set_allowlist(*product_id, accounts, *allowed)
+                .host_function(),
+            Call::SetReceiptMode { product_id, mode } => {
+                client.set_receipt_mode(*product_id, *mode).host_function()
+            }
+            Call::SetVoucherValidity {
+                product_id,
+                validity,
+            } => client
+                .set_voucher_validity(*product_id, *validity)
+                .host_function(),
+            Call::DisableVouchers { product_id } => {
+                client.disable_vouchers(*product_id).host_function()
+            }
+            Call::SetRedeemer {
+                product_id,
+                redeemer,
+                allowed,
+            } => client
+                .set_redeemer(*product_id, redeemer, *allowed)
+                .host_function(),
+            Call::Redeem {
+                redeemer,
+                voucher_id,
+            } => client.redeem(redeemer, *voucher_id).host_function(),
+            Call::RefundVoucher { voucher_id } => {
+                client.refund_voucher(*voucher_id).host_function()
+            }
+            Call::ReleaseEscrow { order_id } => client.release_escrow(*order_id).host_function(),
+            Call::RespondDispute { order_id, response } => {
+                client.respond_dispute(*order_id, response).host_function()
+            }
+            Call::ResolveDispute { order_id, ruling } => {
+                client.resolve_dispute(*order_id, ruling).host_function()
+            }
+            Call::SetReserveAcc { account, approvers } => {
+                client.set_reserve_acc(account, approvers).host_function()
+            }
+            Call::SetDevAcc { account, approvers } => {
+                client.set_dev_acc(account, approvers).host_function()
+            }
+            Call::SetLaunchpadAcc { account, approvers } => {
+                client.set_launchpad_acc(account, approvers).host_function()
+            }
+            Call::SetPayoutMode { mode } => client.set_payout_mode(*mode).host_function(),
+            Call::SetPaused { paused } => client.set_paused(*paused).host_function(),
+            Call::SetProductLimits { limits } => client.set_product_limits(limits).host_function(),
+            Call::SetReferralConfig { config } => {
+                client.set_referral_config(config).host_function()
+            }
+            Call::SetLoyaltyConfig { config } => client.set_loyalty_config(config).host_function(),
+            Call::SetDisputeConfig { config } => client.set_dispute_config(config).host_function(),
+            Call::SetArbiter { arbiter } => client.set_arbiter(arbiter).host_function(),
+            Call::SetCoupon { code, coupon } => client.set_coupon(code, coupon).host_function(),
+            Call::RemoveCoupon { code } => client.remove_coupon(code).host_function(),
+            Call::Propose { proposer, action } => client.propose(proposer, action).host_function(),
+            Call::Approve {
+                signer,
+                proposal_id,
+            } => client.approve(signer, *proposal_id).host_function(),
+            Call::Execute { proposal_id } => client.execute(*proposal_id).host_function(),
+        }
+    }
+}
+
+fn hex_hash<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<[u8; 32], D::Error> {
+    let text = String::deserialize(deserializer)?;
+    let invalid = || serde::de::Error::custom("expected 64 hex digits");
+    if text.len() != 64 || !text.is_ascii() {
+        return Err(invalid());
+    }
+    let mut hash = [0; 32];
+    for (byte, pair) in hash.iter_mut().zip(text.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+    }
+    Ok(hash)
+}
+
+/// Parses `text` as TOML or JSON depending on the extension of `path`.
+pub fn parse<T: serde::de::DeserializeOwned>(path: &Path, text: &str) -> Result<T> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        // toml cannot deserialize `i128` directly, so go through a JSON value.
+        Some("toml") => Ok(serde_json::from_value(toml::from_str(text)?)?),
+        Some("json") => Ok(serde_json::from_str(text)?),
+        _ => Err(format!("{}: expected a .toml or .json file", path.display()).into()),
+    }
+}
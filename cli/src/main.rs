@@ -0,0 +1,86 @@
+//! `marketplace-cli`: builds marketplace transactions and decodes contract
+//! values without touching the network.
+//!
+//! ```text
+//! marketplace-cli build create_product.toml
+//! marketplace-cli import tx.toml catalog.csv
+//! marketplace-cli decode product AAAAEQAAAAEAAAAI...
+//! marketplace-cli decode event AAAAAAAAAAEAAAAB...
+//! ```
+//!
+//! Output is JSON on stdout.
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+mod catalog;
+mod decode;
+mod input;
+mod tx;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(name = "marketplace-cli", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build an unsigned transaction envelope from a TOML or JSON file.
+    Build { file: PathBuf },
+    /// Build `create_products_batch` envelopes for a CSV or JSON catalog,
+    /// using the transaction settings in `file`.
+    Import { file: PathBuf, catalog: PathBuf },
+    /// Decode base64 XDR.
+    Decode {
+        #[command(subcommand)]
+        what: Decode,
+    },
+}
+
+#[derive(Subcommand)]
+enum Decode {
+    /// A `Product` return value (`ScVal`).
+    Product { xdr: String },
+    /// A `ContractEvent` or `DiagnosticEvent`.
+    Event { xdr: String },
+}
+
+fn run(cli: Cli) -> Result<String> {
+    let output = match cli.command {
+        Command::Build { file } => {
+            let text = std::fs::read_to_string(&file)?;
+            let input: input::TxInput = input::parse(&file, &text)?;
+            serde_json::to_string_pretty(&tx::build(&input)?)?
+        }
+        Command::Import { file, catalog } => {
+            let input: input::TxInput = input::parse(&file, &std::fs::read_to_string(&file)?)?;
+            let products = catalog::parse(&catalog, &std::fs::read_to_string(&catalog)?)?;
+            serde_json::to_string_pretty(&tx::import(&input, &products)?)?
+        }
+        Command::Decode {
+            what: Decode::Product { xdr },
+        } => serde_json::to_string_pretty(&decode::product(&xdr)?)?,
+        Command::Decode {
+            what: Decode::Event { xdr },
+        } => serde_json::to_string_pretty(&decode::event(&xdr)?)?,
+    };
+    Ok(output)
+}
+
+fn main() {
+    match run(Cli::parse()) {
+        Ok(output) => println!("{output}"),
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
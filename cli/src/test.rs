@@ -0,0 +1,346 @@
+use std::path::Path;
+
+use marketplace_sdk::stellar_xdr::curr::{
+    AccountId, ContractEvent, ContractEventBody, ContractEventType, ContractEventV0,
+    ExtensionPoint, Hash, HostFunction, LedgerFootprint, Limits, OperationBody, PublicKey, ReadXdr,
+    ScAddress, ScMap, ScVal, SorobanResources, SorobanTransactionData, TransactionEnvelope,
+    TransactionExt, Uint256, WriteXdr,
+};
+use marketplace_sdk::{
+    Client, Event, Product, ProductInput, ProductMetadata, ProposalAction, Split, ToScVal,
+    MAX_BATCH_SIZE,
+};
+
+use super::*;
+
+fn account(n: u8) -> ScAddress {
+    ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([n; 32]))))
+}
+
+fn contract() -> ScAddress {
+    ScAddress::Contract(Hash([9; 32]))
+}
+
+fn propose_split_toml() -> String {
+    format!(
+        r#"
+source = "{source}"
+sequence = 4294967297
+fee = 1000000
+network_passphrase = "Test SDF Network ; September 2015"
+contract = "{contract}"
+
+[call.propose]
+proposer = "{source}"
+action = {{ SetSplit = {{ reserve = 50, launchpad = 20, dev = 30 }} }}
+"#,
+        source = account(1),
+        contract = contract(),
+    )
+}
+
+fn propose_split_json() -> String {
+    format!(
+        r#"{{
+  "source": "{source}",
+  "sequence": 4294967297,
+  "fee": 1000000,
+  "network_passphrase": "Test SDF Network ; September 2015",
+  "contract": "{contract}",
+  "call": {{
+    "propose": {{
+      "proposer": "{source}",
+      "action": {{ "SetSplit": {{ "reserve": 50, "launchpad": 20, "dev": 30 }} }}
+    }}
+  }}
+}}"#,
+        source = account(1),
+        contract = contract(),
+    )
+}
+
+fn envelope_args(built: &tx::Built) -> (TransactionEnvelope, HostFunction) {
+    let envelope =
+        TransactionEnvelope::from_xdr_base64(&built.envelope_xdr, Limits::none()).unwrap();
+    let TransactionEnvelope::Tx(v1) = &envelope else {
+        panic!("expected a v1 envelope");
+    };
+    assert!(v1.signatures.is_empty());
+    let OperationBody::InvokeHostFunction(op) = &v1.tx.operations[0].body else {
+        panic!("expected an invoke host function operation");
+    };
+    let host_function = op.host_function.clone();
+    (envelope, host_function)
+}
+
+#[test]
+fn build_from_toml_and_json() {
+    let from_toml: input::TxInput =
+        input::parse(Path::new("split.toml"), &propose_split_toml()).unwrap();
+    let from_json: input::TxInput =
+        input::parse(Path::new("split.json"), &propose_split_json()).unwrap();
+    assert_eq!(from_toml, from_json);
+
+    let built = tx::build(&from_toml).unwrap();
+    assert_eq!(built, tx::build(&from_json).unwrap());
+
+    let (envelope, host_function) = envelope_args(&built);
+    let expected = Client::new(contract()).propose(
+        &account(1),
+        &ProposalAction::SetSplit(Split {
+            reserve: 50,
+            launchpad: 20,
+            dev: 30,
+        }),
+    );
+    assert_eq!(host_function, expected.host_function());
+
+    let TransactionEnvelope::Tx(v1) = envelope else {
+        unreachable!()
+    };
+    assert_eq!(v1.tx.seq_num.0, 4294967297);
+    assert_eq!(v1.tx.fee, 1000000);
+    assert_eq!(v1.tx.ext, TransactionExt::V0);
+    let hash = tx::hash(&v1.tx, "Test SDF Network ; September 2015").unwrap();
+    assert_eq!(
+        built.hash,
+        hash.iter().map(|b| format!("{b:02x}")).collect::<String>()
+    );
+    assert_ne!(
+        hash,
+        tx::hash(&v1.tx, "Public Global Stellar Network ; September 2015").unwrap()
+    );
+}
+
+#[test]
+fn build_pause_with_simulation_data() {
+    let soroban_data = SorobanTransactionData {
+        ext: ExtensionPoint::V0,
+        resources: SorobanResources {
+            footprint: LedgerFootprint {
+                read_only: Default::default(),
+                read_write: Default::default(),
+            },
+            instructions: 1000,
+            read_bytes: 0,
+            write_bytes: 0,
+        },
+        resource_fee: 50,
+    };
+    let text = format!(
+        r#"
+source = "{source}"
+sequence = 2
+fee = 100
+network_passphrase = "Test SDF Network ; September 2015"
+contract = "{contract}"
+soroban_data = "{data}"
+
+[call.set_paused]
+paused = true
+"#,
+        source = account(1),
+        contract = contract(),
+        data = soroban_data.to_xdr_base64(Limits::none()).unwrap(),
+    );
+    let input: input::TxInput = input::parse(Path::new("pause.toml"), &text).unwrap();
+    let (envelope, host_function) = envelope_args(&tx::build(&input).unwrap());
+
+    assert_eq!(
+        host_function,
+        Client::new(contract()).set_paused(true).host_function()
+    );
+    let TransactionEnvelope::Tx(v1) = envelope else {
+        unreachable!()
+    };
+    assert_eq!(v1.tx.ext, TransactionExt::V1(soroban_data));
+}
+
+#[test]
+fn build_product_with_metadata() {
+    let hash = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+    let text = format!(
+        r#"{{
+  "source": "{source}",
+  "sequence": 3,
+  "fee": 100,
+  "network_passphrase": "Test SDF Network ; September 2015",
+  "contract": "{contract}",
+  "call": {{
+    "create_product_with_metadata": {{
+      "title": "Hat",
+      "category": "Clothes",
+      "expiry": 1767225600,
+      "price": 500,
+      "target": 3,
+      "metadata_hash": "{hash}",
+      "metadata_uri": "ipfs://hat"
+    }}
+  }}
+}}"#,
+        source = account(1),
+        contract = contract(),
+    );
+    let input: input::TxInput = input::parse(Path::new("hat.json"), &text).unwrap();
+    let (_, host_function) = envelope_args(&tx::build(&input).unwrap());
+    let mut expected = [0; 32];
+    for (i, byte) in expected.iter_mut().enumerate() {
+        *byte = (i as u8 % 16) * 0x11;
+    }
+    assert_eq!(
+        host_function,
+        Client::new(contract())
+            .create_product_with_metadata(
+                "Hat",
+                "Clothes",
+                1767225600,
+                500,
+                3,
+                &ProductMetadata {
+                    hash: expected,
+                    uri: "ipfs://hat".into(),
+                },
+            )
+            .host_function()
+    );
+
+    let short = text.replace(hash, "0011");
+    assert!(input::parse::<input::TxInput>(Path::new("hat.json"), &short).is_err());
+    let not_hex = text.replace(hash, &"zz".repeat(32));
+    assert!(input::parse::<input::TxInput>(Path::new("hat.json"), &not_hex).is_err());
+}
+
+#[test]
+fn rejects_bad_input() {
+    assert!(input::parse::<input::TxInput>(Path::new("split.yaml"), "").is_err());
+
+    let unknown = propose_split_toml().replace("call.propose", "call.burn");
+    assert!(input::parse::<input::TxInput>(Path::new("split.toml"), &unknown).is_err());
+
+    let bad_address = propose_split_toml().replace(&contract().to_string(), "CNOTANADDRESS");
+    assert!(input::parse::<input::TxInput>(Path::new("split.toml"), &bad_address).is_err());
+
+    let no_call: input::TxInput = input::parse(Path::new("tx.toml"), &import_toml()).unwrap();
+    assert!(tx::build(&no_call).is_err());
+}
+
+fn import_toml() -> String {
+    format!(
+        r#"
+source = "{source}"
+sequence = 10
+fee = 5000000
+network_passphrase = "Test SDF Network ; September 2015"
+contract = "{contract}"
+"#,
+        source = account(1),
+        contract = contract(),
+    )
+}
+
+#[test]
+fn import_catalog_in_batches() {
+    let csv_text = std::iter::once("title,description,category,expiry,image,price,target".into())
+        .chain((0..MAX_BATCH_SIZE + 1).map(|i| {
+            format!(
+                "Item {i},\"Item {i}, boxed\",Goods,1767225600,item{i}.png,{},3",
+                100 + i
+            )
+        }))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let products = catalog::parse(Path::new("catalog.csv"), &csv_text).unwrap();
+    assert_eq!(products.len(), MAX_BATCH_SIZE + 1);
+    assert_eq!(
+        products[1],
+        ProductInput {
+            title: "Item 1".into(),
+            description: "Item 1, boxed".into(),
+            category: "Goods".into(),
+            expiry: 1767225600,
+            image: "item1.png".into(),
+            price: 101,
+            target: 3,
+        }
+    );
+    let json_text = serde_json::to_string(&products).unwrap();
+    assert_eq!(
+        catalog::parse(Path::new("catalog.json"), &json_text).unwrap(),
+        products
+    );
+
+    let input: input::TxInput = input::parse(Path::new("tx.toml"), &import_toml()).unwrap();
+    let built = tx::import(&input, &products).unwrap();
+    assert_eq!(built.len(), 2);
+    let client = Client::new(contract());
+    for (i, (built, batch)) in built
+        .iter()
+        .zip(products.chunks(MAX_BATCH_SIZE))
+        .enumerate()
+    {
+        let (envelope, host_function) = envelope_args(built);
+        assert_eq!(
+            host_function,
+            client.create_products_batch(batch).host_function()
+        );
+        let TransactionEnvelope::Tx(v1) = envelope else {
+            unreachable!()
+        };
+        assert_eq!(v1.tx.seq_num.0, 10 + i as i64);
+    }
+
+    let with_call = format!("{}\n[call.set_paused]\npaused = true\n", import_toml());
+    let input: input::TxInput = input::parse(Path::new("tx.toml"), &with_call).unwrap();
+    assert!(tx::import(&input, &products).is_err());
+    assert!(catalog::parse(Path::new("catalog.csv"), "title,price\nHat,five").is_err());
+}
+
+#[test]
+fn decodes_products_and_events() {
+    let product = Product {
+        id: 3,
+        title: "Hat".into(),
+        description: "A hat".into(),
+        category: "Clothes".into(),
+        expiry: 1_700_000_000,
+        image: "hat.png".into(),
+        price: 500,
+        remaining: 7,
+    };
+    let xdr = product.to_scval().to_xdr_base64(Limits::none()).unwrap();
+    assert_eq!(decode::product(&xdr).unwrap(), product);
+
+    let symbol = |name: &str| ScVal::Symbol(name.try_into().unwrap());
+    let event = ContractEvent {
+        ext: ExtensionPoint::V0,
+        contract_id: Some(Hash([9; 32])),
+        type_: ContractEventType::Contract,
+        body: ContractEventBody::V0(ContractEventV0 {
+            topics: vec![symbol("market"), symbol("v1"), symbol("acct_set")]
+                .try_into()
+                .unwrap(),
+            data: ScVal::Map(Some(
+                ScMap::sorted_from_pairs(
+                    [
+                        (symbol("role"), symbol("dev")),
+                        (symbol("old"), account(1).to_scval()),
+                        (symbol("new"), account(2).to_scval()),
+                    ]
+                    .into_iter(),
+                )
+                .unwrap(),
+            )),
+        }),
+    };
+    let decoded = decode::event(&event.to_xdr_base64(Limits::none()).unwrap()).unwrap();
+    let Event::AccountChanged(changed) = &decoded else {
+        panic!("expected an account change, got {decoded:?}");
+    };
+    assert_eq!(changed.role, "dev");
+    assert_eq!(changed.new, account(2));
+
+    let json = serde_json::to_string(&decoded).unwrap();
+    assert!(json.contains(&account(2).to_string()), "{json}");
+
+    assert!(decode::product("not base64").is_err());
+}
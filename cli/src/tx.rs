@@ -0,0 +1,105 @@
+//! Transaction envelopes for a single contract call.
+
+use marketplace_sdk::stellar_xdr::curr::{
+    Hash, HostFunction, InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation, OperationBody,
+    Preconditions, ReadXdr, SequenceNumber, SorobanAuthorizationEntry, SorobanTransactionData,
+    Transaction, TransactionEnvelope, TransactionExt, TransactionSignaturePayload,
+    TransactionSignaturePayloadTaggedTransaction, TransactionV1Envelope, WriteXdr,
+};
+use marketplace_sdk::{Client, ProductInput};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::input::TxInput;
+use crate::Result;
+
+/// An unsigned envelope and the hash its signers sign.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Built {
+    pub hash: String,
+    pub envelope_xdr: String,
+}
+
+pub fn transaction(
+    input: &TxInput,
+    host_function: HostFunction,
+    sequence: i64,
+) -> Result<Transaction> {
+    let auth = input
+        .auth
+        .iter()
+        .map(|entry| SorobanAuthorizationEntry::from_xdr_base64(entry, Limits::none()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let ext = match &input.soroban_data {
+        Some(data) => TransactionExt::V1(SorobanTransactionData::from_xdr_base64(
+            data,
+            Limits::none(),
+        )?),
+        None => TransactionExt::V0,
+    };
+
+    let operation = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function,
+            auth: auth.try_into()?,
+        }),
+    };
+    Ok(Transaction {
+        source_account: input.source.parse::<MuxedAccount>()?,
+        fee: input.fee,
+        seq_num: SequenceNumber(sequence),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![operation].try_into()?,
+        ext,
+    })
+}
+
+/// The hash signers sign: the transaction tagged with the network id.
+pub fn hash(tx: &Transaction, network_passphrase: &str) -> Result<[u8; 32]> {
+    let payload = TransactionSignaturePayload {
+        network_id: Hash(Sha256::digest(network_passphrase.as_bytes()).into()),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+    };
+    Ok(Sha256::digest(payload.to_xdr(Limits::none())?).into())
+}
+
+fn envelope(input: &TxInput, host_function: HostFunction, sequence: i64) -> Result<Built> {
+    let tx = transaction(input, host_function, sequence)?;
+    let hash = hash(&tx, &input.network_passphrase)?;
+    let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: Default::default(),
+    });
+    Ok(Built {
+        hash: hash.iter().map(|b| format!("{b:02x}")).collect(),
+        envelope_xdr: envelope.to_xdr_base64(Limits::none())?,
+    })
+}
+
+pub fn build(input: &TxInput) -> Result<Built> {
+    let call = input.call.as_ref().ok_or("missing [call]")?;
+    let client = Client::new(input.contract.clone());
+    envelope(input, call.host_function(&client), input.sequence)
+}
+
+/// One `create_products_batch` transaction per batch of `products`, with
+/// consecutive sequence numbers starting at `input.sequence`.
+///
+/// Simulation data describes a single transaction, so `input` must not carry
+/// any; simulate each envelope before signing it.
+pub fn import(input: &TxInput, products: &[ProductInput]) -> Result<Vec<Built>> {
+    if input.call.is_some() {
+        return Err("import builds its own calls; remove [call]".into());
+    }
+    if input.soroban_data.is_some() || !input.auth.is_empty() {
+        return Err("import doesn't take soroban_data or auth; simulate each batch".into());
+    }
+    Client::new(input.contract.clone())
+        .import_catalog(products)
+        .into_iter()
+        .zip(input.sequence..)
+        .map(|(batch, sequence)| envelope(input, batch.host_function(), sequence))
+        .collect()
+}
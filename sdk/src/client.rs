@@ -0,0 +1,943 @@
+//! Invocation builders, one per contract entrypoint.
+
+use std::marker::PhantomData;
+
+use stellar_xdr::curr::{
+    HostFunction, InvokeContractArgs, Limits, ScAddress, ScSymbol, ScVal, WriteXdr,
+};
+
+use crate::scval::{bytes, symbol, DecodeError, FromScVal, ToScVal};
+use crate::types::{
+    Bundle, Coupon, Dispute, DisputeConfig, Escrow, LoyaltyConfig, Order, PayoutMode, Product,
+    ProductInput, ProductLimits, ProductMetadata, ProductView, Proposal, ProposalAction, Rating,
+    Receipt, ReceiptMode, ReferralConfig, Review, Ruling, SalePhase, Split, Variant,
+    VestingSchedule, Voucher, MAX_BATCH_SIZE,
+};
+
+/// Builds invocations of a deployed marketplace contract.
+///
+/// Nothing here talks to the network: each method returns an [`Invocation`]
+/// that can be turned into XDR for a transaction and that knows how to decode
+/// the entrypoint's return value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Client {
+    pub contract: ScAddress,
+}
+
+/// A call to one entrypoint returning `T`.
+///
+/// Entrypoints that return `Result<T, Error>` on chain fail the invocation
+/// with `ScError::Contract(code)` instead of returning a value; map it back
+/// with [`Error::from_sc_error`](crate::Error::from_sc_error), or
+/// [`OrderError::from_sc_error`](crate::OrderError::from_sc_error) for
+/// entrypoints that return `OrderError`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invocation<T> {
+    args: InvokeContractArgs,
+    result: PhantomData<fn() -> T>,
+}
+
+impl<T: FromScVal> Invocation<T> {
+    fn new(contract: &ScAddress, function: &str, args: Vec<ScVal>) -> Self {
+        Invocation {
+            args: InvokeContractArgs {
+                contract_address: contract.clone(),
+                function_name: ScSymbol(function.try_into().expect("function name is a symbol")),
+                args: args.try_into().expect("at most 10 arguments"),
+            },
+            result: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn args(&self) -> &InvokeContractArgs {
+        &self.args
+    }
+
+    #[must_use]
+    pub fn into_args(self) -> InvokeContractArgs {
+        self.args
+    }
+
+    #[must_use]
+    pub fn host_function(&self) -> HostFunction {
+        HostFunction::InvokeContract(self.args.clone())
+    }
+
+    /// The host function as base64 XDR, ready to go into an
+    /// `InvokeHostFunctionOp`.
+    pub fn to_xdr_base64(&self) -> Result<String, stellar_xdr::curr::Error> {
+        self.host_function().to_xdr_base64(Limits::none())
+    }
+
+    pub fn decode_result(&self, val: &ScVal) -> Result<T, DecodeError> {
+        T::from_scval(val)
+    }
+}
+
+impl Client {
+    #[must_use]
+    pub fn new(contract: ScAddress) -> Self {
+        Client { contract }
+    }
+
+    fn invoke<T: FromScVal>(&self, function: &str, args: Vec<ScVal>) -> Invocation<T> {
+        Invocation::new(&self.contract, function, args)
+    }
+
+    #[must_use]
+    pub fn initialize(
+        &self,
+        reserve_acc: &ScAddress,
+        dev_acc: &ScAddress,
+        launchpad_acc: &ScAddress,
+        admin: &ScAddress,
+        signers: &[ScAddress],
+        threshold: u32,
+    ) -> Invocation<String> {
+        self.invoke(
+            "initialize",
+            vec![
+                reserve_acc.to_scval(),
+                dev_acc.to_scval(),
+                launchpad_acc.to_scval(),
+                admin.to_scval(),
+                signers.to_scval(),
+                threshold.to_scval(),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn create_product(
+        &self,
+        title: &str,
+        description: &str,
+        category: &str,
+        expiry: u64,
+        image: &str,
+        price: i128,
+        target: i128,
+    ) -> Invocation<Product> {
+        self.invoke(
+            "create_product",
+            vec![
+                title.to_scval(),
+                description.to_scval(),
+                category.to_scval(),
+                expiry.to_scval(),
+                image.to_scval(),
+                price.to_scval(),
+                target.to_scval(),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn create_product_with_metadata(
+        &self,
+        title: &str,
+        category: &str,
+        expiry: u64,
+        price: i128,
+        target: i128,
+        metadata: &ProductMetadata,
+    ) -> Invocation<Product> {
+        self.invoke(
+            "create_product_with_metadata",
+            vec![
+                title.to_scval(),
+                category.to_scval(),
+                expiry.to_scval(),
+                price.to_scval(),
+                target.to_scval(),
+                metadata.to_scval(),
+            ],
+        )
+    }
+
+    /// Creates all of `products` in one call; at most [`MAX_BATCH_SIZE`].
+    #[must_use]
+    pub fn create_products_batch(&self, products: &[ProductInput]) -> Invocation<Vec<Product>> {
+        self.invoke("create_products_batch", vec![products.to_scval()])
+    }
+
+    /// Splits a catalog of any size into `create_products_batch` calls, to be
+    /// submitted in order so products get ids in catalog order.
+    #[must_use]
+    pub fn import_catalog(&self, products: &[ProductInput]) -> Vec<Invocation<Vec<Product>>> {
+        products
+            .chunks(MAX_BATCH_SIZE)
+            .map(|batch| self.create_products_batch(batch))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn get_products(&self) -> Invocation<Vec<Product>> {
+        self.invoke("get_products", vec![])
+    }
+
+    #[must_use]
+    pub fn export_products(&self) -> Invocation<Vec<ProductInput>> {
+        self.invoke("export_products", vec![])
+    }
+
+    #[must_use]
+    pub fn get_product(&self, product_id: u32) -> Invocation<Product> {
+        self.invoke("get_product", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_product_view(&self, product_id: u32) -> Invocation<ProductView> {
+        self.invoke("get_product_view", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_product_views(&self) -> Invocation<Vec<ProductView>> {
+        self.invoke("get_product_views", vec![])
+    }
+
+    /// The product's off-chain metadata URI and content hash, if it has any.
+    #[must_use]
+    pub fn get_product_metadata(&self, product_id: u32) -> Invocation<Option<ProductMetadata>> {
+        self.invoke("get_product_metadata", vec![product_id.to_scval()])
+    }
+
+    /// Checks a blob fetched from the metadata URI against the stored hash.
+    #[must_use]
+    pub fn verify_metadata(&self, product_id: u32, data: &[u8]) -> Invocation<bool> {
+        self.invoke("verify_metadata", vec![product_id.to_scval(), bytes(data)])
+    }
+
+    /// Buys one unit of a product for `amount` whole tokens; a `referrer`
+    /// earns the referral commission out of the split.
+    #[must_use]
+    pub fn get_discount(
+        &self,
+        product_id: u32,
+        customer: &ScAddress,
+        amount: i128,
+        token_id: &ScAddress,
+        referrer: Option<&ScAddress>,
+    ) -> Invocation<(i128, i128, i128)> {
+        self.invoke(
+            "get_discount",
+            vec![
+                product_id.to_scval(),
+                customer.to_scval(),
+                amount.to_scval(),
+                token_id.to_scval(),
+                referrer.map_or(ScVal::Void, ToScVal::to_scval),
+            ],
+        )
+    }
+
+    /// Like `get_discount`, spending `points` of the buyer's loyalty balance
+    /// for a discount.
+    #[must_use]
+    pub fn purchase_with_points(
+        &self,
+        product_id: u32,
+        customer: &ScAddress,
+        amount: i128,
+        token_id: &ScAddress,
+        points: i128,
+        referrer: Option<&ScAddress>,
+    ) -> Invocation<(i128, i128, i128)> {
+        self.invoke(
+            "purchase_with_points",
+            vec![
+                product_id.to_scval(),
+                customer.to_scval(),
+                amount.to_scval(),
+                token_id.to_scval(),
+                points.to_scval(),
+                referrer.map_or(ScVal::Void, ToScVal::to_scval),
+            ],
+        )
+    }
+
+    /// Buys during a Merkle-gated sale phase; `proof` holds the sibling
+    /// hashes from the buyer's leaf up to the root, as built by
+    /// [`crate::merkle::proof`].
+    #[must_use]
+    pub fn purchase_with_proof(
+        &self,
+        product_id: u32,
+        customer: &ScAddress,
+        amount: i128,
+        token_id: &ScAddress,
+        proof: &[[u8; 32]],
+        referrer: Option<&ScAddress>,
+    ) -> Invocation<(i128, i128, i128)> {
+        self.invoke(
+            "purchase_with_proof",
+            vec![
+                product_id.to_scval(),
+                customer.to_scval(),
+                amount.to_scval(),
+                token_id.to_scval(),
+                proof.to_scval(),
+                referrer.map_or(ScVal::Void, ToScVal::to_scval),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn set_sale_phases(&self, product_id: u32, phases: &[SalePhase]) -> Invocation<()> {
+        self.invoke(
+            "set_sale_phases",
+            vec![product_id.to_scval(), phases.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_sale_phases(&self, product_id: u32) -> Invocation<Vec<SalePhase>> {
+        self.invoke("get_sale_phases", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_phase_sold(&self, product_id: u32, index: u32) -> Invocation<i128> {
+        self.invoke(
+            "get_phase_sold",
+            vec![product_id.to_scval(), index.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn set_allowlist(
+        &self,
+        product_id: u32,
+        accounts: &[ScAddress],
+        allowed: bool,
+    ) -> Invocation<()> {
+        self.invoke(
+            "set_allowlist",
+            vec![
+                product_id.to_scval(),
+                accounts.to_scval(),
+                allowed.to_scval(),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn is_allowlisted(&self, product_id: u32, account: &ScAddress) -> Invocation<bool> {
+        self.invoke(
+            "is_allowlisted",
+            vec![product_id.to_scval(), account.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn set_receipt_mode(&self, product_id: u32, mode: ReceiptMode) -> Invocation<()> {
+        self.invoke(
+            "set_receipt_mode",
+            vec![product_id.to_scval(), mode.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_receipt_mode(&self, product_id: u32) -> Invocation<ReceiptMode> {
+        self.invoke("get_receipt_mode", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_receipt(&self, receipt_id: u32) -> Invocation<Receipt> {
+        self.invoke("get_receipt", vec![receipt_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn owner_of(&self, receipt_id: u32) -> Invocation<ScAddress> {
+        self.invoke("owner_of", vec![receipt_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn transfer_receipt(
+        &self,
+        from: &ScAddress,
+        to: &ScAddress,
+        receipt_id: u32,
+    ) -> Invocation<()> {
+        self.invoke(
+            "transfer_receipt",
+            vec![from.to_scval(), to.to_scval(), receipt_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn transfer_receipt_from(
+        &self,
+        spender: &ScAddress,
+        from: &ScAddress,
+        to: &ScAddress,
+        receipt_id: u32,
+    ) -> Invocation<()> {
+        self.invoke(
+            "transfer_receipt_from",
+            vec![
+                spender.to_scval(),
+                from.to_scval(),
+                to.to_scval(),
+                receipt_id.to_scval(),
+            ],
+        )
+    }
+
+    /// `None` clears the receipt's approval.
+    #[must_use]
+    pub fn approve_receipt(
+        &self,
+        owner: &ScAddress,
+        receipt_id: u32,
+        spender: Option<&ScAddress>,
+    ) -> Invocation<()> {
+        self.invoke(
+            "approve_receipt",
+            vec![
+                owner.to_scval(),
+                receipt_id.to_scval(),
+                spender.map_or(ScVal::Void, ToScVal::to_scval),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn get_receipt_approval(&self, receipt_id: u32) -> Invocation<Option<ScAddress>> {
+        self.invoke("get_receipt_approval", vec![receipt_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn set_voucher_validity(&self, product_id: u32, validity: u64) -> Invocation<()> {
+        self.invoke(
+            "set_voucher_validity",
+            vec![product_id.to_scval(), validity.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn disable_vouchers(&self, product_id: u32) -> Invocation<()> {
+        self.invoke("disable_vouchers", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_voucher_validity(&self, product_id: u32) -> Invocation<Option<u64>> {
+        self.invoke("get_voucher_validity", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn set_redeemer(
+        &self,
+        product_id: u32,
+        redeemer: &ScAddress,
+        allowed: bool,
+    ) -> Invocation<()> {
+        self.invoke(
+            "set_redeemer",
+            vec![
+                product_id.to_scval(),
+                redeemer.to_scval(),
+                allowed.to_scval(),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn is_redeemer(&self, product_id: u32, account: &ScAddress) -> Invocation<bool> {
+        self.invoke(
+            "is_redeemer",
+            vec![product_id.to_scval(), account.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_voucher(&self, voucher_id: u32) -> Invocation<Voucher> {
+        self.invoke("get_voucher", vec![voucher_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_voucher_count(&self, product_id: u32) -> Invocation<u32> {
+        self.invoke("get_voucher_count", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_outstanding_vouchers(
+        &self,
+        product_id: u32,
+        start: u32,
+        limit: u32,
+    ) -> Invocation<Vec<Voucher>> {
+        self.invoke(
+            "get_outstanding_vouchers",
+            vec![product_id.to_scval(), start.to_scval(), limit.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn redeem(&self, redeemer: &ScAddress, voucher_id: u32) -> Invocation<()> {
+        self.invoke("redeem", vec![redeemer.to_scval(), voucher_id.to_scval()])
+    }
+
+    /// Needs the reserve account's authorization as well as the admin's.
+    #[must_use]
+    pub fn refund_voucher(&self, voucher_id: u32) -> Invocation<i128> {
+        self.invoke("refund_voucher", vec![voucher_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_points(&self, address: &ScAddress) -> Invocation<i128> {
+        self.invoke("get_points", vec![address.to_scval()])
+    }
+
+    #[must_use]
+    pub fn add_variant(
+        &self,
+        product_id: u32,
+        name: &str,
+        price_delta: i128,
+        stock: i128,
+    ) -> Invocation<Variant> {
+        self.invoke(
+            "add_variant",
+            vec![
+                product_id.to_scval(),
+                name.to_scval(),
+                price_delta.to_scval(),
+                stock.to_scval(),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn get_variants(&self, product_id: u32) -> Invocation<Vec<Variant>> {
+        self.invoke("get_variants", vec![product_id.to_scval()])
+    }
+
+    /// Buys one unit of a variant at its price; returns the reserve,
+    /// launchpad and dev shares like `get_discount`.
+    #[must_use]
+    pub fn purchase_variant(
+        &self,
+        product_id: u32,
+        variant_id: u32,
+        customer: &ScAddress,
+        token_id: &ScAddress,
+        referrer: Option<&ScAddress>,
+    ) -> Invocation<(i128, i128, i128)> {
+        self.invoke(
+            "purchase_variant",
+            vec![
+                product_id.to_scval(),
+                variant_id.to_scval(),
+                customer.to_scval(),
+                token_id.to_scval(),
+                referrer.map_or(ScVal::Void, ToScVal::to_scval),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn create_bundle(
+        &self,
+        title: &str,
+        product_ids: &[u32],
+        price: i128,
+    ) -> Invocation<Bundle> {
+        self.invoke(
+            "create_bundle",
+            vec![title.to_scval(), product_ids.to_scval(), price.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_bundle(&self, bundle_id: u32) -> Invocation<Option<Bundle>> {
+        self.invoke("get_bundle", vec![bundle_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn purchase_bundle(
+        &self,
+        bundle_id: u32,
+        customer: &ScAddress,
+        token_id: &ScAddress,
+        referrer: Option<&ScAddress>,
+    ) -> Invocation<(i128, i128, i128)> {
+        self.invoke(
+            "purchase_bundle",
+            vec![
+                bundle_id.to_scval(),
+                customer.to_scval(),
+                token_id.to_scval(),
+                referrer.map_or(ScVal::Void, ToScVal::to_scval),
+            ],
+        )
+    }
+
+    /// Buys every `(product_id, quantity)` item in one payment, optionally
+    /// redeeming the coupon `coupon`. Returns the new order id.
+    #[must_use]
+    pub fn checkout(
+        &self,
+        buyer: &ScAddress,
+        items: &[(u32, u32)],
+        token_id: &ScAddress,
+        coupon: Option<&str>,
+        referrer: Option<&ScAddress>,
+    ) -> Invocation<u32> {
+        self.invoke(
+            "checkout",
+            vec![
+                buyer.to_scval(),
+                items.to_scval(),
+                token_id.to_scval(),
+                coupon.map_or(ScVal::Void, symbol),
+                referrer.map_or(ScVal::Void, ToScVal::to_scval),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn get_order(&self, order_id: u32) -> Invocation<Option<Order>> {
+        self.invoke("get_order", vec![order_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn review(
+        &self,
+        order_id: u32,
+        product_id: u32,
+        rating: u32,
+        hash: &[u8; 32],
+    ) -> Invocation<()> {
+        self.invoke(
+            "review",
+            vec![
+                order_id.to_scval(),
+                product_id.to_scval(),
+                rating.to_scval(),
+                hash.to_scval(),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn get_review(&self, order_id: u32, product_id: u32) -> Invocation<Option<Review>> {
+        self.invoke(
+            "get_review",
+            vec![order_id.to_scval(), product_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn review_purchase(
+        &self,
+        buyer: &ScAddress,
+        product_id: u32,
+        rating: u32,
+        hash: &[u8; 32],
+    ) -> Invocation<()> {
+        self.invoke(
+            "review_purchase",
+            vec![
+                buyer.to_scval(),
+                product_id.to_scval(),
+                rating.to_scval(),
+                hash.to_scval(),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn get_purchase_review(
+        &self,
+        buyer: &ScAddress,
+        product_id: u32,
+    ) -> Invocation<Option<Review>> {
+        self.invoke(
+            "get_purchase_review",
+            vec![buyer.to_scval(), product_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_rating(&self, product_id: u32) -> Invocation<Rating> {
+        self.invoke("get_rating", vec![product_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_escrow(&self, order_id: u32) -> Invocation<Option<Escrow>> {
+        self.invoke("get_escrow", vec![order_id.to_scval()])
+    }
+
+    /// Needs no authorization; anyone can release an undisputed escrow once
+    /// its window has passed.
+    #[must_use]
+    pub fn release_escrow(&self, order_id: u32) -> Invocation<(i128, i128, i128)> {
+        self.invoke("release_escrow", vec![order_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn open_dispute(&self, order_id: u32, claim: &[u8; 32]) -> Invocation<()> {
+        self.invoke("open_dispute", vec![order_id.to_scval(), claim.to_scval()])
+    }
+
+    /// Needs the reserve account's authorization.
+    #[must_use]
+    pub fn respond_dispute(&self, order_id: u32, response: &[u8; 32]) -> Invocation<()> {
+        self.invoke(
+            "respond_dispute",
+            vec![order_id.to_scval(), response.to_scval()],
+        )
+    }
+
+    /// Needs the arbiter's authorization.
+    #[must_use]
+    pub fn resolve_dispute(&self, order_id: u32, ruling: &Ruling) -> Invocation<i128> {
+        self.invoke(
+            "resolve_dispute",
+            vec![order_id.to_scval(), ruling.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_dispute(&self, order_id: u32) -> Invocation<Option<Dispute>> {
+        self.invoke("get_dispute", vec![order_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn set_coupon(&self, code: &str, coupon: &Coupon) -> Invocation<()> {
+        self.invoke("set_coupon", vec![symbol(code), coupon.to_scval()])
+    }
+
+    #[must_use]
+    pub fn remove_coupon(&self, code: &str) -> Invocation<()> {
+        self.invoke("remove_coupon", vec![symbol(code)])
+    }
+
+    #[must_use]
+    pub fn get_coupon(&self, code: &str) -> Invocation<Option<Coupon>> {
+        self.invoke("get_coupon", vec![symbol(code)])
+    }
+
+    #[must_use]
+    pub fn get_reserve_acc(&self) -> Invocation<ScAddress> {
+        self.invoke("get_reserve_acc", vec![])
+    }
+
+    #[must_use]
+    pub fn get_dev_acc(&self) -> Invocation<ScAddress> {
+        self.invoke("get_dev_acc", vec![])
+    }
+
+    #[must_use]
+    pub fn get_launchpad_acc(&self) -> Invocation<ScAddress> {
+        self.invoke("get_launchpad_acc", vec![])
+    }
+
+    #[must_use]
+    pub fn get_admin(&self) -> Invocation<ScAddress> {
+        self.invoke("get_admin", vec![])
+    }
+
+    #[must_use]
+    pub fn get_split(&self) -> Invocation<Split> {
+        self.invoke("get_split", vec![])
+    }
+
+    #[must_use]
+    pub fn set_reserve_acc(
+        &self,
+        reserve_acc: &ScAddress,
+        approvers: &[ScAddress],
+    ) -> Invocation<()> {
+        self.invoke(
+            "set_reserve_acc",
+            vec![reserve_acc.to_scval(), approvers.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn set_dev_acc(&self, dev_acc: &ScAddress, approvers: &[ScAddress]) -> Invocation<()> {
+        self.invoke(
+            "set_dev_acc",
+            vec![dev_acc.to_scval(), approvers.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn set_launchpad_acc(
+        &self,
+        launchpad_acc: &ScAddress,
+        approvers: &[ScAddress],
+    ) -> Invocation<()> {
+        self.invoke(
+            "set_launchpad_acc",
+            vec![launchpad_acc.to_scval(), approvers.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn set_payout_mode(&self, mode: PayoutMode) -> Invocation<()> {
+        self.invoke("set_payout_mode", vec![mode.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_payout_mode(&self) -> Invocation<PayoutMode> {
+        self.invoke("get_payout_mode", vec![])
+    }
+
+    #[must_use]
+    pub fn set_paused(&self, paused: bool) -> Invocation<()> {
+        self.invoke("set_paused", vec![paused.to_scval()])
+    }
+
+    #[must_use]
+    pub fn is_paused(&self) -> Invocation<bool> {
+        self.invoke("is_paused", vec![])
+    }
+
+    #[must_use]
+    pub fn set_product_limits(&self, limits: &ProductLimits) -> Invocation<()> {
+        self.invoke("set_product_limits", vec![limits.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_product_limits(&self) -> Invocation<ProductLimits> {
+        self.invoke("get_product_limits", vec![])
+    }
+
+    #[must_use]
+    pub fn set_referral_config(&self, config: &ReferralConfig) -> Invocation<()> {
+        self.invoke("set_referral_config", vec![config.to_scval()])
+    }
+
+    #[must_use]
+    pub fn set_loyalty_config(&self, config: &LoyaltyConfig) -> Invocation<()> {
+        self.invoke("set_loyalty_config", vec![config.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_loyalty_config(&self) -> Invocation<LoyaltyConfig> {
+        self.invoke("get_loyalty_config", vec![])
+    }
+
+    #[must_use]
+    pub fn set_dispute_config(&self, config: &DisputeConfig) -> Invocation<()> {
+        self.invoke("set_dispute_config", vec![config.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_dispute_config(&self) -> Invocation<DisputeConfig> {
+        self.invoke("get_dispute_config", vec![])
+    }
+
+    #[must_use]
+    pub fn set_arbiter(&self, arbiter: &ScAddress) -> Invocation<()> {
+        self.invoke("set_arbiter", vec![arbiter.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_arbiter(&self) -> Invocation<Option<ScAddress>> {
+        self.invoke("get_arbiter", vec![])
+    }
+
+    #[must_use]
+    pub fn get_referral_config(&self) -> Invocation<ReferralConfig> {
+        self.invoke("get_referral_config", vec![])
+    }
+
+    #[must_use]
+    pub fn get_referral_earnings(
+        &self,
+        referrer: &ScAddress,
+        token_id: &ScAddress,
+    ) -> Invocation<i128> {
+        self.invoke(
+            "get_referral_earnings",
+            vec![referrer.to_scval(), token_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn withdraw(
+        &self,
+        recipient: &ScAddress,
+        token_id: &ScAddress,
+        amount: i128,
+    ) -> Invocation<i128> {
+        self.invoke(
+            "withdraw",
+            vec![recipient.to_scval(), token_id.to_scval(), amount.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_claimable(&self, recipient: &ScAddress, token_id: &ScAddress) -> Invocation<i128> {
+        self.invoke(
+            "get_claimable",
+            vec![recipient.to_scval(), token_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn claim_vested(&self, recipient: &ScAddress, token_id: &ScAddress) -> Invocation<i128> {
+        self.invoke(
+            "claim_vested",
+            vec![recipient.to_scval(), token_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_vested(&self, recipient: &ScAddress, token_id: &ScAddress) -> Invocation<i128> {
+        self.invoke(
+            "get_vested",
+            vec![recipient.to_scval(), token_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_unvested(&self, recipient: &ScAddress, token_id: &ScAddress) -> Invocation<i128> {
+        self.invoke(
+            "get_unvested",
+            vec![recipient.to_scval(), token_id.to_scval()],
+        )
+    }
+
+    #[must_use]
+    pub fn get_vesting_schedule(
+        &self,
+        recipient: &ScAddress,
+    ) -> Invocation<Option<VestingSchedule>> {
+        self.invoke("get_vesting_schedule", vec![recipient.to_scval()])
+    }
+
+    #[must_use]
+    pub fn propose(&self, proposer: &ScAddress, action: &ProposalAction) -> Invocation<u32> {
+        self.invoke("propose", vec![proposer.to_scval(), action.to_scval()])
+    }
+
+    #[must_use]
+    pub fn approve(&self, signer: &ScAddress, proposal_id: u32) -> Invocation<u32> {
+        self.invoke("approve", vec![signer.to_scval(), proposal_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn execute(&self, proposal_id: u32) -> Invocation<()> {
+        self.invoke("execute", vec![proposal_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_proposal(&self, proposal_id: u32) -> Invocation<Proposal> {
+        self.invoke("get_proposal", vec![proposal_id.to_scval()])
+    }
+
+    #[must_use]
+    pub fn get_signers(&self) -> Invocation<Vec<ScAddress>> {
+        self.invoke("get_signers", vec![])
+    }
+
+    #[must_use]
+    pub fn get_threshold(&self) -> Invocation<u32> {
+        self.invoke("get_threshold", vec![])
+    }
+}
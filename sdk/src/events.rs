@@ -0,0 +1,653 @@
+//! Decoding of the contract's events.
+//!
+//! Every event is published with the topics `("market", "v1", <name>)` and
+//! one of the payloads below as data; see the contract's `events` module for
+//! the full table.
+
+use stellar_xdr::curr::{ContractEvent, ContractEventBody, ScAddress, ScVal};
+
+use crate::scval::{symbol_name, DecodeError, Fields, FromScVal};
+use crate::types::{
+    Bundle, Coupon, Dispute, DisputeConfig, Escrow, LoyaltyConfig, PayoutMode, ProductLimits,
+    Proposal, Receipt, ReceiptMode, ReferralConfig, Review, SalePhase, Split, VestingSchedule,
+    Voucher,
+};
+
+pub const EVENTS_NAMESPACE: &str = "market";
+pub const EVENTS_VERSION: &str = "v1";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InitEvent {
+    pub admin: ScAddress,
+    pub reserve_acc: ScAddress,
+    pub dev_acc: ScAddress,
+    pub launchpad_acc: ScAddress,
+    pub signers: Vec<ScAddress>,
+    pub threshold: u32,
+}
+
+impl FromScVal for InitEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(InitEvent {
+            admin: fields.get("admin")?,
+            reserve_acc: fields.get("reserve_acc")?,
+            dev_acc: fields.get("dev_acc")?,
+            launchpad_acc: fields.get("launchpad_acc")?,
+            signers: fields.get("signers")?,
+            threshold: fields.get("threshold")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProductEvent {
+    pub product_id: u32,
+    pub price: i128,
+    pub remaining: i128,
+    pub expiry: u64,
+}
+
+impl FromScVal for ProductEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ProductEvent {
+            product_id: fields.get("product_id")?,
+            price: fields.get("price")?,
+            remaining: fields.get("remaining")?,
+            expiry: fields.get("expiry")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PurchaseEvent {
+    pub buyer: ScAddress,
+    pub product_id: u32,
+    pub token: ScAddress,
+    pub quantity: u32,
+    pub reserve_amount: i128,
+    pub launchpad_amount: i128,
+    pub dev_amount: i128,
+}
+
+impl FromScVal for PurchaseEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(PurchaseEvent {
+            buyer: fields.get("buyer")?,
+            product_id: fields.get("product_id")?,
+            token: fields.get("token")?,
+            quantity: fields.get("quantity")?,
+            reserve_amount: fields.get("reserve_amount")?,
+            launchpad_amount: fields.get("launchpad_amount")?,
+            dev_amount: fields.get("dev_amount")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariantEvent {
+    pub product_id: u32,
+    pub variant_id: u32,
+    pub price: i128,
+    pub remaining: i128,
+}
+
+impl FromScVal for VariantEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(VariantEvent {
+            product_id: fields.get("product_id")?,
+            variant_id: fields.get("variant_id")?,
+            price: fields.get("price")?,
+            remaining: fields.get("remaining")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderEvent {
+    pub order_id: u32,
+    pub buyer: ScAddress,
+    pub token: ScAddress,
+    pub discount: i128,
+    pub total: i128,
+}
+
+impl FromScVal for OrderEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(OrderEvent {
+            order_id: fields.get("order_id")?,
+            buyer: fields.get("buyer")?,
+            token: fields.get("token")?,
+            discount: fields.get("discount")?,
+            total: fields.get("total")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BundlePurchaseEvent {
+    pub buyer: ScAddress,
+    pub bundle_id: u32,
+    pub token: ScAddress,
+    pub reserve_amount: i128,
+    pub launchpad_amount: i128,
+    pub dev_amount: i128,
+}
+
+impl FromScVal for BundlePurchaseEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(BundlePurchaseEvent {
+            buyer: fields.get("buyer")?,
+            bundle_id: fields.get("bundle_id")?,
+            token: fields.get("token")?,
+            reserve_amount: fields.get("reserve_amount")?,
+            launchpad_amount: fields.get("launchpad_amount")?,
+            dev_amount: fields.get("dev_amount")?,
+        })
+    }
+}
+
+/// An undisputed escrow was paid out to the payout accounts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EscrowReleaseEvent {
+    pub order_id: u32,
+    pub reserve_amount: i128,
+    pub launchpad_amount: i128,
+    pub dev_amount: i128,
+}
+
+impl FromScVal for EscrowReleaseEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(EscrowReleaseEvent {
+            order_id: fields.get("order_id")?,
+            reserve_amount: fields.get("reserve_amount")?,
+            launchpad_amount: fields.get("launchpad_amount")?,
+            dev_amount: fields.get("dev_amount")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferralEvent {
+    pub referrer: ScAddress,
+    pub buyer: ScAddress,
+    pub token: ScAddress,
+    pub amount: i128,
+}
+
+impl FromScVal for ReferralEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ReferralEvent {
+            referrer: fields.get("referrer")?,
+            buyer: fields.get("buyer")?,
+            token: fields.get("token")?,
+            amount: fields.get("amount")?,
+        })
+    }
+}
+
+/// `change` is positive when points were earned and negative when redeemed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointsEvent {
+    pub account: ScAddress,
+    pub change: i128,
+    pub balance: i128,
+    pub expires_at: u64,
+}
+
+impl FromScVal for PointsEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(PointsEvent {
+            account: fields.get("account")?,
+            change: fields.get("change")?,
+            balance: fields.get("balance")?,
+            expires_at: fields.get("expires_at")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReceiptTransferEvent {
+    pub receipt_id: u32,
+    pub from: ScAddress,
+    pub to: ScAddress,
+}
+
+impl FromScVal for ReceiptTransferEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ReceiptTransferEvent {
+            receipt_id: fields.get("receipt_id")?,
+            from: fields.get("from")?,
+            to: fields.get("to")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReceiptApprovalEvent {
+    pub receipt_id: u32,
+    pub owner: ScAddress,
+    pub spender: ScAddress,
+}
+
+impl FromScVal for ReceiptApprovalEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ReceiptApprovalEvent {
+            receipt_id: fields.get("receipt_id")?,
+            owner: fields.get("owner")?,
+            spender: fields.get("spender")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReceiptModeEvent {
+    pub product_id: u32,
+    pub mode: ReceiptMode,
+}
+
+impl FromScVal for ReceiptModeEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ReceiptModeEvent {
+            product_id: fields.get("product_id")?,
+            mode: fields.get("mode")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedeemEvent {
+    pub voucher_id: u32,
+    pub product_id: u32,
+    pub redeemer: ScAddress,
+}
+
+impl FromScVal for RedeemEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(RedeemEvent {
+            voucher_id: fields.get("voucher_id")?,
+            product_id: fields.get("product_id")?,
+            redeemer: fields.get("redeemer")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoucherValidityEvent {
+    pub product_id: u32,
+    pub validity: u64,
+}
+
+impl FromScVal for VoucherValidityEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(VoucherValidityEvent {
+            product_id: fields.get("product_id")?,
+            validity: fields.get("validity")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedeemerEvent {
+    pub product_id: u32,
+    pub redeemer: ScAddress,
+    pub allowed: bool,
+}
+
+impl FromScVal for RedeemerEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(RedeemerEvent {
+            product_id: fields.get("product_id")?,
+            redeemer: fields.get("redeemer")?,
+            allowed: fields.get("allowed")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SalePhasesEvent {
+    pub product_id: u32,
+    pub phases: Vec<SalePhase>,
+}
+
+impl FromScVal for SalePhasesEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(SalePhasesEvent {
+            product_id: fields.get("product_id")?,
+            phases: fields.get("phases")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllowlistEvent {
+    pub product_id: u32,
+    pub accounts: Vec<ScAddress>,
+    pub allowed: bool,
+}
+
+impl FromScVal for AllowlistEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(AllowlistEvent {
+            product_id: fields.get("product_id")?,
+            accounts: fields.get("accounts")?,
+            allowed: fields.get("allowed")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CouponEvent {
+    pub code: String,
+    pub coupon: Coupon,
+}
+
+impl FromScVal for CouponEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(CouponEvent {
+            code: fields.symbol("code")?,
+            coupon: fields.get("coupon")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefundEvent {
+    pub buyer: ScAddress,
+    pub product_id: u32,
+    pub token: ScAddress,
+    pub amount: i128,
+}
+
+impl FromScVal for RefundEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(RefundEvent {
+            buyer: fields.get("buyer")?,
+            product_id: fields.get("product_id")?,
+            token: fields.get("token")?,
+            amount: fields.get("amount")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WithdrawEvent {
+    pub recipient: ScAddress,
+    pub token: ScAddress,
+    pub amount: i128,
+    pub remaining: i128,
+}
+
+impl FromScVal for WithdrawEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(WithdrawEvent {
+            recipient: fields.get("recipient")?,
+            token: fields.get("token")?,
+            amount: fields.get("amount")?,
+            remaining: fields.get("remaining")?,
+        })
+    }
+}
+
+/// `unvested` is what stays locked in the vault after the claim.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VestingClaimEvent {
+    pub recipient: ScAddress,
+    pub token: ScAddress,
+    pub amount: i128,
+    pub unvested: i128,
+}
+
+impl FromScVal for VestingClaimEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(VestingClaimEvent {
+            recipient: fields.get("recipient")?,
+            token: fields.get("token")?,
+            amount: fields.get("amount")?,
+            unvested: fields.get("unvested")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountChangedEvent {
+    /// `reserve`, `dev` or `launchpad`.
+    pub role: String,
+    pub old: ScAddress,
+    pub new: ScAddress,
+}
+
+impl FromScVal for AccountChangedEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(AccountChangedEvent {
+            role: fields.symbol("role")?,
+            old: fields.get("old")?,
+            new: fields.get("new")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitChangedEvent {
+    pub old: Split,
+    pub new: Split,
+}
+
+impl FromScVal for SplitChangedEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(SplitChangedEvent {
+            old: fields.get("old")?,
+            new: fields.get("new")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VestingEvent {
+    pub recipient: ScAddress,
+    pub schedule: VestingSchedule,
+}
+
+impl FromScVal for VestingEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(VestingEvent {
+            recipient: fields.get("recipient")?,
+            schedule: fields.get("schedule")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProposalEvent {
+    pub proposal_id: u32,
+    pub signer: ScAddress,
+}
+
+impl FromScVal for ProposalEvent {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ProposalEvent {
+            proposal_id: fields.get("proposal_id")?,
+            signer: fields.get("signer")?,
+        })
+    }
+}
+
+/// A decoded marketplace event, one variant per event name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    Init(InitEvent),
+    ProductCreated(ProductEvent),
+    ProductUpdated(ProductEvent),
+    Purchase(PurchaseEvent),
+    VariantUpdated(VariantEvent),
+    Order(OrderEvent),
+    BundleCreated(Bundle),
+    BundlePurchase(BundlePurchaseEvent),
+    Reviewed(Review),
+    Escrowed(Escrow),
+    EscrowReleased(EscrowReleaseEvent),
+    DisputeOpened(Dispute),
+    DisputeAnswered(Dispute),
+    DisputeResolved(Dispute),
+    ReceiptMinted(Receipt),
+    ReceiptTransferred(ReceiptTransferEvent),
+    ReceiptApproved(ReceiptApprovalEvent),
+    ReceiptApprovalRevoked(u32),
+    ReceiptBurned(u32),
+    VoucherIssued(Voucher),
+    VoucherRedeemed(RedeemEvent),
+    VoucherVoided(u32),
+    Referral(ReferralEvent),
+    PointsChanged(PointsEvent),
+    CouponSet(CouponEvent),
+    CouponRemoved(String),
+    Refund(RefundEvent),
+    Withdraw(WithdrawEvent),
+    VestingClaimed(VestingClaimEvent),
+    AccountChanged(AccountChangedEvent),
+    SplitChanged(SplitChangedEvent),
+    PayoutModeChanged(PayoutMode),
+    PausedChanged(bool),
+    ProductLimitsChanged(ProductLimits),
+    SalePhasesChanged(SalePhasesEvent),
+    AllowlistChanged(AllowlistEvent),
+    ReceiptModeChanged(ReceiptModeEvent),
+    VoucherValiditySet(VoucherValidityEvent),
+    VouchersDisabled(u32),
+    RedeemerChanged(RedeemerEvent),
+    ReferralConfigChanged(ReferralConfig),
+    LoyaltyConfigChanged(LoyaltyConfig),
+    DisputeConfigChanged(DisputeConfig),
+    ArbiterChanged(ScAddress),
+    Upgraded([u8; 32]),
+    VestingSet(VestingEvent),
+    VestingRemoved(ScAddress),
+    ProposalCreated(ProposalEvent),
+    ProposalApproved(ProposalEvent),
+    ProposalExecuted(Proposal),
+}
+
+impl Event {
+    /// Decodes an event from its topics and data. Events published by other
+    /// contracts, or by a newer event version, are `DecodeError::UnknownEvent`.
+    pub fn decode(topics: &[ScVal], data: &ScVal) -> Result<Self, DecodeError> {
+        let [namespace, version, name] = topics else {
+            return Err(DecodeError::UnknownEvent);
+        };
+        let symbol = |topic| symbol_name(topic).map_err(|_| DecodeError::UnknownEvent);
+        if symbol(namespace)? != EVENTS_NAMESPACE || symbol(version)? != EVENTS_VERSION {
+            return Err(DecodeError::UnknownEvent);
+        }
+
+        let event = match symbol(name)?.as_str() {
+            "init" => Event::Init(FromScVal::from_scval(data)?),
+            "prod_new" => Event::ProductCreated(FromScVal::from_scval(data)?),
+            "prod_upd" => Event::ProductUpdated(FromScVal::from_scval(data)?),
+            "purchase" => Event::Purchase(FromScVal::from_scval(data)?),
+            "var_upd" => Event::VariantUpdated(FromScVal::from_scval(data)?),
+            "order" => Event::Order(FromScVal::from_scval(data)?),
+            "bundle" => Event::BundleCreated(FromScVal::from_scval(data)?),
+            "bndl_buy" => Event::BundlePurchase(FromScVal::from_scval(data)?),
+            "review" => Event::Reviewed(FromScVal::from_scval(data)?),
+            "escrow" => Event::Escrowed(FromScVal::from_scval(data)?),
+            "release" => Event::EscrowReleased(FromScVal::from_scval(data)?),
+            "disp_open" => Event::DisputeOpened(FromScVal::from_scval(data)?),
+            "disp_ans" => Event::DisputeAnswered(FromScVal::from_scval(data)?),
+            "disp_rule" => Event::DisputeResolved(FromScVal::from_scval(data)?),
+            "receipt" => Event::ReceiptMinted(FromScVal::from_scval(data)?),
+            "rcpt_xfer" => Event::ReceiptTransferred(FromScVal::from_scval(data)?),
+            "rcpt_appr" => Event::ReceiptApproved(FromScVal::from_scval(data)?),
+            "rcpt_rvk" => Event::ReceiptApprovalRevoked(FromScVal::from_scval(data)?),
+            "rcpt_burn" => Event::ReceiptBurned(FromScVal::from_scval(data)?),
+            "voucher" => Event::VoucherIssued(FromScVal::from_scval(data)?),
+            "redeem" => Event::VoucherRedeemed(FromScVal::from_scval(data)?),
+            "vchr_void" => Event::VoucherVoided(FromScVal::from_scval(data)?),
+            "referral" => Event::Referral(FromScVal::from_scval(data)?),
+            "points" => Event::PointsChanged(FromScVal::from_scval(data)?),
+            "coupon" => Event::CouponSet(FromScVal::from_scval(data)?),
+            "coupon_rm" => Event::CouponRemoved(symbol_name(data)?),
+            "refund" => Event::Refund(FromScVal::from_scval(data)?),
+            "withdraw" => Event::Withdraw(FromScVal::from_scval(data)?),
+            "vested" => Event::VestingClaimed(FromScVal::from_scval(data)?),
+            "acct_set" => Event::AccountChanged(FromScVal::from_scval(data)?),
+            "split_set" => Event::SplitChanged(FromScVal::from_scval(data)?),
+            "mode_set" => Event::PayoutModeChanged(FromScVal::from_scval(data)?),
+            "paused" => Event::PausedChanged(FromScVal::from_scval(data)?),
+            "limits" => Event::ProductLimitsChanged(FromScVal::from_scval(data)?),
+            "phases" => Event::SalePhasesChanged(FromScVal::from_scval(data)?),
+            "allowlist" => Event::AllowlistChanged(FromScVal::from_scval(data)?),
+            "rcpt_mode" => Event::ReceiptModeChanged(FromScVal::from_scval(data)?),
+            "vchr_set" => Event::VoucherValiditySet(FromScVal::from_scval(data)?),
+            "vchr_off" => Event::VouchersDisabled(FromScVal::from_scval(data)?),
+            "redeemer" => Event::RedeemerChanged(FromScVal::from_scval(data)?),
+            "ref_cfg" => Event::ReferralConfigChanged(FromScVal::from_scval(data)?),
+            "loyalty" => Event::LoyaltyConfigChanged(FromScVal::from_scval(data)?),
+            "disp_cfg" => Event::DisputeConfigChanged(FromScVal::from_scval(data)?),
+            "arbiter" => Event::ArbiterChanged(FromScVal::from_scval(data)?),
+            "upgrade" => Event::Upgraded(FromScVal::from_scval(data)?),
+            "vest_set" => Event::VestingSet(FromScVal::from_scval(data)?),
+            "vest_rm" => Event::VestingRemoved(FromScVal::from_scval(data)?),
+            "prop_new" => Event::ProposalCreated(FromScVal::from_scval(data)?),
+            "prop_appr" => Event::ProposalApproved(FromScVal::from_scval(data)?),
+            "prop_exec" => Event::ProposalExecuted(FromScVal::from_scval(data)?),
+            _ => return Err(DecodeError::UnknownEvent),
+        };
+        Ok(event)
+    }
+
+    /// Decodes an event as it appears in transaction meta.
+    pub fn from_contract_event(event: &ContractEvent) -> Result<Self, DecodeError> {
+        let ContractEventBody::V0(body) = &event.body;
+        Self::decode(&body.topics, &body.data)
+    }
+}
@@ -0,0 +1,31 @@
+//! Off-chain client for the marketplace contract.
+//!
+//! Rust mirrors of the contract's types, builders that turn each entrypoint
+//! call into `InvokeContractArgs` or host function XDR, and decoders for
+//! return values and events. Everything works on plain `stellar-xdr` values,
+//! so callers don't need soroban-sdk or a network connection.
+//!
+//! ```
+//! use marketplace_sdk::Client;
+//! use stellar_xdr::curr::{Hash, ScAddress};
+//!
+//! let contract = ScAddress::Contract(Hash([7; 32]));
+//! let call = Client::new(contract).get_product(1);
+//! assert_eq!(call.args().function_name.0.to_utf8_string().unwrap(), "get_product");
+//! ```
+
+mod client;
+mod events;
+pub mod merkle;
+mod scval;
+mod types;
+
+pub use client::{Client, Invocation};
+pub use events::*;
+pub use scval::{DecodeError, FromScVal, ToScVal};
+pub use types::*;
+
+pub use stellar_xdr;
+
+#[cfg(test)]
+mod test;
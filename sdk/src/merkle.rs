@@ -0,0 +1,88 @@
+//! Merkle trees for `PhaseAccess::Merkle` sale phases.
+//!
+//! A leaf is the SHA-256 of the buyer's address as `ScVal` XDR, and each
+//! parent is the SHA-256 of its two children in ascending order, so proofs
+//! don't need to say which side a sibling is on. A node left without a pair
+//! moves up a level unchanged.
+//!
+//! ```
+//! use marketplace_sdk::merkle;
+//! use stellar_xdr::curr::{Hash, ScAddress};
+//!
+//! let buyers: Vec<ScAddress> = (0..5).map(|i| ScAddress::Contract(Hash([i; 32]))).collect();
+//! let leaves: Vec<[u8; 32]> = buyers.iter().map(merkle::leaf).collect();
+//! let root = merkle::root(&leaves);
+//! let proof = merkle::proof(&leaves, 3);
+//! assert!(merkle::verify(&root, &buyers[3], &proof));
+//! ```
+
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{Limits, ScAddress, ScVal, WriteXdr};
+
+/// The leaf hash of `account`.
+pub fn leaf(account: &ScAddress) -> [u8; 32] {
+    let xdr = ScVal::Address(account.clone())
+        .to_xdr(Limits::none())
+        .expect("address encodes");
+    Sha256::digest(xdr).into()
+}
+
+fn parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(low);
+    hasher.update(high);
+    hasher.finalize().into()
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => parent(a, b),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// The root of the tree over `leaves`, in order. An empty tree has an
+/// all-zero root that no proof matches.
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// The sibling hashes from `leaves[index]` up to the root; the `proof`
+/// argument of `purchase_with_proof`.
+///
+/// # Panics
+///
+/// If `index` is out of bounds.
+pub fn proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    assert!(index < leaves.len(), "leaf index out of bounds");
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+/// Checks a proof the way the contract does.
+pub fn verify(root: &[u8; 32], account: &ScAddress, proof: &[[u8; 32]]) -> bool {
+    let node = proof
+        .iter()
+        .fold(leaf(account), |node, sibling| parent(&node, sibling));
+    node == *root
+}
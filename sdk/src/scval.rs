@@ -0,0 +1,289 @@
+//! Conversions between the SDK types and XDR `ScVal`s, following the
+//! encoding `#[contracttype]` uses on chain:
+//!
+//! - structs are maps keyed by field name symbols, sorted by key,
+//! - enum variants are vectors of the variant name symbol followed by the
+//!   variant's fields,
+//! - contract errors are `ScError::Contract(code)`.
+
+use std::fmt;
+
+use stellar_xdr::curr::{ScAddress, ScBytes, ScMap, ScMapEntry, ScString, ScSymbol, ScVal, ScVec};
+
+/// A value that doesn't have the shape the contract spec promises.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The value is not of the expected XDR type.
+    UnexpectedType { expected: &'static str },
+    /// A struct map is missing one of its fields.
+    MissingField(&'static str),
+    /// An enum value names a variant the SDK doesn't know about.
+    UnknownVariant(String),
+    /// An event's topics don't belong to the marketplace event scheme.
+    UnknownEvent,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedType { expected } => write!(f, "expected {expected}"),
+            DecodeError::MissingField(name) => write!(f, "missing field `{name}`"),
+            DecodeError::UnknownVariant(name) => write!(f, "unknown variant `{name}`"),
+            DecodeError::UnknownEvent => write!(f, "not a marketplace event"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub trait ToScVal {
+    fn to_scval(&self) -> ScVal;
+}
+
+pub trait FromScVal: Sized {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError>;
+}
+
+fn unexpected<T>(expected: &'static str) -> Result<T, DecodeError> {
+    Err(DecodeError::UnexpectedType { expected })
+}
+
+// Lengths are bounded by `u32::MAX`, which no caller-built value gets near,
+// so the XDR length checks below can't fail in practice.
+const LENGTH: &str = "value length exceeds the XDR limit";
+
+macro_rules! impl_number {
+    ($($ty:ty => $name:literal),*) => {
+        $(
+            impl ToScVal for $ty {
+                fn to_scval(&self) -> ScVal {
+                    ScVal::from(*self)
+                }
+            }
+
+            impl FromScVal for $ty {
+                fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+                    <$ty>::try_from(val.clone())
+                        .or_else(|_| unexpected($name))
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(bool => "bool", u32 => "u32", u64 => "u64", i128 => "i128");
+
+impl ToScVal for () {
+    fn to_scval(&self) -> ScVal {
+        ScVal::Void
+    }
+}
+
+impl FromScVal for () {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Void => Ok(()),
+            _ => unexpected("void"),
+        }
+    }
+}
+
+impl ToScVal for str {
+    fn to_scval(&self) -> ScVal {
+        ScVal::String(ScString(self.as_bytes().try_into().expect(LENGTH)))
+    }
+}
+
+impl ToScVal for String {
+    fn to_scval(&self) -> ScVal {
+        self.as_str().to_scval()
+    }
+}
+
+impl FromScVal for String {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::String(s) => s.0.to_utf8_string().or_else(|_| unexpected("utf-8 string")),
+            _ => unexpected("string"),
+        }
+    }
+}
+
+impl ToScVal for ScAddress {
+    fn to_scval(&self) -> ScVal {
+        ScVal::Address(self.clone())
+    }
+}
+
+impl FromScVal for ScAddress {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Address(address) => Ok(address.clone()),
+            _ => unexpected("address"),
+        }
+    }
+}
+
+impl ToScVal for [u8; 32] {
+    fn to_scval(&self) -> ScVal {
+        ScVal::Bytes(ScBytes(self.to_vec().try_into().expect(LENGTH)))
+    }
+}
+
+impl FromScVal for [u8; 32] {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Bytes(bytes) => bytes
+                .0
+                .as_slice()
+                .try_into()
+                .or_else(|_| unexpected("32 bytes")),
+            _ => unexpected("bytes"),
+        }
+    }
+}
+
+impl<T: ToScVal> ToScVal for [T] {
+    fn to_scval(&self) -> ScVal {
+        let items: Vec<ScVal> = self.iter().map(ToScVal::to_scval).collect();
+        ScVal::Vec(Some(ScVec(items.try_into().expect(LENGTH))))
+    }
+}
+
+impl<T: ToScVal> ToScVal for Vec<T> {
+    fn to_scval(&self) -> ScVal {
+        self.as_slice().to_scval()
+    }
+}
+
+impl<T: FromScVal> FromScVal for Vec<T> {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Vec(Some(items)) => items.iter().map(T::from_scval).collect(),
+            _ => unexpected("vec"),
+        }
+    }
+}
+
+/// `Option`s encode `None` as void and `Some` as the value itself.
+impl<T: ToScVal> ToScVal for Option<T> {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            Some(val) => val.to_scval(),
+            None => ScVal::Void,
+        }
+    }
+}
+
+impl<T: FromScVal> FromScVal for Option<T> {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Void => Ok(None),
+            val => T::from_scval(val).map(Some),
+        }
+    }
+}
+
+/// Encodes a byte string of any length, as opposed to the fixed `[u8; 32]`.
+pub(crate) fn bytes(data: &[u8]) -> ScVal {
+    ScVal::Bytes(ScBytes(data.try_into().expect(LENGTH)))
+}
+
+pub(crate) fn symbol(name: &str) -> ScVal {
+    ScVal::Symbol(ScSymbol(name.try_into().expect(LENGTH)))
+}
+
+pub(crate) fn symbol_name(val: &ScVal) -> Result<String, DecodeError> {
+    match val {
+        ScVal::Symbol(sym) => sym.0.to_utf8_string().or_else(|_| unexpected("symbol")),
+        _ => unexpected("symbol"),
+    }
+}
+
+/// Encodes a struct as a map keyed by its field names.
+pub(crate) fn encode_struct(fields: &[(&str, ScVal)]) -> ScVal {
+    let entries = fields.iter().map(|(name, val)| (symbol(name), val.clone()));
+    ScVal::Map(Some(ScMap::sorted_from_pairs(entries).expect(LENGTH)))
+}
+
+/// Encodes an enum variant as its name followed by its fields.
+pub(crate) fn encode_variant(name: &str, fields: &[ScVal]) -> ScVal {
+    let mut items = vec![symbol(name)];
+    items.extend_from_slice(fields);
+    ScVal::Vec(Some(ScVec(items.try_into().expect(LENGTH))))
+}
+
+/// Field lookup over a decoded struct map.
+pub(crate) struct Fields<'a>(&'a [ScMapEntry]);
+
+impl<'a> Fields<'a> {
+    pub(crate) fn new(val: &'a ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Map(Some(map)) => Ok(Fields(map.as_slice())),
+            _ => unexpected("struct map"),
+        }
+    }
+
+    fn field(&self, name: &'static str) -> Result<&'a ScVal, DecodeError> {
+        self.0
+            .iter()
+            .find(|entry| matches!(symbol_name(&entry.key), Ok(key) if key == name))
+            .map(|entry| &entry.val)
+            .ok_or(DecodeError::MissingField(name))
+    }
+
+    pub(crate) fn get<T: FromScVal>(&self, name: &'static str) -> Result<T, DecodeError> {
+        T::from_scval(self.field(name)?)
+    }
+
+    pub(crate) fn symbol(&self, name: &'static str) -> Result<String, DecodeError> {
+        symbol_name(self.field(name)?)
+    }
+}
+
+/// Splits an enum value into its variant name and fields.
+pub(crate) fn decode_variant(val: &ScVal) -> Result<(String, &[ScVal]), DecodeError> {
+    match val {
+        ScVal::Vec(Some(items)) if !items.is_empty() => Ok((symbol_name(&items[0])?, &items[1..])),
+        _ => unexpected("enum variant"),
+    }
+}
+
+/// The single field of a tuple variant.
+pub(crate) fn only_field<T: FromScVal>(fields: &[ScVal]) -> Result<T, DecodeError> {
+    match fields {
+        [field] => T::from_scval(field),
+        _ => unexpected("one variant field"),
+    }
+}
+
+impl<A: ToScVal, B: ToScVal> ToScVal for (A, B) {
+    fn to_scval(&self) -> ScVal {
+        let items = vec![self.0.to_scval(), self.1.to_scval()];
+        ScVal::Vec(Some(ScVec(items.try_into().expect(LENGTH))))
+    }
+}
+
+impl<A: FromScVal, B: FromScVal> FromScVal for (A, B) {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Vec(Some(items)) => match items.as_slice() {
+                [a, b] => Ok((A::from_scval(a)?, B::from_scval(b)?)),
+                _ => unexpected("2-tuple"),
+            },
+            _ => unexpected("2-tuple"),
+        }
+    }
+}
+
+impl<A: FromScVal, B: FromScVal, C: FromScVal> FromScVal for (A, B, C) {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match val {
+            ScVal::Vec(Some(items)) => match items.as_slice() {
+                [a, b, c] => Ok((A::from_scval(a)?, B::from_scval(b)?, C::from_scval(c)?)),
+                _ => unexpected("3-tuple"),
+            },
+            _ => unexpected("3-tuple"),
+        }
+    }
+}
@@ -0,0 +1,1945 @@
+//! Checks the SDK against the contract itself: invocations must match the
+//! contract's spec, and values must survive a round trip through the contract
+//! running in the in-memory test host.
+
+use super::*;
+
+use marketplace_soroban as contract;
+use soroban_sdk::{
+    testutils::{Address as _, Events as _},
+    Address, Env, Symbol, TryFromVal, Val,
+};
+use stellar_xdr::curr::{
+    Hash, InvokeContractArgs, Limits, ReadXdr, ScAddress, ScError, ScSpecEntry, ScSpecTypeDef,
+    ScSpecUdtUnionCaseV0, ScVal,
+};
+
+fn contract_address() -> ScAddress {
+    ScAddress::Contract(Hash([7; 32]))
+}
+
+fn account(n: u8) -> ScAddress {
+    ScAddress::Contract(Hash([n; 32]))
+}
+
+fn spec(xdr: &[u8]) -> ScSpecEntry {
+    ScSpecEntry::from_xdr(xdr, Limits::none()).unwrap()
+}
+
+/// The contract's user-defined types, as published in its spec.
+fn udts() -> Vec<ScSpecEntry> {
+    [
+        &contract::Product::spec_xdr()[..],
+        &contract::ProductInput::spec_xdr(),
+        &contract::ProductLimits::spec_xdr(),
+        &contract::ProductMetadata::spec_xdr(),
+        &contract::Variant::spec_xdr(),
+        &contract::ProductView::spec_xdr(),
+        &contract::Bundle::spec_xdr(),
+        &contract::Coupon::spec_xdr(),
+        &contract::Order::spec_xdr(),
+        &contract::Split::spec_xdr(),
+        &contract::PayoutMode::spec_xdr(),
+        &contract::ReferralSource::spec_xdr(),
+        &contract::ReferralConfig::spec_xdr(),
+        &contract::LoyaltyConfig::spec_xdr(),
+        &contract::Points::spec_xdr(),
+        &contract::PhaseAccess::spec_xdr(),
+        &contract::SalePhase::spec_xdr(),
+        &contract::VestingSchedule::spec_xdr(),
+        &contract::VestingBalance::spec_xdr(),
+        &contract::ReceiptMode::spec_xdr(),
+        &contract::Receipt::spec_xdr(),
+        &contract::VoucherStatus::spec_xdr(),
+        &contract::Voucher::spec_xdr(),
+        &contract::Rating::spec_xdr(),
+        &contract::Review::spec_xdr(),
+        &contract::DisputeConfig::spec_xdr(),
+        &contract::Escrow::spec_xdr(),
+        &contract::DisputeStatus::spec_xdr(),
+        &contract::Ruling::spec_xdr(),
+        &contract::Dispute::spec_xdr(),
+        &contract::ProposalAction::spec_xdr(),
+        &contract::Proposal::spec_xdr(),
+        &contract::DataKey::spec_xdr(),
+        &contract::Error::spec_xdr(),
+        &contract::OrderError::spec_xdr(),
+    ]
+    .into_iter()
+    .map(spec)
+    .collect()
+}
+
+fn name_of(entry: &ScSpecEntry) -> String {
+    match entry {
+        ScSpecEntry::FunctionV0(f) => f.name.0.to_utf8_string().unwrap(),
+        ScSpecEntry::UdtStructV0(s) => s.name.to_utf8_string().unwrap(),
+        ScSpecEntry::UdtUnionV0(u) => u.name.to_utf8_string().unwrap(),
+        ScSpecEntry::UdtEnumV0(e) => e.name.to_utf8_string().unwrap(),
+        ScSpecEntry::UdtErrorEnumV0(e) => e.name.to_utf8_string().unwrap(),
+    }
+}
+
+/// Whether `val` is a valid encoding of the spec type `ty`.
+fn conforms(val: &ScVal, ty: &ScSpecTypeDef, udts: &[ScSpecEntry]) -> bool {
+    match (ty, val) {
+        (ScSpecTypeDef::Bool, ScVal::Bool(_))
+        | (ScSpecTypeDef::U32, ScVal::U32(_))
+        | (ScSpecTypeDef::U64, ScVal::U64(_))
+        | (ScSpecTypeDef::I128, ScVal::I128(_))
+        | (ScSpecTypeDef::String, ScVal::String(_))
+        | (ScSpecTypeDef::Symbol, ScVal::Symbol(_))
+        | (ScSpecTypeDef::Bytes, ScVal::Bytes(_))
+        | (ScSpecTypeDef::Address, ScVal::Address(_)) => true,
+        (ScSpecTypeDef::Option(_), ScVal::Void) => true,
+        (ScSpecTypeDef::Option(option), val) => conforms(val, &option.value_type, udts),
+        (ScSpecTypeDef::BytesN(n), ScVal::Bytes(bytes)) => bytes.len() == n.n as usize,
+        (ScSpecTypeDef::Vec(vec), ScVal::Vec(Some(items))) => items
+            .iter()
+            .all(|item| conforms(item, &vec.element_type, udts)),
+        (ScSpecTypeDef::Tuple(tuple), ScVal::Vec(Some(items))) => {
+            items.len() == tuple.value_types.len()
+                && tuple
+                    .value_types
+                    .iter()
+                    .zip(items.iter())
+                    .all(|(ty, item)| conforms(item, ty, udts))
+        }
+        (ScSpecTypeDef::Udt(udt), _) => {
+            let name = udt.name.to_utf8_string().unwrap();
+            let entry = udts
+                .iter()
+                .find(|entry| name_of(entry) == name)
+                .unwrap_or_else(|| panic!("type {name} is not in the spec"));
+            conforms_udt(val, entry, udts)
+        }
+        _ => false,
+    }
+}
+
+fn conforms_udt(val: &ScVal, entry: &ScSpecEntry, udts: &[ScSpecEntry]) -> bool {
+    match (entry, val) {
+        (ScSpecEntry::UdtStructV0(spec), ScVal::Map(Some(map))) => {
+            map.len() == spec.fields.len()
+                && spec.fields.iter().all(|field| {
+                    let key = scval::symbol(&field.name.to_utf8_string().unwrap());
+                    map.iter()
+                        .any(|entry| entry.key == key && conforms(&entry.val, &field.type_, udts))
+                })
+        }
+        (ScSpecEntry::UdtUnionV0(spec), ScVal::Vec(Some(items))) => {
+            spec.cases.iter().any(|case| match case {
+                ScSpecUdtUnionCaseV0::VoidV0(case) => {
+                    items.as_slice() == [scval::symbol(&case.name.to_utf8_string().unwrap())]
+                }
+                ScSpecUdtUnionCaseV0::TupleV0(case) => {
+                    items.len() == case.type_.len() + 1
+                        && items[0] == scval::symbol(&case.name.to_utf8_string().unwrap())
+                        && case
+                            .type_
+                            .iter()
+                            .zip(items.iter().skip(1))
+                            .all(|(ty, item)| conforms(item, ty, udts))
+                }
+            })
+        }
+        _ => false,
+    }
+}
+
+fn input() -> ProductInput {
+    ProductInput {
+        title: "title".into(),
+        description: "description".into(),
+        category: "category".into(),
+        expiry: 10,
+        image: "image".into(),
+        price: 1000,
+        target: 5,
+    }
+}
+
+fn metadata() -> ProductMetadata {
+    ProductMetadata {
+        hash: [3; 32],
+        uri: "ipfs://hat".into(),
+    }
+}
+
+fn coupon() -> Coupon {
+    Coupon {
+        discount_bps: 1000,
+        expiry: 10,
+        uses_left: 2,
+    }
+}
+
+fn loyalty() -> LoyaltyConfig {
+    LoyaltyConfig {
+        earn_rate: 10,
+        burn_rate: 100,
+        ttl: 1000,
+    }
+}
+
+fn disputes() -> DisputeConfig {
+    DisputeConfig {
+        window: 1000,
+        response_window: 500,
+    }
+}
+
+fn phases() -> Vec<SalePhase> {
+    vec![
+        SalePhase {
+            access: PhaseAccess::Merkle([3; 32]),
+            start: 10,
+            end: 20,
+            cap: 5,
+        },
+        SalePhase {
+            access: PhaseAccess::Public,
+            start: 20,
+            end: 30,
+            cap: 0,
+        },
+    ]
+}
+
+fn vesting() -> VestingSchedule {
+    VestingSchedule {
+        start: 10,
+        cliff: 100,
+        duration: 1000,
+    }
+}
+
+fn limits() -> ProductLimits {
+    ProductLimits {
+        title: 16,
+        description: 256,
+        category: 16,
+        image: 64,
+    }
+}
+
+#[test]
+fn invocations_match_contract_spec() {
+    let udts = udts();
+    let client = Client::new(contract_address());
+    let split = Split {
+        reserve: 50,
+        launchpad: 20,
+        dev: 30,
+    };
+
+    let calls: Vec<(InvokeContractArgs, &[u8])> = vec![
+        (
+            client
+                .initialize(
+                    &account(1),
+                    &account(2),
+                    &account(3),
+                    &account(4),
+                    &[account(4)],
+                    1,
+                )
+                .into_args(),
+            &contract::__SPEC_XDR_FN_INITIALIZE,
+        ),
+        (
+            client
+                .create_product("title", "description", "category", 10, "image", 1000, 5)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_CREATE_PRODUCT,
+        ),
+        (
+            client
+                .create_product_with_metadata("title", "category", 10, 1000, 5, &metadata())
+                .into_args(),
+            &contract::__SPEC_XDR_FN_CREATE_PRODUCT_WITH_METADATA,
+        ),
+        (
+            client.create_products_batch(&[input()]).into_args(),
+            &contract::__SPEC_XDR_FN_CREATE_PRODUCTS_BATCH,
+        ),
+        (
+            client.get_products().into_args(),
+            &contract::__SPEC_XDR_FN_GET_PRODUCTS,
+        ),
+        (
+            client.export_products().into_args(),
+            &contract::__SPEC_XDR_FN_EXPORT_PRODUCTS,
+        ),
+        (
+            client.get_product(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_PRODUCT,
+        ),
+        (
+            client.get_product_view(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_PRODUCT_VIEW,
+        ),
+        (
+            client.get_product_views().into_args(),
+            &contract::__SPEC_XDR_FN_GET_PRODUCT_VIEWS,
+        ),
+        (
+            client.get_product_metadata(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_PRODUCT_METADATA,
+        ),
+        (
+            client.verify_metadata(1, b"blob").into_args(),
+            &contract::__SPEC_XDR_FN_VERIFY_METADATA,
+        ),
+        (
+            client
+                .get_discount(1, &account(5), 1, &account(6), Some(&account(7)))
+                .into_args(),
+            &contract::__SPEC_XDR_FN_GET_DISCOUNT,
+        ),
+        (
+            client.add_variant(1, "Large", 5, 3).into_args(),
+            &contract::__SPEC_XDR_FN_ADD_VARIANT,
+        ),
+        (
+            client.get_variants(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_VARIANTS,
+        ),
+        (
+            client
+                .purchase_variant(1, 2, &account(1), &account(2), None)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PURCHASE_VARIANT,
+        ),
+        (
+            client.create_bundle("Set", &[1, 2, 2], 25).into_args(),
+            &contract::__SPEC_XDR_FN_CREATE_BUNDLE,
+        ),
+        (
+            client.get_bundle(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_BUNDLE,
+        ),
+        (
+            client
+                .purchase_bundle(1, &account(1), &account(2), None)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PURCHASE_BUNDLE,
+        ),
+        (
+            client
+                .checkout(
+                    &account(1),
+                    &[(1, 2), (3, 1)],
+                    &account(2),
+                    Some("TEN"),
+                    None,
+                )
+                .into_args(),
+            &contract::__SPEC_XDR_FN_CHECKOUT,
+        ),
+        (
+            client
+                .checkout(&account(1), &[(1, 2)], &account(2), None, None)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_CHECKOUT,
+        ),
+        (
+            client
+                .set_referral_config(&ReferralConfig {
+                    bps: 500,
+                    source: ReferralSource::Reserve,
+                })
+                .into_args(),
+            &contract::__SPEC_XDR_FN_SET_REFERRAL_CONFIG,
+        ),
+        (
+            client
+                .purchase_with_points(1, &account(1), 3, &account(2), 50, None)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PURCHASE_WITH_POINTS,
+        ),
+        (
+            client
+                .set_receipt_mode(1, ReceiptMode::Transferable)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_SET_RECEIPT_MODE,
+        ),
+        (
+            client.get_receipt_mode(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_RECEIPT_MODE,
+        ),
+        (
+            client.get_receipt(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_RECEIPT,
+        ),
+        (
+            client.owner_of(1).into_args(),
+            &contract::__SPEC_XDR_FN_OWNER_OF,
+        ),
+        (
+            client
+                .transfer_receipt(&account(1), &account(2), 1)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_TRANSFER_RECEIPT,
+        ),
+        (
+            client
+                .transfer_receipt_from(&account(3), &account(1), &account(2), 1)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_TRANSFER_RECEIPT_FROM,
+        ),
+        (
+            client
+                .approve_receipt(&account(1), 1, Some(&account(3)))
+                .into_args(),
+            &contract::__SPEC_XDR_FN_APPROVE_RECEIPT,
+        ),
+        (
+            client.approve_receipt(&account(1), 1, None).into_args(),
+            &contract::__SPEC_XDR_FN_APPROVE_RECEIPT,
+        ),
+        (
+            client.get_receipt_approval(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_RECEIPT_APPROVAL,
+        ),
+        (
+            client.set_voucher_validity(1, 3600).into_args(),
+            &contract::__SPEC_XDR_FN_SET_VOUCHER_VALIDITY,
+        ),
+        (
+            client.disable_vouchers(1).into_args(),
+            &contract::__SPEC_XDR_FN_DISABLE_VOUCHERS,
+        ),
+        (
+            client.get_voucher_validity(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_VOUCHER_VALIDITY,
+        ),
+        (
+            client.set_redeemer(1, &account(1), true).into_args(),
+            &contract::__SPEC_XDR_FN_SET_REDEEMER,
+        ),
+        (
+            client.is_redeemer(1, &account(1)).into_args(),
+            &contract::__SPEC_XDR_FN_IS_REDEEMER,
+        ),
+        (
+            client.get_voucher(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_VOUCHER,
+        ),
+        (
+            client.get_voucher_count(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_VOUCHER_COUNT,
+        ),
+        (
+            client.get_outstanding_vouchers(1, 0, 25).into_args(),
+            &contract::__SPEC_XDR_FN_GET_OUTSTANDING_VOUCHERS,
+        ),
+        (
+            client.redeem(&account(1), 1).into_args(),
+            &contract::__SPEC_XDR_FN_REDEEM,
+        ),
+        (
+            client.refund_voucher(1).into_args(),
+            &contract::__SPEC_XDR_FN_REFUND_VOUCHER,
+        ),
+        (
+            client.review(1, 2, 5, &[7; 32]).into_args(),
+            &contract::__SPEC_XDR_FN_REVIEW,
+        ),
+        (
+            client.get_review(1, 2).into_args(),
+            &contract::__SPEC_XDR_FN_GET_REVIEW,
+        ),
+        (
+            client
+                .review_purchase(&account(1), 2, 5, &[7; 32])
+                .into_args(),
+            &contract::__SPEC_XDR_FN_REVIEW_PURCHASE,
+        ),
+        (
+            client.get_purchase_review(&account(1), 2).into_args(),
+            &contract::__SPEC_XDR_FN_GET_PURCHASE_REVIEW,
+        ),
+        (
+            client.get_rating(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_RATING,
+        ),
+        (
+            client.get_escrow(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_ESCROW,
+        ),
+        (
+            client.release_escrow(1).into_args(),
+            &contract::__SPEC_XDR_FN_RELEASE_ESCROW,
+        ),
+        (
+            client.open_dispute(1, &[1; 32]).into_args(),
+            &contract::__SPEC_XDR_FN_OPEN_DISPUTE,
+        ),
+        (
+            client.respond_dispute(1, &[2; 32]).into_args(),
+            &contract::__SPEC_XDR_FN_RESPOND_DISPUTE,
+        ),
+        (
+            client.resolve_dispute(1, &Ruling::Split(2500)).into_args(),
+            &contract::__SPEC_XDR_FN_RESOLVE_DISPUTE,
+        ),
+        (
+            client.get_dispute(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_DISPUTE,
+        ),
+        (
+            client.get_points(&account(1)).into_args(),
+            &contract::__SPEC_XDR_FN_GET_POINTS,
+        ),
+        (
+            client
+                .purchase_with_proof(1, &account(1), 3, &account(2), &[[1; 32]], None)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PURCHASE_WITH_PROOF,
+        ),
+        (
+            client.set_sale_phases(1, &phases()).into_args(),
+            &contract::__SPEC_XDR_FN_SET_SALE_PHASES,
+        ),
+        (
+            client.get_sale_phases(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_SALE_PHASES,
+        ),
+        (
+            client.get_phase_sold(1, 0).into_args(),
+            &contract::__SPEC_XDR_FN_GET_PHASE_SOLD,
+        ),
+        (
+            client
+                .set_allowlist(1, &[account(1), account(2)], true)
+                .into_args(),
+            &contract::__SPEC_XDR_FN_SET_ALLOWLIST,
+        ),
+        (
+            client.is_allowlisted(1, &account(1)).into_args(),
+            &contract::__SPEC_XDR_FN_IS_ALLOWLISTED,
+        ),
+        (
+            client.set_loyalty_config(&loyalty()).into_args(),
+            &contract::__SPEC_XDR_FN_SET_LOYALTY_CONFIG,
+        ),
+        (
+            client.get_loyalty_config().into_args(),
+            &contract::__SPEC_XDR_FN_GET_LOYALTY_CONFIG,
+        ),
+        (
+            client.set_dispute_config(&disputes()).into_args(),
+            &contract::__SPEC_XDR_FN_SET_DISPUTE_CONFIG,
+        ),
+        (
+            client.get_dispute_config().into_args(),
+            &contract::__SPEC_XDR_FN_GET_DISPUTE_CONFIG,
+        ),
+        (
+            client.set_arbiter(&account(5)).into_args(),
+            &contract::__SPEC_XDR_FN_SET_ARBITER,
+        ),
+        (
+            client.get_arbiter().into_args(),
+            &contract::__SPEC_XDR_FN_GET_ARBITER,
+        ),
+        (
+            client.get_referral_config().into_args(),
+            &contract::__SPEC_XDR_FN_GET_REFERRAL_CONFIG,
+        ),
+        (
+            client
+                .get_referral_earnings(&account(1), &account(2))
+                .into_args(),
+            &contract::__SPEC_XDR_FN_GET_REFERRAL_EARNINGS,
+        ),
+        (
+            client.get_order(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_ORDER,
+        ),
+        (
+            client.set_coupon("TEN", &coupon()).into_args(),
+            &contract::__SPEC_XDR_FN_SET_COUPON,
+        ),
+        (
+            client.remove_coupon("TEN").into_args(),
+            &contract::__SPEC_XDR_FN_REMOVE_COUPON,
+        ),
+        (
+            client.get_coupon("TEN").into_args(),
+            &contract::__SPEC_XDR_FN_GET_COUPON,
+        ),
+        (
+            client.get_reserve_acc().into_args(),
+            &contract::__SPEC_XDR_FN_GET_RESERVE_ACC,
+        ),
+        (
+            client.get_dev_acc().into_args(),
+            &contract::__SPEC_XDR_FN_GET_DEV_ACC,
+        ),
+        (
+            client.get_launchpad_acc().into_args(),
+            &contract::__SPEC_XDR_FN_GET_LAUNCHPAD_ACC,
+        ),
+        (
+            client.get_admin().into_args(),
+            &contract::__SPEC_XDR_FN_GET_ADMIN,
+        ),
+        (
+            client.get_split().into_args(),
+            &contract::__SPEC_XDR_FN_GET_SPLIT,
+        ),
+        (
+            client
+                .set_reserve_acc(&account(1), &[account(4)])
+                .into_args(),
+            &contract::__SPEC_XDR_FN_SET_RESERVE_ACC,
+        ),
+        (
+            client.set_dev_acc(&account(2), &[account(4)]).into_args(),
+            &contract::__SPEC_XDR_FN_SET_DEV_ACC,
+        ),
+        (
+            client
+                .set_launchpad_acc(&account(3), &[account(4)])
+                .into_args(),
+            &contract::__SPEC_XDR_FN_SET_LAUNCHPAD_ACC,
+        ),
+        (
+            client.set_payout_mode(PayoutMode::Accrue).into_args(),
+            &contract::__SPEC_XDR_FN_SET_PAYOUT_MODE,
+        ),
+        (
+            client.get_payout_mode().into_args(),
+            &contract::__SPEC_XDR_FN_GET_PAYOUT_MODE,
+        ),
+        (
+            client.set_paused(true).into_args(),
+            &contract::__SPEC_XDR_FN_SET_PAUSED,
+        ),
+        (
+            client.is_paused().into_args(),
+            &contract::__SPEC_XDR_FN_IS_PAUSED,
+        ),
+        (
+            client.set_product_limits(&limits()).into_args(),
+            &contract::__SPEC_XDR_FN_SET_PRODUCT_LIMITS,
+        ),
+        (
+            client.get_product_limits().into_args(),
+            &contract::__SPEC_XDR_FN_GET_PRODUCT_LIMITS,
+        ),
+        (
+            client.withdraw(&account(1), &account(6), 1).into_args(),
+            &contract::__SPEC_XDR_FN_WITHDRAW,
+        ),
+        (
+            client.get_claimable(&account(1), &account(6)).into_args(),
+            &contract::__SPEC_XDR_FN_GET_CLAIMABLE,
+        ),
+        (
+            client.claim_vested(&account(1), &account(6)).into_args(),
+            &contract::__SPEC_XDR_FN_CLAIM_VESTED,
+        ),
+        (
+            client.get_vested(&account(1), &account(6)).into_args(),
+            &contract::__SPEC_XDR_FN_GET_VESTED,
+        ),
+        (
+            client.get_unvested(&account(1), &account(6)).into_args(),
+            &contract::__SPEC_XDR_FN_GET_UNVESTED,
+        ),
+        (
+            client.get_vesting_schedule(&account(1)).into_args(),
+            &contract::__SPEC_XDR_FN_GET_VESTING_SCHEDULE,
+        ),
+        (
+            client
+                .propose(&account(4), &ProposalAction::SetSplit(split))
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PROPOSE,
+        ),
+        (
+            client
+                .propose(&account(4), &ProposalAction::Upgrade([1; 32]))
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PROPOSE,
+        ),
+        (
+            client
+                .propose(
+                    &account(4),
+                    &ProposalAction::SetVesting(account(3), vesting()),
+                )
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PROPOSE,
+        ),
+        (
+            client
+                .propose(&account(4), &ProposalAction::RemoveVesting(account(3)))
+                .into_args(),
+            &contract::__SPEC_XDR_FN_PROPOSE,
+        ),
+        (
+            client.approve(&account(4), 1).into_args(),
+            &contract::__SPEC_XDR_FN_APPROVE,
+        ),
+        (
+            client.execute(1).into_args(),
+            &contract::__SPEC_XDR_FN_EXECUTE,
+        ),
+        (
+            client.get_proposal(1).into_args(),
+            &contract::__SPEC_XDR_FN_GET_PROPOSAL,
+        ),
+        (
+            client.get_signers().into_args(),
+            &contract::__SPEC_XDR_FN_GET_SIGNERS,
+        ),
+        (
+            client.get_threshold().into_args(),
+            &contract::__SPEC_XDR_FN_GET_THRESHOLD,
+        ),
+    ];
+
+    for (args, xdr) in calls {
+        let ScSpecEntry::FunctionV0(function) = spec(xdr) else {
+            panic!("not a function spec");
+        };
+        let name = function.name.0.to_utf8_string().unwrap();
+        assert_eq!(args.function_name.0.to_utf8_string().unwrap(), name);
+        assert_eq!(args.args.len(), function.inputs.len(), "{name} arity");
+        for (arg, input) in args.args.iter().zip(function.inputs.iter()) {
+            assert!(
+                conforms(arg, &input.type_, &udts),
+                "{name}: argument {} doesn't match the spec: {arg:?}",
+                input.name.to_utf8_string().unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn types_match_contract_spec() {
+    let udts = udts();
+    let udt = |name: &str| {
+        udts.iter()
+            .find(|entry| name_of(entry) == name)
+            .unwrap()
+            .clone()
+    };
+
+    let product = Product {
+        id: 1,
+        title: "title".into(),
+        description: "description".into(),
+        category: "category".into(),
+        expiry: 10,
+        image: "image".into(),
+        price: 1000,
+        remaining: 5,
+    };
+    assert!(conforms_udt(&product.to_scval(), &udt("Product"), &udts));
+    let rating = Rating {
+        count: 2,
+        total: 9,
+        average: 450,
+    };
+    assert!(conforms_udt(&rating.to_scval(), &udt("Rating"), &udts));
+    assert_eq!(
+        Product::from_scval(&product.to_scval()),
+        Ok(product.clone())
+    );
+    let variant = Variant {
+        id: 1,
+        name: "Large".into(),
+        price_delta: -5,
+        remaining: 2,
+    };
+    assert!(conforms_udt(&variant.to_scval(), &udt("Variant"), &udts));
+    assert_eq!(
+        Variant::from_scval(&variant.to_scval()),
+        Ok(variant.clone())
+    );
+    let view = ProductView {
+        product: product.clone(),
+        variants: vec![variant],
+        rating: rating.clone(),
+    };
+    assert!(conforms_udt(&view.to_scval(), &udt("ProductView"), &udts));
+    assert_eq!(ProductView::from_scval(&view.to_scval()), Ok(view));
+    assert!(conforms_udt(
+        &input().to_scval(),
+        &udt("ProductInput"),
+        &udts
+    ));
+    assert_eq!(ProductInput::from_scval(&input().to_scval()), Ok(input()));
+    assert_eq!(MAX_BATCH_SIZE, contract::MAX_BATCH_SIZE as usize);
+    assert_eq!(MAX_VARIANTS, contract::MAX_VARIANTS as usize);
+    assert_eq!(MAX_RATING, contract::MAX_RATING);
+    let review = Review {
+        order_id: 1,
+        product_id: 2,
+        rating: 4,
+        hash: [7; 32],
+    };
+    assert!(conforms_udt(&review.to_scval(), &udt("Review"), &udts));
+    assert_eq!(Review::from_scval(&review.to_scval()), Ok(review));
+    assert!(conforms_udt(
+        &limits().to_scval(),
+        &udt("ProductLimits"),
+        &udts
+    ));
+    assert_eq!(
+        ProductLimits::from_scval(&limits().to_scval()),
+        Ok(limits())
+    );
+    let bundle = Bundle {
+        id: 1,
+        title: "Set".into(),
+        product_ids: vec![1, 2, 2],
+        price: 25,
+    };
+    assert!(conforms_udt(&bundle.to_scval(), &udt("Bundle"), &udts));
+    assert_eq!(Bundle::from_scval(&bundle.to_scval()), Ok(bundle));
+    let points = Points {
+        balance: 50,
+        expires_at: 1000,
+    };
+    assert!(conforms_udt(&points.to_scval(), &udt("Points"), &udts));
+    assert_eq!(Points::from_scval(&points.to_scval()), Ok(points));
+    assert_eq!(MAX_PHASES, contract::MAX_PHASES as usize);
+    for phase in phases() {
+        assert!(conforms_udt(&phase.to_scval(), &udt("SalePhase"), &udts));
+        assert_eq!(SalePhase::from_scval(&phase.to_scval()), Ok(phase));
+    }
+    assert!(conforms_udt(
+        &PhaseAccess::Allowlist.to_scval(),
+        &udt("PhaseAccess"),
+        &udts
+    ));
+    assert!(conforms_udt(
+        &vesting().to_scval(),
+        &udt("VestingSchedule"),
+        &udts
+    ));
+    let balance = VestingBalance {
+        deposited: 30,
+        claimed: 10,
+    };
+    assert!(conforms_udt(
+        &balance.to_scval(),
+        &udt("VestingBalance"),
+        &udts
+    ));
+    assert_eq!(VestingBalance::from_scval(&balance.to_scval()), Ok(balance));
+    for action in [
+        ProposalAction::SetVesting(account(3), vesting()),
+        ProposalAction::RemoveVesting(account(3)),
+    ] {
+        assert_eq!(ProposalAction::from_scval(&action.to_scval()), Ok(action));
+    }
+    for mode in [
+        ReceiptMode::Off,
+        ReceiptMode::NonTransferable,
+        ReceiptMode::Transferable,
+    ] {
+        assert!(conforms_udt(&mode.to_scval(), &udt("ReceiptMode"), &udts));
+        assert_eq!(ReceiptMode::from_scval(&mode.to_scval()), Ok(mode));
+    }
+    let receipt = Receipt {
+        id: 1,
+        owner: account(1),
+        product_id: 2,
+        order_id: 3,
+    };
+    assert!(conforms_udt(&receipt.to_scval(), &udt("Receipt"), &udts));
+    assert_eq!(Receipt::from_scval(&receipt.to_scval()), Ok(receipt));
+    let voucher = Voucher {
+        id: 1,
+        product_id: 2,
+        holder: account(1),
+        order_id: 0,
+        token: account(2),
+        amount: 10,
+        expires_at: 1000,
+        status: VoucherStatus::Expired,
+    };
+    assert!(conforms_udt(&voucher.to_scval(), &udt("Voucher"), &udts));
+    assert_eq!(Voucher::from_scval(&voucher.to_scval()), Ok(voucher));
+    for status in [
+        VoucherStatus::Issued,
+        VoucherStatus::Redeemed,
+        VoucherStatus::Refunded,
+    ] {
+        assert_eq!(VoucherStatus::from_scval(&status.to_scval()), Ok(status));
+    }
+    assert!(conforms_udt(
+        &disputes().to_scval(),
+        &udt("DisputeConfig"),
+        &udts
+    ));
+    assert_eq!(
+        DisputeConfig::from_scval(&disputes().to_scval()),
+        Ok(disputes())
+    );
+    let escrow = Escrow {
+        order_id: 1,
+        amount: 10,
+        release_at: 1000,
+    };
+    assert!(conforms_udt(&escrow.to_scval(), &udt("Escrow"), &udts));
+    assert_eq!(Escrow::from_scval(&escrow.to_scval()), Ok(escrow));
+    let dispute = Dispute {
+        order_id: 1,
+        status: DisputeStatus::Split,
+        claim: [1; 32],
+        response: [2; 32],
+        respond_by: 500,
+        refunded: 5,
+    };
+    assert!(conforms_udt(&dispute.to_scval(), &udt("Dispute"), &udts));
+    assert_eq!(Dispute::from_scval(&dispute.to_scval()), Ok(dispute));
+    for status in [
+        DisputeStatus::Open,
+        DisputeStatus::Answered,
+        DisputeStatus::Refunded,
+        DisputeStatus::Released,
+    ] {
+        assert_eq!(DisputeStatus::from_scval(&status.to_scval()), Ok(status));
+    }
+    for ruling in [Ruling::Refund, Ruling::Release, Ruling::Split(2500)] {
+        assert!(conforms_udt(&ruling.to_scval(), &udt("Ruling"), &udts));
+        assert_eq!(Ruling::from_scval(&ruling.to_scval()), Ok(ruling));
+    }
+    assert!(conforms_udt(&coupon().to_scval(), &udt("Coupon"), &udts));
+    assert_eq!(Coupon::from_scval(&coupon().to_scval()), Ok(coupon()));
+
+    let keys = [
+        DataKey::ReserveAccount,
+        DataKey::DevAccount,
+        DataKey::LaunchpadAccount,
+        DataKey::Admin,
+        DataKey::Split,
+        DataKey::Signers,
+        DataKey::Threshold,
+        DataKey::NoOfProposals,
+        DataKey::Proposal(3),
+        DataKey::PayoutMode,
+        DataKey::Claimable(account(1), account(2)),
+        DataKey::Paused,
+        DataKey::ProductLimits,
+        DataKey::Metadata(3),
+        DataKey::Coupon("TEN".into()),
+        DataKey::NoOfOrders,
+        DataKey::Order(1),
+        DataKey::NoOfBundles,
+        DataKey::Bundle(2),
+        DataKey::ReferralConfig,
+        DataKey::ReferralEarnings(account(1), account(2)),
+        DataKey::LoyaltyConfig,
+        DataKey::Points(account(1)),
+        DataKey::SalePhases(2),
+        DataKey::PhaseSold(2, 1),
+        DataKey::Allowlist(2, account(1)),
+        DataKey::VestingSchedule(account(3)),
+        DataKey::Vesting(account(3), account(2)),
+        DataKey::ReceiptMode(2),
+        DataKey::NoOfReceipts,
+        DataKey::Receipt(1),
+        DataKey::ReceiptApproval(1),
+        DataKey::VoucherValidity(2),
+        DataKey::NoOfVouchers,
+        DataKey::Voucher(1),
+        DataKey::ProductVouchers(2),
+        DataKey::ProductVoucher(2, 3),
+        DataKey::Redeemer(2, account(1)),
+        DataKey::Review(1, 2),
+        DataKey::DisputeConfig,
+        DataKey::Arbiter,
+        DataKey::Escrow(1),
+        DataKey::EscrowReferrer(1),
+        DataKey::Dispute(1),
+        DataKey::Variants(1),
+        DataKey::Rating(1),
+        DataKey::Purchased(account(1), 2),
+        DataKey::PurchaseReview(account(1), 2),
+        DataKey::OrderGrants(3),
+    ];
+    let ScSpecEntry::UdtUnionV0(data_key) = udt("DataKey") else {
+        panic!("DataKey is a union");
+    };
+    assert_eq!(
+        keys.len(),
+        data_key.cases.len(),
+        "every DataKey is mirrored"
+    );
+    for key in keys {
+        assert!(conforms_udt(&key.to_scval(), &udt("DataKey"), &udts));
+        assert_eq!(DataKey::from_scval(&key.to_scval()), Ok(key));
+    }
+
+    let cases = |name: &str| -> Vec<(String, u32)> {
+        let ScSpecEntry::UdtErrorEnumV0(errors) = udt(name) else {
+            panic!("{name} is an error enum");
+        };
+        errors
+            .cases
+            .iter()
+            .map(|case| (case.name.to_utf8_string().unwrap(), case.value))
+            .collect()
+    };
+    let mirrored: Vec<(String, u32)> = Error::ALL
+        .iter()
+        .map(|error| (format!("{error:?}"), error.code()))
+        .collect();
+    assert_eq!(mirrored, cases("Error"));
+    let mirrored: Vec<(String, u32)> = OrderError::ALL
+        .iter()
+        .map(|error| (format!("{error:?}"), error.code()))
+        .collect();
+    assert_eq!(mirrored, cases("OrderError"));
+}
+
+/// Runs `call` against the contract in the test host and decodes its result
+/// with the SDK.
+fn invoke<T: FromScVal>(env: &Env, contract_id: &Address, call: Invocation<T>) -> T {
+    let args = call.args();
+    let mut vals = soroban_sdk::Vec::<Val>::new(env);
+    for arg in args.args.iter() {
+        vals.push_back(Val::try_from_val(env, arg).unwrap());
+    }
+    let function = Symbol::new(env, &args.function_name.0.to_utf8_string().unwrap());
+    let result: Val = env.invoke_contract(contract_id, &function, vals);
+    call.decode_result(&ScVal::try_from_val(env, &result).unwrap())
+        .unwrap()
+}
+
+fn sc_address(address: &Address) -> ScAddress {
+    match ScVal::try_from(address).unwrap() {
+        ScVal::Address(address) => address,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn invocations_round_trip_through_the_contract() {
+    let env = Env::default();
+    // The budget covers the whole test rather than one transaction.
+    env.budget().reset_unlimited();
+    let market = contract::testutils::Marketplace::setup(&env);
+    let client = Client::new(sc_address(&market.contract_id));
+    let buyer = market.funded_buyer(1_000_000_000_000);
+    let expiry = env.ledger().timestamp() + 1000;
+
+    let created = invoke(
+        &env,
+        &market.contract_id,
+        client.create_product("Hat", "A hat", "Clothes", expiry, "hat.png", 500, 3),
+    );
+    assert_eq!(
+        created,
+        Product {
+            id: 1,
+            title: "Hat".into(),
+            description: "A hat".into(),
+            category: "Clothes".into(),
+            expiry,
+            image: "hat.png".into(),
+            price: 500,
+            remaining: 3,
+        }
+    );
+
+    let shares = invoke(
+        &env,
+        &market.contract_id,
+        client.get_discount(
+            1,
+            &sc_address(&buyer),
+            10,
+            &sc_address(&market.token_id),
+            None,
+        ),
+    );
+    assert_eq!(shares, (60_000_000, 10_000_000, 30_000_000));
+    let products = invoke(&env, &market.contract_id, client.get_products());
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].remaining, 2);
+
+    let catalog: Vec<ProductInput> = (0..MAX_BATCH_SIZE + 2)
+        .map(|i| ProductInput {
+            title: format!("Item {i}"),
+            expiry,
+            ..input()
+        })
+        .collect();
+    let batches = client.import_catalog(&catalog);
+    assert_eq!(batches.len(), 2);
+    let ids: Vec<u32> = batches
+        .into_iter()
+        .flat_map(|batch| invoke(&env, &market.contract_id, batch))
+        .map(|product| product.id)
+        .collect();
+    assert_eq!(ids, (2..=MAX_BATCH_SIZE as u32 + 3).collect::<Vec<_>>());
+    let exported = invoke(&env, &market.contract_id, client.export_products());
+    assert_eq!(exported[1..], catalog[..]);
+
+    let blob = b"{\"description\":\"A hat\"}";
+    let metadata = ProductMetadata {
+        hash: env
+            .crypto()
+            .sha256(&soroban_sdk::Bytes::from_slice(&env, blob))
+            .to_array(),
+        uri: "ipfs://hat".into(),
+    };
+    let with_metadata = invoke(
+        &env,
+        &market.contract_id,
+        client.create_product_with_metadata("Hat", "Clothes", expiry, 500, 3, &metadata),
+    );
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.get_product_metadata(with_metadata.id)
+        ),
+        Some(metadata)
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_product_metadata(1)),
+        None
+    );
+    assert!(invoke(
+        &env,
+        &market.contract_id,
+        client.verify_metadata(with_metadata.id, blob)
+    ));
+    assert!(!invoke(
+        &env,
+        &market.contract_id,
+        client.verify_metadata(with_metadata.id, b"{}")
+    ));
+
+    let variant = invoke(
+        &env,
+        &market.contract_id,
+        client.add_variant(1, "Large", 100, 1),
+    );
+    let shares = invoke(
+        &env,
+        &market.contract_id,
+        client.purchase_variant(
+            1,
+            variant.id,
+            &sc_address(&buyer),
+            &sc_address(&market.token_id),
+            None,
+        ),
+    );
+    assert_eq!(shares, (3_600_000_000, 600_000_000, 1_800_000_000));
+    let product = invoke(&env, &market.contract_id, client.get_product(1));
+    assert_eq!(product.remaining, 1);
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_variants(1)),
+        vec![Variant {
+            remaining: 0,
+            ..variant.clone()
+        }]
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_product_view(1)),
+        ProductView {
+            product,
+            variants: vec![Variant {
+                remaining: 0,
+                ..variant
+            }],
+            rating: Rating::default(),
+        }
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_product_views()).len(),
+        invoke(&env, &market.contract_id, client.get_products()).len()
+    );
+
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_coupon("TEN", &Coupon { expiry, ..coupon() }),
+    );
+    let order_id = invoke(
+        &env,
+        &market.contract_id,
+        client.checkout(
+            &sc_address(&buyer),
+            &[(2, 1), (3, 2)],
+            &sc_address(&market.token_id),
+            Some("TEN"),
+            None,
+        ),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_order(order_id)),
+        Some(Order {
+            id: order_id,
+            buyer: sc_address(&buyer),
+            token: sc_address(&market.token_id),
+            items: vec![(2, 1), (3, 2)],
+            discount: 3_000_000_000,
+            total: 27_000_000_000,
+        })
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_coupon("TEN")).map(|c| c.uses_left),
+        Some(1)
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.review(order_id, 3, 4, &[7; 32]),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_review(order_id, 3)),
+        Some(Review {
+            order_id,
+            product_id: 3,
+            rating: 4,
+            hash: [7; 32],
+        })
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_rating(3)),
+        Rating {
+            count: 1,
+            total: 4,
+            average: 400,
+        }
+    );
+
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_dispute_config(&disputes()),
+    );
+    invoke(&env, &market.contract_id, client.set_arbiter(&account(5)));
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_arbiter()),
+        Some(account(5))
+    );
+    let escrowed = invoke(
+        &env,
+        &market.contract_id,
+        client.checkout(
+            &sc_address(&buyer),
+            &[(8, 1)],
+            &sc_address(&market.token_id),
+            None,
+            None,
+        ),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_escrow(escrowed)),
+        Some(Escrow {
+            order_id: escrowed,
+            amount: 10_000_000_000,
+            release_at: env.ledger().timestamp() + 1000,
+        })
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.open_dispute(escrowed, &[1; 32]),
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.respond_dispute(escrowed, &[2; 32]),
+    );
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.resolve_dispute(escrowed, &Ruling::Split(5000))
+        ),
+        5_000_000_000
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_dispute(escrowed)),
+        Some(Dispute {
+            order_id: escrowed,
+            status: DisputeStatus::Split,
+            claim: [1; 32],
+            response: [2; 32],
+            respond_by: env.ledger().timestamp() + 500,
+            refunded: 5_000_000_000,
+        })
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_dispute_config(&DisputeConfig::default()),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_dispute_config()),
+        DisputeConfig::default()
+    );
+
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_loyalty_config(&loyalty()),
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.get_discount(
+            4,
+            &sc_address(&buyer),
+            10,
+            &sc_address(&market.token_id),
+            None,
+        ),
+    );
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.get_points(&sc_address(&buyer))
+        ),
+        100
+    );
+    let shares = invoke(
+        &env,
+        &market.contract_id,
+        client.purchase_with_points(
+            4,
+            &sc_address(&buyer),
+            10,
+            &sc_address(&market.token_id),
+            100,
+            None,
+        ),
+    );
+    assert_eq!(shares, (54_000_000, 9_000_000, 27_000_000));
+
+    let bundle = invoke(
+        &env,
+        &market.contract_id,
+        client.create_bundle("Set", &[2, 3, 3], 2500),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_bundle(bundle.id)),
+        Some(bundle.clone())
+    );
+    let shares = invoke(
+        &env,
+        &market.contract_id,
+        client.purchase_bundle(
+            bundle.id,
+            &sc_address(&buyer),
+            &sc_address(&market.token_id),
+            None,
+        ),
+    );
+    assert_eq!(shares, (15_000_000_000, 2_500_000_000, 7_500_000_000));
+    let remaining: Vec<i128> = invoke(&env, &market.contract_id, client.get_products())[1..3]
+        .iter()
+        .map(|product| product.remaining)
+        .collect();
+    assert_eq!(remaining, vec![3, 1]);
+
+    let others: Vec<ScAddress> = (1..5).map(account).collect();
+    let leaves: Vec<[u8; 32]> = others
+        .iter()
+        .chain([sc_address(&buyer)].iter())
+        .map(merkle::leaf)
+        .collect();
+    let now = env.ledger().timestamp();
+    let presale = vec![
+        SalePhase {
+            access: PhaseAccess::Merkle(merkle::root(&leaves)),
+            start: now,
+            end: now + 100,
+            cap: 2,
+        },
+        SalePhase {
+            access: PhaseAccess::Allowlist,
+            start: now + 100,
+            end: now + 200,
+            cap: 0,
+        },
+    ];
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_sale_phases(5, &presale),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_sale_phases(5)),
+        presale
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.purchase_with_proof(
+            5,
+            &sc_address(&buyer),
+            10,
+            &sc_address(&market.token_id),
+            &merkle::proof(&leaves, 4),
+            None,
+        ),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_phase_sold(5, 0)),
+        1
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_allowlist(5, &[sc_address(&buyer)], true),
+    );
+    assert!(invoke(
+        &env,
+        &market.contract_id,
+        client.is_allowlisted(5, &sc_address(&buyer))
+    ));
+
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_receipt_mode(2, ReceiptMode::Transferable),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_receipt_mode(2)),
+        ReceiptMode::Transferable
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.get_discount(
+            2,
+            &sc_address(&buyer),
+            10,
+            &sc_address(&market.token_id),
+            None,
+        ),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_receipt(1)),
+        Receipt {
+            id: 1,
+            owner: sc_address(&buyer),
+            product_id: 2,
+            order_id: 0,
+        }
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.approve_receipt(&sc_address(&buyer), 1, Some(&account(3))),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_receipt_approval(1)),
+        Some(account(3))
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.transfer_receipt_from(&account(3), &sc_address(&buyer), &account(1), 1),
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.transfer_receipt(&account(1), &account(2), 1),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.owner_of(1)),
+        account(2)
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.approve_receipt(&account(2), 1, None),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_receipt_approval(1)),
+        None
+    );
+
+    let token = sc_address(&market.token_id);
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_voucher_validity(7, 3600),
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_voucher_validity(7)),
+        Some(3600)
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.set_redeemer(7, &account(1), true),
+    );
+    assert!(invoke(
+        &env,
+        &market.contract_id,
+        client.is_redeemer(7, &account(1))
+    ));
+    for _ in 0..2 {
+        invoke(
+            &env,
+            &market.contract_id,
+            client.get_discount(7, &sc_address(&buyer), 10, &token, None),
+        );
+    }
+    invoke(
+        &env,
+        &market.contract_id,
+        client.review_purchase(&sc_address(&buyer), 7, 5, &[7; 32]),
+    );
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.get_purchase_review(&sc_address(&buyer), 7)
+        )
+        .map(|review| review.rating),
+        Some(5)
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_voucher(1)),
+        Voucher {
+            id: 1,
+            product_id: 7,
+            holder: sc_address(&buyer),
+            order_id: 0,
+            token: token.clone(),
+            amount: 100_000_000,
+            expires_at: env.ledger().timestamp() + 3600,
+            status: VoucherStatus::Issued,
+        }
+    );
+    invoke(&env, &market.contract_id, client.redeem(&account(1), 1));
+    market.mint(&market.reserve_acc, 100_000_000);
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.refund_voucher(2)),
+        60_000_000
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_voucher(2)).status,
+        VoucherStatus::Refunded
+    );
+    assert!(invoke(
+        &env,
+        &market.contract_id,
+        client.get_outstanding_vouchers(7, 0, MAX_BATCH_SIZE as u32)
+    )
+    .is_empty());
+    invoke(&env, &market.contract_id, client.disable_vouchers(7));
+
+    let split = Split {
+        reserve: 50,
+        launchpad: 25,
+        dev: 25,
+    };
+    let admin = sc_address(&market.admin);
+    let proposal_id = invoke(
+        &env,
+        &market.contract_id,
+        client.propose(&admin, &ProposalAction::SetSplit(split.clone())),
+    );
+    let proposal = invoke(&env, &market.contract_id, client.get_proposal(proposal_id));
+    assert_eq!(proposal.action, ProposalAction::SetSplit(split));
+
+    let launchpad = sc_address(&market.launchpad_acc);
+    // Starts once the proposal's timelock has run out.
+    let schedule = VestingSchedule {
+        start: env.ledger().timestamp() + 2 * 24 * 60 * 60,
+        cliff: 0,
+        duration: 100,
+    };
+    let vesting_id = invoke(
+        &env,
+        &market.contract_id,
+        client.propose(
+            &admin,
+            &ProposalAction::SetVesting(launchpad.clone(), schedule.clone()),
+        ),
+    );
+    market.set_time(invoke(&env, &market.contract_id, client.get_proposal(vesting_id)).eta);
+    invoke(&env, &market.contract_id, client.execute(vesting_id));
+    market.set_time(schedule.start);
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.get_vesting_schedule(&launchpad)
+        ),
+        Some(schedule)
+    );
+    invoke(
+        &env,
+        &market.contract_id,
+        client.get_discount(
+            6,
+            &sc_address(&buyer),
+            10,
+            &sc_address(&market.token_id),
+            None,
+        ),
+    );
+    market.advance_time(50);
+    let token = sc_address(&market.token_id);
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.get_unvested(&launchpad, &token)
+        ),
+        5_000_000
+    );
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.get_vested(&launchpad, &token)
+        ),
+        5_000_000
+    );
+    assert_eq!(
+        invoke(
+            &env,
+            &market.contract_id,
+            client.claim_vested(&launchpad, &token)
+        ),
+        5_000_000
+    );
+    assert_eq!(proposal.approvals, vec![admin.clone()]);
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_signers()),
+        vec![admin]
+    );
+    assert_eq!(
+        invoke(&env, &market.contract_id, client.get_payout_mode()),
+        PayoutMode::Push
+    );
+
+    let missing = client.get_proposal(99);
+    let args = missing.args();
+    let result = env.try_invoke_contract::<Val, soroban_sdk::Error>(
+        &market.contract_id,
+        &Symbol::new(&env, "get_proposal"),
+        soroban_sdk::vec![&env, Val::try_from_val(&env, &args.args[0]).unwrap()],
+    );
+    let Err(Ok(error)) = result else {
+        panic!("expected a contract error, got {result:?}");
+    };
+    assert_eq!(
+        Error::from_sc_error(&ScError::try_from(error).unwrap()),
+        Some(Error::ProposalNotExist)
+    );
+}
+
+#[test]
+fn events_decode() {
+    let env = Env::default();
+    let market = contract::testutils::Marketplace::setup(&env);
+    let contract_client = market.client();
+    let buyer = market.funded_buyer(1_000_000_000_000);
+    let product = market.product().create();
+    let referrer = Address::generate(&env);
+    contract_client.set_referral_config(&contract::ReferralConfig {
+        bps: 5000,
+        source: contract::ReferralSource::Launchpad,
+    });
+    contract_client.set_receipt_mode(&product.id, &contract::ReceiptMode::Transferable);
+    contract_client.set_voucher_validity(&product.id, &0);
+    contract_client.get_discount(
+        &product.id,
+        &buyer,
+        &1,
+        &market.token_id,
+        &Some(referrer.clone()),
+    );
+    contract_client.approve_receipt(&buyer, &1, &Some(referrer.clone()));
+    contract_client.transfer_receipt_from(&referrer, &buyer, &referrer, &1);
+    contract_client.approve_receipt(&referrer, &1, &None);
+    contract_client.set_redeemer(&product.id, &referrer, &true);
+    contract_client.redeem(&referrer, &1);
+    let escrowed_at = env.ledger().timestamp();
+    contract_client.set_dispute_config(&contract::DisputeConfig {
+        window: 1000,
+        response_window: 500,
+    });
+    contract_client.set_arbiter(&referrer);
+    let plain = market.product().create();
+    let items = soroban_sdk::vec![&env, (plain.id, 1)];
+    let disputed = contract_client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    let released = contract_client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    contract_client.open_dispute(&disputed, &soroban_sdk::BytesN::from_array(&env, &[1; 32]));
+    contract_client.respond_dispute(&disputed, &soroban_sdk::BytesN::from_array(&env, &[2; 32]));
+    contract_client.resolve_dispute(&disputed, &contract::Ruling::Refund);
+    contract_client.set_payout_mode(&contract::PayoutMode::Accrue);
+    contract_client.set_paused(&true);
+    contract_client.set_product_limits(&contract::ProductLimits::DEFAULT);
+    contract_client.add_variant(
+        &product.id,
+        &soroban_sdk::String::from_str(&env, "Large"),
+        &5,
+        &2,
+    );
+    let code = Symbol::new(&env, "TEN");
+    let contract_coupon = contract::Coupon {
+        discount_bps: 1000,
+        expiry: 10,
+        uses_left: 2,
+    };
+    contract_client.set_coupon(&code, &contract_coupon);
+    contract_client.remove_coupon(&code);
+    contract_client.set_loyalty_config(&contract::LoyaltyConfig {
+        earn_rate: 10,
+        burn_rate: 100,
+        ttl: 1000,
+    });
+    contract_client.set_sale_phases(
+        &product.id,
+        &soroban_sdk::vec![
+            &env,
+            contract::SalePhase {
+                access: contract::PhaseAccess::Merkle(soroban_sdk::BytesN::from_array(
+                    &env, &[3; 32]
+                )),
+                start: 10,
+                end: 20,
+                cap: 5,
+            },
+            contract::SalePhase {
+                access: contract::PhaseAccess::Public,
+                start: 20,
+                end: 30,
+                cap: 0,
+            },
+        ],
+    );
+    contract_client.set_allowlist(&product.id, &soroban_sdk::vec![&env, buyer.clone()], &false);
+    let proposal_id = contract_client.propose(
+        &market.admin,
+        &contract::ProposalAction::RemoveVesting(market.dev_acc.clone()),
+    );
+    market.set_time(contract_client.get_proposal(&proposal_id).eta);
+    contract_client.execute(&proposal_id);
+    contract_client.release_escrow(&released);
+
+    let decoded: Vec<Event> = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract_id, _, _)| *contract_id == market.contract_id)
+        .map(|(_, topics, data)| {
+            let topics: Vec<ScVal> = topics
+                .iter()
+                .map(|topic| ScVal::try_from_val(&env, &topic).unwrap())
+                .collect();
+            Event::decode(&topics, &ScVal::try_from_val(&env, &data).unwrap()).unwrap()
+        })
+        .collect();
+
+    let admin = sc_address(&market.admin);
+    let token = sc_address(&market.token_id);
+    let product_event = |remaining| ProductEvent {
+        product_id: product.id,
+        price: product.price,
+        remaining,
+        expiry: product.expiry,
+    };
+    let plain_event = |remaining| ProductEvent {
+        product_id: plain.id,
+        price: plain.price,
+        remaining,
+        expiry: plain.expiry,
+    };
+    let escrow = |order_id| Escrow {
+        order_id,
+        amount: 10_000_000_000,
+        release_at: escrowed_at + 1000,
+    };
+    let order_event = |order_id| OrderEvent {
+        order_id,
+        buyer: sc_address(&buyer),
+        token: token.clone(),
+        discount: 0,
+        total: 10_000_000_000,
+    };
+    let dispute = |status, response, refunded| Dispute {
+        order_id: disputed,
+        status,
+        claim: [1; 32],
+        response,
+        respond_by: escrowed_at + 500,
+        refunded,
+    };
+    assert_eq!(
+        decoded,
+        vec![
+            Event::Init(InitEvent {
+                admin: admin.clone(),
+                reserve_acc: sc_address(&market.reserve_acc),
+                dev_acc: sc_address(&market.dev_acc),
+                launchpad_acc: sc_address(&market.launchpad_acc),
+                signers: vec![admin.clone()],
+                threshold: 1,
+            }),
+            Event::ProductCreated(product_event(10)),
+            Event::ReferralConfigChanged(ReferralConfig {
+                bps: 5000,
+                source: ReferralSource::Launchpad,
+            }),
+            Event::ReceiptModeChanged(ReceiptModeEvent {
+                product_id: product.id,
+                mode: ReceiptMode::Transferable,
+            }),
+            Event::VoucherValiditySet(VoucherValidityEvent {
+                product_id: product.id,
+                validity: 0,
+            }),
+            Event::Referral(ReferralEvent {
+                referrer: sc_address(&referrer),
+                buyer: sc_address(&buyer),
+                token: token.clone(),
+                amount: 500_000,
+            }),
+            Event::Purchase(PurchaseEvent {
+                buyer: sc_address(&buyer),
+                product_id: product.id,
+                token: token.clone(),
+                quantity: 1,
+                reserve_amount: 6_000_000,
+                launchpad_amount: 500_000,
+                dev_amount: 3_000_000,
+            }),
+            Event::ProductUpdated(product_event(9)),
+            Event::ReceiptMinted(Receipt {
+                id: 1,
+                owner: sc_address(&buyer),
+                product_id: product.id,
+                order_id: 0,
+            }),
+            Event::VoucherIssued(Voucher {
+                id: 1,
+                product_id: product.id,
+                holder: sc_address(&buyer),
+                order_id: 0,
+                token: token.clone(),
+                amount: 10_000_000,
+                expires_at: 0,
+                status: VoucherStatus::Issued,
+            }),
+            Event::ReceiptApproved(ReceiptApprovalEvent {
+                receipt_id: 1,
+                owner: sc_address(&buyer),
+                spender: sc_address(&referrer),
+            }),
+            Event::ReceiptTransferred(ReceiptTransferEvent {
+                receipt_id: 1,
+                from: sc_address(&buyer),
+                to: sc_address(&referrer),
+            }),
+            Event::ReceiptApprovalRevoked(1),
+            Event::RedeemerChanged(RedeemerEvent {
+                product_id: product.id,
+                redeemer: sc_address(&referrer),
+                allowed: true,
+            }),
+            Event::VoucherRedeemed(RedeemEvent {
+                voucher_id: 1,
+                product_id: product.id,
+                redeemer: sc_address(&referrer),
+            }),
+            Event::DisputeConfigChanged(disputes()),
+            Event::ArbiterChanged(sc_address(&referrer)),
+            Event::ProductCreated(plain_event(10)),
+            Event::ProductUpdated(plain_event(9)),
+            Event::Escrowed(escrow(disputed)),
+            Event::Order(order_event(disputed)),
+            Event::ProductUpdated(plain_event(8)),
+            Event::Escrowed(escrow(released)),
+            Event::Order(order_event(released)),
+            Event::DisputeOpened(dispute(DisputeStatus::Open, [0; 32], 0)),
+            Event::DisputeAnswered(dispute(DisputeStatus::Answered, [2; 32], 0)),
+            Event::ProductUpdated(plain_event(9)),
+            Event::DisputeResolved(dispute(DisputeStatus::Refunded, [2; 32], 10_000_000_000,)),
+            Event::PayoutModeChanged(PayoutMode::Accrue),
+            Event::PausedChanged(true),
+            Event::ProductLimitsChanged(ProductLimits {
+                title: 64,
+                description: 1024,
+                category: 32,
+                image: 256,
+            }),
+            Event::VariantUpdated(VariantEvent {
+                product_id: product.id,
+                variant_id: 1,
+                price: product.price + 5,
+                remaining: 2,
+            }),
+            Event::CouponSet(CouponEvent {
+                code: "TEN".into(),
+                coupon: coupon(),
+            }),
+            Event::CouponRemoved("TEN".into()),
+            Event::LoyaltyConfigChanged(loyalty()),
+            Event::SalePhasesChanged(SalePhasesEvent {
+                product_id: product.id,
+                phases: phases(),
+            }),
+            Event::AllowlistChanged(AllowlistEvent {
+                product_id: product.id,
+                accounts: vec![sc_address(&buyer)],
+                allowed: false,
+            }),
+            Event::ProposalCreated(ProposalEvent {
+                proposal_id,
+                signer: admin.clone(),
+            }),
+            Event::VestingRemoved(sc_address(&market.dev_acc)),
+            Event::ProposalExecuted(Proposal {
+                id: proposal_id,
+                proposer: admin.clone(),
+                action: ProposalAction::RemoveVesting(sc_address(&market.dev_acc)),
+                approvals: vec![admin.clone()],
+                eta: contract_client.get_proposal(&proposal_id).eta,
+                executed: true,
+            }),
+            Event::EscrowReleased(EscrowReleaseEvent {
+                order_id: released,
+                reserve_amount: 6_000_000_000,
+                launchpad_amount: 1_000_000_000,
+                dev_amount: 3_000_000_000,
+            }),
+        ]
+    );
+
+    let topics = |name| {
+        [
+            scval::symbol("market"),
+            scval::symbol("v1"),
+            scval::symbol(name),
+        ]
+    };
+    assert_eq!(
+        Event::decode(&topics("rcpt_burn"), &3u32.to_scval()),
+        Ok(Event::ReceiptBurned(3))
+    );
+    assert_eq!(
+        Event::decode(&topics("vchr_void"), &4u32.to_scval()),
+        Ok(Event::VoucherVoided(4))
+    );
+    assert_eq!(
+        Event::decode(&[scval::symbol("transfer")], &ScVal::Void),
+        Err(DecodeError::UnknownEvent)
+    );
+}
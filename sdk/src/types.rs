@@ -0,0 +1,1539 @@
+//! Mirrors of the contract's `#[contracttype]`s, `Error` and `OrderError`.
+
+use std::fmt;
+
+use stellar_xdr::curr::{ScAddress, ScError, ScVal};
+
+use crate::scval::{
+    decode_variant, encode_struct, encode_variant, only_field, symbol, symbol_name, DecodeError,
+    Fields, FromScVal, ToScVal,
+};
+
+/// Errors returned by the contract, surfaced off chain as
+/// `ScError::Contract(code)`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u32)]
+pub enum Error {
+    DiscountExpired = 1,
+    ProductNotExist = 2,
+    /// No longer returned; `get_discount` reports a zero amount as
+    /// `AmountMustBeGreaterThanZero`. Kept so the other codes stay put.
+    AmountMustNonZero = 3,
+    TargetReached = 4,
+    AmountExceedTargetLimit = 5,
+    ProductAlreadyExist = 6,
+    IdProductMustNonZero = 7,
+    LowAmountForSplitter = 8,
+    ExpiryShouldBeFuture = 9,
+    AlreadyInitialized = 10,
+    AmountMustBeGreaterThanZero = 11,
+    InvalidThreshold = 12,
+    NotSigner = 13,
+    ProposalNotExist = 14,
+    AlreadyApproved = 15,
+    ThresholdNotReached = 16,
+    TimelockNotElapsed = 17,
+    ProposalAlreadyExecuted = 18,
+    InvalidSplit = 19,
+    InsufficientClaimable = 20,
+    Paused = 21,
+    EmptyBatch = 22,
+    BatchTooLarge = 23,
+    EmptyTitle = 24,
+    TitleTooLong = 25,
+    DescriptionTooLong = 26,
+    CategoryTooLong = 27,
+    ImageTooLong = 28,
+    PriceMustBePositive = 29,
+    TargetMustBePositive = 30,
+    InvalidProductLimits = 31,
+    NoMetadata = 32,
+    VariantNotExist = 33,
+    VariantRequired = 34,
+    TooManyVariants = 35,
+    InvalidCoupon = 36,
+    BundleNotExist = 37,
+    SelfReferral = 38,
+    InsufficientPoints = 39,
+    InvalidLoyaltyConfig = 40,
+    SaleNotActive = 41,
+    NotAllowlisted = 42,
+    InvalidSchedule = 43,
+    ReceiptNotExist = 44,
+    NotReceiptOwner = 45,
+    NotTransferable = 46,
+    VoucherNotExist = 47,
+    InvalidVoucherState = 48,
+    NotRedeemer = 49,
+}
+
+impl Error {
+    pub const ALL: [Error; 49] = [
+        Error::DiscountExpired,
+        Error::ProductNotExist,
+        Error::AmountMustNonZero,
+        Error::TargetReached,
+        Error::AmountExceedTargetLimit,
+        Error::ProductAlreadyExist,
+        Error::IdProductMustNonZero,
+        Error::LowAmountForSplitter,
+        Error::ExpiryShouldBeFuture,
+        Error::AlreadyInitialized,
+        Error::AmountMustBeGreaterThanZero,
+        Error::InvalidThreshold,
+        Error::NotSigner,
+        Error::ProposalNotExist,
+        Error::AlreadyApproved,
+        Error::ThresholdNotReached,
+        Error::TimelockNotElapsed,
+        Error::ProposalAlreadyExecuted,
+        Error::InvalidSplit,
+        Error::InsufficientClaimable,
+        Error::Paused,
+        Error::EmptyBatch,
+        Error::BatchTooLarge,
+        Error::EmptyTitle,
+        Error::TitleTooLong,
+        Error::DescriptionTooLong,
+        Error::CategoryTooLong,
+        Error::ImageTooLong,
+        Error::PriceMustBePositive,
+        Error::TargetMustBePositive,
+        Error::InvalidProductLimits,
+        Error::NoMetadata,
+        Error::VariantNotExist,
+        Error::VariantRequired,
+        Error::TooManyVariants,
+        Error::InvalidCoupon,
+        Error::BundleNotExist,
+        Error::SelfReferral,
+        Error::InsufficientPoints,
+        Error::InvalidLoyaltyConfig,
+        Error::SaleNotActive,
+        Error::NotAllowlisted,
+        Error::InvalidSchedule,
+        Error::ReceiptNotExist,
+        Error::NotReceiptOwner,
+        Error::NotTransferable,
+        Error::VoucherNotExist,
+        Error::InvalidVoucherState,
+        Error::NotRedeemer,
+    ];
+
+    #[must_use]
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+
+    /// The contract error carried by a failed invocation, if it is one.
+    #[must_use]
+    pub fn from_sc_error(error: &ScError) -> Option<Self> {
+        match error {
+            ScError::Contract(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?} ({})", self.code())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Errors of reviews and disputes of checkout orders, with codes carrying
+/// on from `Error`'s.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u32)]
+pub enum OrderError {
+    OrderNotExist = 51,
+    NotBuyer = 52,
+    AlreadyReviewed = 53,
+    InvalidRating = 54,
+    NoEscrow = 55,
+    DisputeWindowClosed = 56,
+    DisputeWindowOpen = 57,
+    AlreadyDisputed = 58,
+    NoDispute = 59,
+    NoArbiter = 60,
+    ResponsePending = 61,
+    ResponseWindowClosed = 62,
+    DisputeClosed = 63,
+    InvalidDisputeConfig = 64,
+    OrderRefunded = 65,
+}
+
+impl OrderError {
+    pub const ALL: [OrderError; 15] = [
+        OrderError::OrderNotExist,
+        OrderError::NotBuyer,
+        OrderError::AlreadyReviewed,
+        OrderError::InvalidRating,
+        OrderError::NoEscrow,
+        OrderError::DisputeWindowClosed,
+        OrderError::DisputeWindowOpen,
+        OrderError::AlreadyDisputed,
+        OrderError::NoDispute,
+        OrderError::NoArbiter,
+        OrderError::ResponsePending,
+        OrderError::ResponseWindowClosed,
+        OrderError::DisputeClosed,
+        OrderError::InvalidDisputeConfig,
+        OrderError::OrderRefunded,
+    ];
+
+    #[must_use]
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+
+    /// The order error carried by a failed invocation, if it is one.
+    #[must_use]
+    pub fn from_sc_error(error: &ScError) -> Option<Self> {
+        match error {
+            ScError::Contract(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?} ({})", self.code())
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Product {
+    pub id: u32,
+    pub title: String,
+    pub description: String,
+    pub category: String,
+    pub expiry: u64,
+    pub image: String,
+    pub price: i128,
+    pub remaining: i128,
+}
+
+impl ToScVal for Product {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("id", self.id.to_scval()),
+            ("title", self.title.to_scval()),
+            ("description", self.description.to_scval()),
+            ("category", self.category.to_scval()),
+            ("expiry", self.expiry.to_scval()),
+            ("image", self.image.to_scval()),
+            ("price", self.price.to_scval()),
+            ("remaining", self.remaining.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Product {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Product {
+            id: fields.get("id")?,
+            title: fields.get("title")?,
+            description: fields.get("description")?,
+            category: fields.get("category")?,
+            expiry: fields.get("expiry")?,
+            image: fields.get("image")?,
+            price: fields.get("price")?,
+            remaining: fields.get("remaining")?,
+        })
+    }
+}
+
+/// Best rating a buyer can give; the worst is 1.
+pub const MAX_RATING: u32 = 5;
+
+/// A product's aggregated reviews. `average` is in hundredths of a star.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rating {
+    pub count: u32,
+    pub total: u32,
+    pub average: u32,
+}
+
+impl ToScVal for Rating {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("count", self.count.to_scval()),
+            ("total", self.total.to_scval()),
+            ("average", self.average.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Rating {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Rating {
+            count: fields.get("count")?,
+            total: fields.get("total")?,
+            average: fields.get("average")?,
+        })
+    }
+}
+
+/// A buyer's review of one product in one of their orders. `hash` points
+/// at the review text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Review {
+    pub order_id: u32,
+    pub product_id: u32,
+    pub rating: u32,
+    pub hash: [u8; 32],
+}
+
+impl ToScVal for Review {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("order_id", self.order_id.to_scval()),
+            ("product_id", self.product_id.to_scval()),
+            ("rating", self.rating.to_scval()),
+            ("hash", self.hash.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Review {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Review {
+            order_id: fields.get("order_id")?,
+            product_id: fields.get("product_id")?,
+            rating: fields.get("rating")?,
+            hash: fields.get("hash")?,
+        })
+    }
+}
+
+/// A discount code: `discount_bps` off a whole checkout, usable `uses_left`
+/// more times until `expiry`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coupon {
+    pub discount_bps: u32,
+    pub expiry: u64,
+    pub uses_left: u32,
+}
+
+impl ToScVal for Coupon {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("discount_bps", self.discount_bps.to_scval()),
+            ("expiry", self.expiry.to_scval()),
+            ("uses_left", self.uses_left.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Coupon {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Coupon {
+            discount_bps: fields.get("discount_bps")?,
+            expiry: fields.get("expiry")?,
+            uses_left: fields.get("uses_left")?,
+        })
+    }
+}
+
+/// A completed checkout; `items` are `(product_id, quantity)` pairs and the
+/// amounts are in stroops.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order {
+    pub id: u32,
+    pub buyer: ScAddress,
+    pub token: ScAddress,
+    pub items: Vec<(u32, u32)>,
+    pub discount: i128,
+    pub total: i128,
+}
+
+impl FromScVal for Order {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Order {
+            id: fields.get("id")?,
+            buyer: fields.get("buyer")?,
+            token: fields.get("token")?,
+            items: fields.get("items")?,
+            discount: fields.get("discount")?,
+            total: fields.get("total")?,
+        })
+    }
+}
+
+/// Most variants a single product can have.
+pub const MAX_VARIANTS: usize = 10;
+
+/// An option of a product with its own stock, costing the product's price
+/// plus `price_delta`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variant {
+    pub id: u32,
+    pub name: String,
+    pub price_delta: i128,
+    pub remaining: i128,
+}
+
+impl ToScVal for Variant {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("id", self.id.to_scval()),
+            ("name", self.name.to_scval()),
+            ("price_delta", self.price_delta.to_scval()),
+            ("remaining", self.remaining.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Variant {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Variant {
+            id: fields.get("id")?,
+            name: fields.get("name")?,
+            price_delta: fields.get("price_delta")?,
+            remaining: fields.get("remaining")?,
+        })
+    }
+}
+
+/// A product together with its variants and rating, as `get_product_view`
+/// returns it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProductView {
+    pub product: Product,
+    pub variants: Vec<Variant>,
+    pub rating: Rating,
+}
+
+impl ToScVal for ProductView {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("product", self.product.to_scval()),
+            ("variants", self.variants.to_scval()),
+            ("rating", self.rating.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for ProductView {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ProductView {
+            product: fields.get("product")?,
+            variants: fields.get("variants")?,
+            rating: fields.get("rating")?,
+        })
+    }
+}
+
+/// Several products sold together at `price`; a product listed twice is two
+/// units of it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bundle {
+    pub id: u32,
+    pub title: String,
+    pub product_ids: Vec<u32>,
+    pub price: i128,
+}
+
+impl ToScVal for Bundle {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("id", self.id.to_scval()),
+            ("title", self.title.to_scval()),
+            ("product_ids", self.product_ids.to_scval()),
+            ("price", self.price.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Bundle {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Bundle {
+            id: fields.get("id")?,
+            title: fields.get("title")?,
+            product_ids: fields.get("product_ids")?,
+            price: fields.get("price")?,
+        })
+    }
+}
+
+/// Most products `create_products_batch` accepts in one call.
+pub const MAX_BATCH_SIZE: usize = 25;
+
+/// A product as listed, before the contract assigns its id; `target` becomes
+/// its `remaining` stock. Also what `export_products` returns.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProductInput {
+    pub title: String,
+    pub description: String,
+    pub category: String,
+    pub expiry: u64,
+    pub image: String,
+    pub price: i128,
+    pub target: i128,
+}
+
+impl ToScVal for ProductInput {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("title", self.title.to_scval()),
+            ("description", self.description.to_scval()),
+            ("category", self.category.to_scval()),
+            ("expiry", self.expiry.to_scval()),
+            ("image", self.image.to_scval()),
+            ("price", self.price.to_scval()),
+            ("target", self.target.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for ProductInput {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ProductInput {
+            title: fields.get("title")?,
+            description: fields.get("description")?,
+            category: fields.get("category")?,
+            expiry: fields.get("expiry")?,
+            image: fields.get("image")?,
+            price: fields.get("price")?,
+            target: fields.get("target")?,
+        })
+    }
+}
+
+/// Off-chain product description: `uri` points at a blob whose SHA-256 is
+/// `hash`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProductMetadata {
+    pub hash: [u8; 32],
+    pub uri: String,
+}
+
+impl ToScVal for ProductMetadata {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[("hash", self.hash.to_scval()), ("uri", self.uri.to_scval())])
+    }
+}
+
+impl FromScVal for ProductMetadata {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ProductMetadata {
+            hash: fields.get("hash")?,
+            uri: fields.get("uri")?,
+        })
+    }
+}
+
+/// Maximum byte lengths of a product's text fields, checked on creation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProductLimits {
+    pub title: u32,
+    pub description: u32,
+    pub category: u32,
+    pub image: u32,
+}
+
+impl ToScVal for ProductLimits {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("title", self.title.to_scval()),
+            ("description", self.description.to_scval()),
+            ("category", self.category.to_scval()),
+            ("image", self.image.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for ProductLimits {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ProductLimits {
+            title: fields.get("title")?,
+            description: fields.get("description")?,
+            category: fields.get("category")?,
+            image: fields.get("image")?,
+        })
+    }
+}
+
+/// Percentage shares of a payment; they always add up to 100.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Split {
+    pub reserve: i128,
+    pub launchpad: i128,
+    pub dev: i128,
+}
+
+impl ToScVal for Split {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("reserve", self.reserve.to_scval()),
+            ("launchpad", self.launchpad.to_scval()),
+            ("dev", self.dev.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Split {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Split {
+            reserve: fields.get("reserve")?,
+            launchpad: fields.get("launchpad")?,
+            dev: fields.get("dev")?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PayoutMode {
+    Push,
+    Accrue,
+}
+
+impl ToScVal for PayoutMode {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            PayoutMode::Push => encode_variant("Push", &[]),
+            PayoutMode::Accrue => encode_variant("Accrue", &[]),
+        }
+    }
+}
+
+impl FromScVal for PayoutMode {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match decode_variant(val)? {
+            (name, []) if name == "Push" => Ok(PayoutMode::Push),
+            (name, []) if name == "Accrue" => Ok(PayoutMode::Accrue),
+            (name, _) => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+/// Buyers earn `earn_rate` points per whole token paid and `burn_rate`
+/// points redeem for one whole token off a purchase. Balances expire `ttl`
+/// seconds after points were last earned; 0 keeps them forever.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoyaltyConfig {
+    pub earn_rate: u32,
+    pub burn_rate: u32,
+    pub ttl: u64,
+}
+
+impl ToScVal for LoyaltyConfig {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("earn_rate", self.earn_rate.to_scval()),
+            ("burn_rate", self.burn_rate.to_scval()),
+            ("ttl", self.ttl.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for LoyaltyConfig {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(LoyaltyConfig {
+            earn_rate: fields.get("earn_rate")?,
+            burn_rate: fields.get("burn_rate")?,
+            ttl: fields.get("ttl")?,
+        })
+    }
+}
+
+/// A buyer's stored loyalty balance, void once `expires_at` passes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Points {
+    pub balance: i128,
+    pub expires_at: u64,
+}
+
+impl ToScVal for Points {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("balance", self.balance.to_scval()),
+            ("expires_at", self.expires_at.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Points {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Points {
+            balance: fields.get("balance")?,
+            expires_at: fields.get("expires_at")?,
+        })
+    }
+}
+
+/// The payout share a referral commission is taken from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReferralSource {
+    Launchpad,
+    Reserve,
+}
+
+impl ToScVal for ReferralSource {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            ReferralSource::Launchpad => encode_variant("Launchpad", &[]),
+            ReferralSource::Reserve => encode_variant("Reserve", &[]),
+        }
+    }
+}
+
+impl FromScVal for ReferralSource {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match decode_variant(val)? {
+            (name, []) if name == "Launchpad" => Ok(ReferralSource::Launchpad),
+            (name, []) if name == "Reserve" => Ok(ReferralSource::Reserve),
+            (name, _) => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+/// Referrers earn `bps` of the `source` share of every purchase they refer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferralConfig {
+    pub bps: u32,
+    pub source: ReferralSource,
+}
+
+impl ToScVal for ReferralConfig {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("bps", self.bps.to_scval()),
+            ("source", self.source.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for ReferralConfig {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(ReferralConfig {
+            bps: fields.get("bps")?,
+            source: fields.get("source")?,
+        })
+    }
+}
+
+/// Most sale phases a single product can have.
+pub const MAX_PHASES: usize = 10;
+
+/// Who may buy during a sale phase. `Merkle` holds a tree root; see
+/// [`crate::merkle`] for building trees and proofs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PhaseAccess {
+    Public,
+    Allowlist,
+    Merkle([u8; 32]),
+}
+
+impl ToScVal for PhaseAccess {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            PhaseAccess::Public => encode_variant("Public", &[]),
+            PhaseAccess::Allowlist => encode_variant("Allowlist", &[]),
+            PhaseAccess::Merkle(root) => encode_variant("Merkle", &[root.to_scval()]),
+        }
+    }
+}
+
+impl FromScVal for PhaseAccess {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let (name, fields) = decode_variant(val)?;
+        match (name.as_str(), fields) {
+            ("Public", []) => Ok(PhaseAccess::Public),
+            ("Allowlist", []) => Ok(PhaseAccess::Allowlist),
+            ("Merkle", fields) => Ok(PhaseAccess::Merkle(only_field(fields)?)),
+            _ => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+/// A window `[start, end)` in which a product sells to `access`, capped at
+/// `cap` units across all buyers; a `cap` of 0 leaves it uncapped.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SalePhase {
+    pub access: PhaseAccess,
+    pub start: u64,
+    pub end: u64,
+    pub cap: i128,
+}
+
+impl ToScVal for SalePhase {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("access", self.access.to_scval()),
+            ("start", self.start.to_scval()),
+            ("end", self.end.to_scval()),
+            ("cap", self.cap.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for SalePhase {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(SalePhase {
+            access: fields.get("access")?,
+            start: fields.get("start")?,
+            end: fields.get("end")?,
+            cap: fields.get("cap")?,
+        })
+    }
+}
+
+/// Releases a recipient's payout shares linearly from `start` to
+/// `start + duration`, with nothing claimable before `start + cliff`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VestingSchedule {
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+impl ToScVal for VestingSchedule {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("start", self.start.to_scval()),
+            ("cliff", self.cliff.to_scval()),
+            ("duration", self.duration.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for VestingSchedule {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(VestingSchedule {
+            start: fields.get("start")?,
+            cliff: fields.get("cliff")?,
+            duration: fields.get("duration")?,
+        })
+    }
+}
+
+/// What a recipient has been paid into the vesting vault in one token, and
+/// how much of it they've claimed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VestingBalance {
+    pub deposited: i128,
+    pub claimed: i128,
+}
+
+impl ToScVal for VestingBalance {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("deposited", self.deposited.to_scval()),
+            ("claimed", self.claimed.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for VestingBalance {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(VestingBalance {
+            deposited: fields.get("deposited")?,
+            claimed: fields.get("claimed")?,
+        })
+    }
+}
+
+/// Whether buying a product mints a receipt, and whether that receipt can
+/// change hands afterwards.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReceiptMode {
+    Off,
+    NonTransferable,
+    Transferable,
+}
+
+impl ToScVal for ReceiptMode {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            ReceiptMode::Off => encode_variant("Off", &[]),
+            ReceiptMode::NonTransferable => encode_variant("NonTransferable", &[]),
+            ReceiptMode::Transferable => encode_variant("Transferable", &[]),
+        }
+    }
+}
+
+impl FromScVal for ReceiptMode {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match decode_variant(val)? {
+            (name, []) if name == "Off" => Ok(ReceiptMode::Off),
+            (name, []) if name == "NonTransferable" => Ok(ReceiptMode::NonTransferable),
+            (name, []) if name == "Transferable" => Ok(ReceiptMode::Transferable),
+            (name, _) => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+/// A non-fungible proof of purchase. `order_id` is 0 for a purchase made
+/// outside checkout.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    pub id: u32,
+    pub owner: ScAddress,
+    pub product_id: u32,
+    pub order_id: u32,
+}
+
+impl ToScVal for Receipt {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("id", self.id.to_scval()),
+            ("owner", self.owner.to_scval()),
+            ("product_id", self.product_id.to_scval()),
+            ("order_id", self.order_id.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Receipt {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Receipt {
+            id: fields.get("id")?,
+            owner: fields.get("owner")?,
+            product_id: fields.get("product_id")?,
+            order_id: fields.get("order_id")?,
+        })
+    }
+}
+
+/// Where a voucher is in its life. Issued vouchers read as `Expired` once
+/// their `expires_at` has passed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoucherStatus {
+    Issued,
+    Redeemed,
+    Expired,
+    Refunded,
+}
+
+impl ToScVal for VoucherStatus {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            VoucherStatus::Issued => encode_variant("Issued", &[]),
+            VoucherStatus::Redeemed => encode_variant("Redeemed", &[]),
+            VoucherStatus::Expired => encode_variant("Expired", &[]),
+            VoucherStatus::Refunded => encode_variant("Refunded", &[]),
+        }
+    }
+}
+
+impl FromScVal for VoucherStatus {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match decode_variant(val)? {
+            (name, []) if name == "Issued" => Ok(VoucherStatus::Issued),
+            (name, []) if name == "Redeemed" => Ok(VoucherStatus::Redeemed),
+            (name, []) if name == "Expired" => Ok(VoucherStatus::Expired),
+            (name, []) if name == "Refunded" => Ok(VoucherStatus::Refunded),
+            (name, _) => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+/// One purchased unit of a voucher product. `amount` is what the holder
+/// paid for it in stroops of `token`; `order_id` is 0 for a single purchase
+/// and `expires_at` is 0 for a voucher that never expires.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Voucher {
+    pub id: u32,
+    pub product_id: u32,
+    pub holder: ScAddress,
+    pub order_id: u32,
+    pub token: ScAddress,
+    pub amount: i128,
+    pub expires_at: u64,
+    pub status: VoucherStatus,
+}
+
+impl ToScVal for Voucher {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("id", self.id.to_scval()),
+            ("product_id", self.product_id.to_scval()),
+            ("holder", self.holder.to_scval()),
+            ("order_id", self.order_id.to_scval()),
+            ("token", self.token.to_scval()),
+            ("amount", self.amount.to_scval()),
+            ("expires_at", self.expires_at.to_scval()),
+            ("status", self.status.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Voucher {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Voucher {
+            id: fields.get("id")?,
+            product_id: fields.get("product_id")?,
+            holder: fields.get("holder")?,
+            order_id: fields.get("order_id")?,
+            token: fields.get("token")?,
+            amount: fields.get("amount")?,
+            expires_at: fields.get("expires_at")?,
+            status: fields.get("status")?,
+        })
+    }
+}
+
+/// How long checkout payments are held in escrow and how long sellers have
+/// to answer disputes, in seconds. A `window` of 0 turns escrow off.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisputeConfig {
+    pub window: u64,
+    pub response_window: u64,
+}
+
+impl ToScVal for DisputeConfig {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("window", self.window.to_scval()),
+            ("response_window", self.response_window.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for DisputeConfig {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(DisputeConfig {
+            window: fields.get("window")?,
+            response_window: fields.get("response_window")?,
+        })
+    }
+}
+
+/// An order's payment held by the contract until `release_at`, in stroops
+/// of the order's token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Escrow {
+    pub order_id: u32,
+    pub amount: i128,
+    pub release_at: u64,
+}
+
+impl ToScVal for Escrow {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("order_id", self.order_id.to_scval()),
+            ("amount", self.amount.to_scval()),
+            ("release_at", self.release_at.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Escrow {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Escrow {
+            order_id: fields.get("order_id")?,
+            amount: fields.get("amount")?,
+            release_at: fields.get("release_at")?,
+        })
+    }
+}
+
+/// Where a dispute is. The last three are the arbiter's rulings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DisputeStatus {
+    Open,
+    Answered,
+    Refunded,
+    Released,
+    Split,
+}
+
+impl ToScVal for DisputeStatus {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            DisputeStatus::Open => encode_variant("Open", &[]),
+            DisputeStatus::Answered => encode_variant("Answered", &[]),
+            DisputeStatus::Refunded => encode_variant("Refunded", &[]),
+            DisputeStatus::Released => encode_variant("Released", &[]),
+            DisputeStatus::Split => encode_variant("Split", &[]),
+        }
+    }
+}
+
+impl FromScVal for DisputeStatus {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        match decode_variant(val)? {
+            (name, []) if name == "Open" => Ok(DisputeStatus::Open),
+            (name, []) if name == "Answered" => Ok(DisputeStatus::Answered),
+            (name, []) if name == "Refunded" => Ok(DisputeStatus::Refunded),
+            (name, []) if name == "Released" => Ok(DisputeStatus::Released),
+            (name, []) if name == "Split" => Ok(DisputeStatus::Split),
+            (name, _) => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+/// The arbiter's decision on a dispute. `Split` refunds the buyer that
+/// share of the escrow in basis points and releases the rest.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ruling {
+    Refund,
+    Release,
+    Split(u32),
+}
+
+impl ToScVal for Ruling {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            Ruling::Refund => encode_variant("Refund", &[]),
+            Ruling::Release => encode_variant("Release", &[]),
+            Ruling::Split(bps) => encode_variant("Split", &[bps.to_scval()]),
+        }
+    }
+}
+
+impl FromScVal for Ruling {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let (name, fields) = decode_variant(val)?;
+        match (name.as_str(), fields) {
+            ("Refund", []) => Ok(Ruling::Refund),
+            ("Release", []) => Ok(Ruling::Release),
+            ("Split", _) => Ok(Ruling::Split(only_field(fields)?)),
+            _ => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+/// A buyer's dispute of an escrowed order. `claim` and `response` hash the
+/// buyer's and the seller's statements; `response` is all zeros until the
+/// seller answers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dispute {
+    pub order_id: u32,
+    pub status: DisputeStatus,
+    pub claim: [u8; 32],
+    pub response: [u8; 32],
+    pub respond_by: u64,
+    pub refunded: i128,
+}
+
+impl ToScVal for Dispute {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("order_id", self.order_id.to_scval()),
+            ("status", self.status.to_scval()),
+            ("claim", self.claim.to_scval()),
+            ("response", self.response.to_scval()),
+            ("respond_by", self.respond_by.to_scval()),
+            ("refunded", self.refunded.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Dispute {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Dispute {
+            order_id: fields.get("order_id")?,
+            status: fields.get("status")?,
+            claim: fields.get("claim")?,
+            response: fields.get("response")?,
+            respond_by: fields.get("respond_by")?,
+            refunded: fields.get("refunded")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProposalAction {
+    SetReserveAccount(ScAddress),
+    SetDevAccount(ScAddress),
+    SetLaunchpadAccount(ScAddress),
+    SetSplit(Split),
+    Upgrade([u8; 32]),
+    SetVesting(ScAddress, VestingSchedule),
+    RemoveVesting(ScAddress),
+}
+
+impl ToScVal for ProposalAction {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            ProposalAction::SetReserveAccount(acc) => {
+                encode_variant("SetReserveAccount", &[acc.to_scval()])
+            }
+            ProposalAction::SetDevAccount(acc) => {
+                encode_variant("SetDevAccount", &[acc.to_scval()])
+            }
+            ProposalAction::SetLaunchpadAccount(acc) => {
+                encode_variant("SetLaunchpadAccount", &[acc.to_scval()])
+            }
+            ProposalAction::SetSplit(split) => encode_variant("SetSplit", &[split.to_scval()]),
+            ProposalAction::Upgrade(hash) => encode_variant("Upgrade", &[hash.to_scval()]),
+            ProposalAction::SetVesting(recipient, schedule) => {
+                encode_variant("SetVesting", &[recipient.to_scval(), schedule.to_scval()])
+            }
+            ProposalAction::RemoveVesting(recipient) => {
+                encode_variant("RemoveVesting", &[recipient.to_scval()])
+            }
+        }
+    }
+}
+
+impl FromScVal for ProposalAction {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let (name, fields) = decode_variant(val)?;
+        match (name.as_str(), fields) {
+            ("SetReserveAccount", _) => Ok(ProposalAction::SetReserveAccount(only_field(fields)?)),
+            ("SetDevAccount", _) => Ok(ProposalAction::SetDevAccount(only_field(fields)?)),
+            ("SetLaunchpadAccount", _) => {
+                Ok(ProposalAction::SetLaunchpadAccount(only_field(fields)?))
+            }
+            ("SetSplit", _) => Ok(ProposalAction::SetSplit(only_field(fields)?)),
+            ("Upgrade", _) => Ok(ProposalAction::Upgrade(only_field(fields)?)),
+            ("SetVesting", [recipient, schedule]) => Ok(ProposalAction::SetVesting(
+                ScAddress::from_scval(recipient)?,
+                VestingSchedule::from_scval(schedule)?,
+            )),
+            ("RemoveVesting", _) => Ok(ProposalAction::RemoveVesting(only_field(fields)?)),
+            _ => Err(DecodeError::UnknownVariant(name)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Proposal {
+    pub id: u32,
+    pub proposer: ScAddress,
+    pub action: ProposalAction,
+    pub approvals: Vec<ScAddress>,
+    pub eta: u64,
+    pub executed: bool,
+}
+
+impl ToScVal for Proposal {
+    fn to_scval(&self) -> ScVal {
+        encode_struct(&[
+            ("id", self.id.to_scval()),
+            ("proposer", self.proposer.to_scval()),
+            ("action", self.action.to_scval()),
+            ("approvals", self.approvals.to_scval()),
+            ("eta", self.eta.to_scval()),
+            ("executed", self.executed.to_scval()),
+        ])
+    }
+}
+
+impl FromScVal for Proposal {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let fields = Fields::new(val)?;
+        Ok(Proposal {
+            id: fields.get("id")?,
+            proposer: fields.get("proposer")?,
+            action: fields.get("action")?,
+            approvals: fields.get("approvals")?,
+            eta: fields.get("eta")?,
+            executed: fields.get("executed")?,
+        })
+    }
+}
+
+/// Keys of the contract's instance storage. Products are stored under their
+/// `u32` id and the product counter under [`PRODUCT_COUNT_KEY`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataKey {
+    ReserveAccount,
+    DevAccount,
+    LaunchpadAccount,
+    Admin,
+    Split,
+    Signers,
+    Threshold,
+    NoOfProposals,
+    Proposal(u32),
+    PayoutMode,
+    Claimable(ScAddress, ScAddress),
+    Paused,
+    ProductLimits,
+    Metadata(u32),
+    /// Keyed by the coupon code, a symbol.
+    Coupon(String),
+    NoOfOrders,
+    Order(u32),
+    NoOfBundles,
+    Bundle(u32),
+    ReferralConfig,
+    ReferralEarnings(ScAddress, ScAddress),
+    LoyaltyConfig,
+    Points(ScAddress),
+    SalePhases(u32),
+    /// Units sold in a product's phase, keyed by product id and phase index.
+    PhaseSold(u32, u32),
+    Allowlist(u32, ScAddress),
+    VestingSchedule(ScAddress),
+    /// A recipient's vesting vault, keyed by recipient and token.
+    Vesting(ScAddress, ScAddress),
+    ReceiptMode(u32),
+    NoOfReceipts,
+    Receipt(u32),
+    ReceiptApproval(u32),
+    VoucherValidity(u32),
+    NoOfVouchers,
+    Voucher(u32),
+    /// Number of vouchers a product has issued.
+    ProductVouchers(u32),
+    /// Keyed by product id and the voucher's index among the product's;
+    /// holds the voucher id.
+    ProductVoucher(u32, u32),
+    Redeemer(u32, ScAddress),
+    /// Keyed by order id and product id.
+    Review(u32, u32),
+    DisputeConfig,
+    Arbiter,
+    Escrow(u32),
+    EscrowReferrer(u32),
+    Dispute(u32),
+    Variants(u32),
+    Rating(u32),
+    Purchased(ScAddress, u32),
+    PurchaseReview(ScAddress, u32),
+    OrderGrants(u32),
+}
+
+/// Symbol under which the contract keeps the number of products created.
+pub const PRODUCT_COUNT_KEY: &str = "PRODUCTS";
+
+impl ToScVal for DataKey {
+    fn to_scval(&self) -> ScVal {
+        match self {
+            DataKey::ReserveAccount => encode_variant("ReserveAccount", &[]),
+            DataKey::DevAccount => encode_variant("DevAccount", &[]),
+            DataKey::LaunchpadAccount => encode_variant("LaunchpadAccount", &[]),
+            DataKey::Admin => encode_variant("Admin", &[]),
+            DataKey::Split => encode_variant("Split", &[]),
+            DataKey::Signers => encode_variant("Signers", &[]),
+            DataKey::Threshold => encode_variant("Threshold", &[]),
+            DataKey::NoOfProposals => encode_variant("NoOfProposals", &[]),
+            DataKey::Proposal(id) => encode_variant("Proposal", &[id.to_scval()]),
+            DataKey::PayoutMode => encode_variant("PayoutMode", &[]),
+            DataKey::Claimable(recipient, token) => {
+                encode_variant("Claimable", &[recipient.to_scval(), token.to_scval()])
+            }
+            DataKey::Paused => encode_variant("Paused", &[]),
+            DataKey::ProductLimits => encode_variant("ProductLimits", &[]),
+            DataKey::Metadata(product_id) => encode_variant("Metadata", &[product_id.to_scval()]),
+            DataKey::Coupon(code) => encode_variant("Coupon", &[symbol(code)]),
+            DataKey::NoOfOrders => encode_variant("NoOfOrders", &[]),
+            DataKey::Order(id) => encode_variant("Order", &[id.to_scval()]),
+            DataKey::NoOfBundles => encode_variant("NoOfBundles", &[]),
+            DataKey::Bundle(id) => encode_variant("Bundle", &[id.to_scval()]),
+            DataKey::ReferralConfig => encode_variant("ReferralConfig", &[]),
+            DataKey::ReferralEarnings(referrer, token) => {
+                encode_variant("ReferralEarnings", &[referrer.to_scval(), token.to_scval()])
+            }
+            DataKey::LoyaltyConfig => encode_variant("LoyaltyConfig", &[]),
+            DataKey::Points(account) => encode_variant("Points", &[account.to_scval()]),
+            DataKey::SalePhases(product_id) => {
+                encode_variant("SalePhases", &[product_id.to_scval()])
+            }
+            DataKey::PhaseSold(product_id, index) => {
+                encode_variant("PhaseSold", &[product_id.to_scval(), index.to_scval()])
+            }
+            DataKey::Allowlist(product_id, account) => {
+                encode_variant("Allowlist", &[product_id.to_scval(), account.to_scval()])
+            }
+            DataKey::VestingSchedule(recipient) => {
+                encode_variant("VestingSchedule", &[recipient.to_scval()])
+            }
+            DataKey::Vesting(recipient, token) => {
+                encode_variant("Vesting", &[recipient.to_scval(), token.to_scval()])
+            }
+            DataKey::ReceiptMode(product_id) => {
+                encode_variant("ReceiptMode", &[product_id.to_scval()])
+            }
+            DataKey::NoOfReceipts => encode_variant("NoOfReceipts", &[]),
+            DataKey::Receipt(id) => encode_variant("Receipt", &[id.to_scval()]),
+            DataKey::ReceiptApproval(id) => encode_variant("ReceiptApproval", &[id.to_scval()]),
+            DataKey::VoucherValidity(product_id) => {
+                encode_variant("VoucherValidity", &[product_id.to_scval()])
+            }
+            DataKey::NoOfVouchers => encode_variant("NoOfVouchers", &[]),
+            DataKey::Voucher(id) => encode_variant("Voucher", &[id.to_scval()]),
+            DataKey::ProductVouchers(product_id) => {
+                encode_variant("ProductVouchers", &[product_id.to_scval()])
+            }
+            DataKey::ProductVoucher(product_id, index) => {
+                encode_variant("ProductVoucher", &[product_id.to_scval(), index.to_scval()])
+            }
+            DataKey::Redeemer(product_id, account) => {
+                encode_variant("Redeemer", &[product_id.to_scval(), account.to_scval()])
+            }
+            DataKey::Review(order_id, product_id) => {
+                encode_variant("Review", &[order_id.to_scval(), product_id.to_scval()])
+            }
+            DataKey::DisputeConfig => encode_variant("DisputeConfig", &[]),
+            DataKey::Arbiter => encode_variant("Arbiter", &[]),
+            DataKey::Escrow(order_id) => encode_variant("Escrow", &[order_id.to_scval()]),
+            DataKey::EscrowReferrer(order_id) => {
+                encode_variant("EscrowReferrer", &[order_id.to_scval()])
+            }
+            DataKey::Dispute(order_id) => encode_variant("Dispute", &[order_id.to_scval()]),
+            DataKey::Variants(product_id) => encode_variant("Variants", &[product_id.to_scval()]),
+            DataKey::Rating(product_id) => encode_variant("Rating", &[product_id.to_scval()]),
+            DataKey::Purchased(buyer, product_id) => {
+                encode_variant("Purchased", &[buyer.to_scval(), product_id.to_scval()])
+            }
+            DataKey::PurchaseReview(buyer, product_id) => {
+                encode_variant("PurchaseReview", &[buyer.to_scval(), product_id.to_scval()])
+            }
+            DataKey::OrderGrants(order_id) => encode_variant("OrderGrants", &[order_id.to_scval()]),
+        }
+    }
+}
+
+impl FromScVal for DataKey {
+    fn from_scval(val: &ScVal) -> Result<Self, DecodeError> {
+        let (name, fields) = decode_variant(val)?;
+        let key = match (name.as_str(), fields) {
+            ("ReserveAccount", []) => DataKey::ReserveAccount,
+            ("DevAccount", []) => DataKey::DevAccount,
+            ("LaunchpadAccount", []) => DataKey::LaunchpadAccount,
+            ("Admin", []) => DataKey::Admin,
+            ("Split", []) => DataKey::Split,
+            ("Signers", []) => DataKey::Signers,
+            ("Threshold", []) => DataKey::Threshold,
+            ("NoOfProposals", []) => DataKey::NoOfProposals,
+            ("Proposal", _) => DataKey::Proposal(only_field(fields)?),
+            ("PayoutMode", []) => DataKey::PayoutMode,
+            ("Claimable", [recipient, token]) => DataKey::Claimable(
+                ScAddress::from_scval(recipient)?,
+                ScAddress::from_scval(token)?,
+            ),
+            ("Paused", []) => DataKey::Paused,
+            ("ProductLimits", []) => DataKey::ProductLimits,
+            ("Metadata", fields) => DataKey::Metadata(only_field(fields)?),
+            ("Coupon", [code]) => DataKey::Coupon(symbol_name(code)?),
+            ("NoOfOrders", []) => DataKey::NoOfOrders,
+            ("Order", fields) => DataKey::Order(only_field(fields)?),
+            ("NoOfBundles", []) => DataKey::NoOfBundles,
+            ("Bundle", fields) => DataKey::Bundle(only_field(fields)?),
+            ("ReferralConfig", []) => DataKey::ReferralConfig,
+            ("ReferralEarnings", [referrer, token]) => DataKey::ReferralEarnings(
+                ScAddress::from_scval(referrer)?,
+                ScAddress::from_scval(token)?,
+            ),
+            ("LoyaltyConfig", []) => DataKey::LoyaltyConfig,
+            ("Points", fields) => DataKey::Points(only_field(fields)?),
+            ("SalePhases", fields) => DataKey::SalePhases(only_field(fields)?),
+            ("PhaseSold", [product_id, index]) => {
+                DataKey::PhaseSold(u32::from_scval(product_id)?, u32::from_scval(index)?)
+            }
+            ("Allowlist", [product_id, account]) => DataKey::Allowlist(
+                u32::from_scval(product_id)?,
+                ScAddress::from_scval(account)?,
+            ),
+            ("VestingSchedule", fields) => DataKey::VestingSchedule(only_field(fields)?),
+            ("Vesting", [recipient, token]) => DataKey::Vesting(
+                ScAddress::from_scval(recipient)?,
+                ScAddress::from_scval(token)?,
+            ),
+            ("ReceiptMode", fields) => DataKey::ReceiptMode(only_field(fields)?),
+            ("NoOfReceipts", []) => DataKey::NoOfReceipts,
+            ("Receipt", fields) => DataKey::Receipt(only_field(fields)?),
+            ("ReceiptApproval", fields) => DataKey::ReceiptApproval(only_field(fields)?),
+            ("VoucherValidity", fields) => DataKey::VoucherValidity(only_field(fields)?),
+            ("NoOfVouchers", []) => DataKey::NoOfVouchers,
+            ("Voucher", fields) => DataKey::Voucher(only_field(fields)?),
+            ("ProductVouchers", fields) => DataKey::ProductVouchers(only_field(fields)?),
+            ("ProductVoucher", [product_id, index]) => {
+                DataKey::ProductVoucher(u32::from_scval(product_id)?, u32::from_scval(index)?)
+            }
+            ("Redeemer", [product_id, account]) => DataKey::Redeemer(
+                u32::from_scval(product_id)?,
+                ScAddress::from_scval(account)?,
+            ),
+            ("Review", [order_id, product_id]) => {
+                DataKey::Review(u32::from_scval(order_id)?, u32::from_scval(product_id)?)
+            }
+            ("DisputeConfig", []) => DataKey::DisputeConfig,
+            ("Arbiter", []) => DataKey::Arbiter,
+            ("Escrow", fields) => DataKey::Escrow(only_field(fields)?),
+            ("EscrowReferrer", fields) => DataKey::EscrowReferrer(only_field(fields)?),
+            ("Dispute", fields) => DataKey::Dispute(only_field(fields)?),
+            ("Variants", fields) => DataKey::Variants(only_field(fields)?),
+            ("Rating", fields) => DataKey::Rating(only_field(fields)?),
+            ("Purchased", [buyer, product_id]) => {
+                DataKey::Purchased(ScAddress::from_scval(buyer)?, u32::from_scval(product_id)?)
+            }
+            ("PurchaseReview", [buyer, product_id]) => {
+                DataKey::PurchaseReview(ScAddress::from_scval(buyer)?, u32::from_scval(product_id)?)
+            }
+            ("OrderGrants", fields) => DataKey::OrderGrants(only_field(fields)?),
+            _ => return Err(DecodeError::UnknownVariant(name)),
+        };
+        Ok(key)
+    }
+}
@@ -0,0 +1,38 @@
+use soroban_sdk::{Address, Env, Symbol};
+
+use crate::events::{self, AccountChangedEvent};
+use crate::{vesting, DataKey, Marketplace};
+
+pub(crate) fn require_admin(env: &Env) -> Address {
+    let admin = Marketplace::get_admin(env.clone());
+    admin.require_auth();
+    admin
+}
+
+pub(crate) fn is_paused(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::Paused)
+        .unwrap_or(false)
+}
+
+/// Replaces one of the payout accounts and publishes the old and new address
+/// with `role` naming the account. A vesting schedule on the old account
+/// applies to the new one too, so that moving the role doesn't unlock its
+/// future shares; what the old account has in the vault keeps vesting.
+pub(crate) fn set_payout_acc(env: &Env, key: DataKey, role: Symbol, new_acc: Address) {
+    let old_acc: Address = env.storage().instance().get(&key).expect("none");
+    env.storage().instance().set(&key, &new_acc);
+    if let Some(schedule) = vesting::get_schedule(env, &old_acc) {
+        vesting::set_schedule(env, new_acc.clone(), schedule);
+    }
+
+    events::account_changed(
+        env,
+        AccountChangedEvent {
+            role,
+            old: old_acc,
+            new: new_acc,
+        },
+    );
+}
@@ -0,0 +1,797 @@
+#![cfg(test)]
+
+//! CPU and memory cost regression checks for every entrypoint.
+//!
+//! Each entrypoint (other than `initialize`, which only ever runs against an
+//! empty contract) is invoked against catalogs of 1, 100 and 1000 products
+//! and its `env.budget()` cost is compared with `budget_baseline.txt`. A run
+//! fails when any cost grows more than `TOLERANCE_PERCENT` above the stored
+//! value. After an intended change, refresh the baseline with:
+//!
+//! ```text
+//! UPDATE_BUDGET_BASELINE=1 cargo test budget
+//! ```
+
+use super::*;
+use crate::testutils;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::xdr::ToXdr;
+
+extern crate std;
+use std::collections::BTreeMap;
+use std::format;
+use std::string::String as StdString;
+use std::vec::Vec as StdVec;
+
+const CATALOG_SIZES: [u32; 3] = [1, 100, 1000];
+const TOLERANCE_PERCENT: u64 = 10;
+const BASELINE: &str = "budget_baseline.txt";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Cost {
+    cpu: u64,
+    mem: u64,
+}
+
+fn measure(env: &Env, call: impl FnOnce()) -> Cost {
+    env.budget().reset_unlimited();
+    env.budget().reset_tracker();
+    call();
+    Cost {
+        cpu: env.budget().cpu_instruction_cost(),
+        mem: env.budget().memory_bytes_cost(),
+    }
+}
+
+/// Writes `count` products straight into storage, which is much faster than
+/// going through `create_product` for large catalogs.
+fn seed_catalog(market: &testutils::Marketplace, count: u32) {
+    let env = &market.env;
+    env.as_contract(&market.contract_id, || {
+        for id in 1..=count {
+            let product = Product {
+                id,
+                title: String::from_str(env, "Product"),
+                description: String::from_str(env, "Description"),
+                category: String::from_str(env, "Category"),
+                expiry: env.ledger().timestamp() + 24 * 60 * 60,
+                image: String::from_str(env, "image.png"),
+                price: 1000,
+                remaining: 10,
+            };
+            env.storage().instance().set(&id, &product);
+        }
+        env.storage().instance().set(&NO_OF_PRODUCTS, &count);
+    });
+}
+
+fn measure_catalog(size: u32, costs: &mut BTreeMap<(StdString, u32), Cost>) {
+    let env = Env::default();
+    let signer = Address::generate(&env);
+    let market = testutils::Marketplace::setup_with_signers(&env, std::slice::from_ref(&signer), 1);
+    let client = market.client();
+    let buyer = market.funded_buyer(1_000_000_000_000_000);
+
+    seed_catalog(&market, size - 1);
+
+    let mut record = |name: &str, cost: Cost| {
+        costs.insert((StdString::from(name), size), cost);
+    };
+
+    record(
+        "create_product",
+        measure(&env, || {
+            market.product().create();
+        }),
+    );
+    record(
+        "get_product",
+        measure(&env, || {
+            client.get_product(&size);
+        }),
+    );
+    record(
+        "get_products",
+        measure(&env, || {
+            client.get_products();
+        }),
+    );
+    record(
+        "get_product_view",
+        measure(&env, || {
+            client.get_product_view(&size);
+        }),
+    );
+    record(
+        "get_product_views",
+        measure(&env, || {
+            client.get_product_views();
+        }),
+    );
+    record(
+        "create_products_batch",
+        measure(&env, || {
+            client.create_products_batch(&soroban_sdk::vec![&env, market.product().input()]);
+        }),
+    );
+    record(
+        "export_products",
+        measure(&env, || {
+            client.export_products();
+        }),
+    );
+    record(
+        "set_product_limits",
+        measure(&env, || {
+            client.set_product_limits(&ProductLimits::DEFAULT);
+        }),
+    );
+    record(
+        "get_product_limits",
+        measure(&env, || {
+            client.get_product_limits();
+        }),
+    );
+    let blob = Bytes::from_slice(&env, b"metadata");
+    let mut described = market.product().create();
+    record(
+        "create_product_with_metadata",
+        measure(&env, || {
+            described = client.create_product_with_metadata(
+                &String::from_str(&env, "Product"),
+                &String::from_str(&env, "Category"),
+                &described.expiry,
+                &described.price,
+                &described.remaining,
+                &ProductMetadata {
+                    hash: env.crypto().sha256(&blob),
+                    uri: String::from_str(&env, "ipfs://metadata"),
+                },
+            );
+        }),
+    );
+    record(
+        "get_product_metadata",
+        measure(&env, || {
+            client.get_product_metadata(&described.id);
+        }),
+    );
+    record(
+        "verify_metadata",
+        measure(&env, || {
+            client.verify_metadata(&described.id, &blob);
+        }),
+    );
+    record(
+        "get_discount",
+        measure(&env, || {
+            client.get_discount(&size, &buyer, &1, &market.token_id, &None);
+        }),
+    );
+    let product = market.product().create();
+    record(
+        "set_receipt_mode",
+        measure(&env, || {
+            client.set_receipt_mode(&product.id, &ReceiptMode::Transferable);
+        }),
+    );
+    record(
+        "get_discount_receipt",
+        measure(&env, || {
+            client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+        }),
+    );
+    let holder = Address::generate(&env);
+    record(
+        "transfer_receipt",
+        measure(&env, || client.transfer_receipt(&buyer, &holder, &1)),
+    );
+    record(
+        "get_receipt",
+        measure(&env, || {
+            client.get_receipt(&1);
+        }),
+    );
+    record(
+        "owner_of",
+        measure(&env, || {
+            client.owner_of(&1);
+        }),
+    );
+    record(
+        "get_receipt_mode",
+        measure(&env, || {
+            client.get_receipt_mode(&product.id);
+        }),
+    );
+    record(
+        "approve_receipt",
+        measure(&env, || {
+            client.approve_receipt(&holder, &1, &Some(buyer.clone()));
+        }),
+    );
+    record(
+        "get_receipt_approval",
+        measure(&env, || {
+            client.get_receipt_approval(&1);
+        }),
+    );
+    record(
+        "transfer_receipt_from",
+        measure(&env, || {
+            client.transfer_receipt_from(&buyer, &holder, &buyer, &1)
+        }),
+    );
+    let ticket = market.product().create();
+    record(
+        "set_voucher_validity",
+        measure(&env, || {
+            client.set_voucher_validity(&ticket.id, &0);
+        }),
+    );
+    record(
+        "get_voucher_validity",
+        measure(&env, || {
+            client.get_voucher_validity(&ticket.id);
+        }),
+    );
+    record(
+        "get_discount_voucher",
+        measure(&env, || {
+            client.get_discount(&ticket.id, &buyer, &1, &market.token_id, &None);
+        }),
+    );
+    client.get_discount(&ticket.id, &buyer, &1, &market.token_id, &None);
+    record(
+        "get_outstanding_vouchers",
+        measure(&env, || {
+            client.get_outstanding_vouchers(&ticket.id, &0, &MAX_BATCH_SIZE);
+        }),
+    );
+    record(
+        "get_voucher_count",
+        measure(&env, || {
+            client.get_voucher_count(&ticket.id);
+        }),
+    );
+    record(
+        "get_voucher",
+        measure(&env, || {
+            client.get_voucher(&1);
+        }),
+    );
+    record(
+        "set_redeemer",
+        measure(&env, || client.set_redeemer(&ticket.id, &holder, &true)),
+    );
+    record(
+        "is_redeemer",
+        measure(&env, || {
+            client.is_redeemer(&ticket.id, &holder);
+        }),
+    );
+    record("redeem", measure(&env, || client.redeem(&market.admin, &1)));
+    record(
+        "refund_voucher",
+        measure(&env, || {
+            client.refund_voucher(&2);
+        }),
+    );
+    record(
+        "disable_vouchers",
+        measure(&env, || client.disable_vouchers(&ticket.id)),
+    );
+    record(
+        "set_payout_mode",
+        measure(&env, || client.set_payout_mode(&PayoutMode::Accrue)),
+    );
+    record(
+        "get_payout_mode",
+        measure(&env, || {
+            client.get_payout_mode();
+        }),
+    );
+    record(
+        "get_discount_accrue",
+        measure(&env, || {
+            client.get_discount(&size, &buyer, &1, &market.token_id, &None);
+        }),
+    );
+    let referral = ReferralConfig {
+        bps: 1000,
+        source: ReferralSource::Launchpad,
+    };
+    record(
+        "set_referral_config",
+        measure(&env, || client.set_referral_config(&referral)),
+    );
+    record(
+        "get_referral_config",
+        measure(&env, || {
+            client.get_referral_config();
+        }),
+    );
+    let referrer = Address::generate(&env);
+    record(
+        "get_discount_referral",
+        measure(&env, || {
+            client.get_discount(&size, &buyer, &1, &market.token_id, &Some(referrer.clone()));
+        }),
+    );
+    record(
+        "get_referral_earnings",
+        measure(&env, || {
+            client.get_referral_earnings(&referrer, &market.token_id);
+        }),
+    );
+    let loyalty = LoyaltyConfig {
+        earn_rate: 100,
+        burn_rate: 100,
+        ttl: 0,
+    };
+    record(
+        "set_loyalty_config",
+        measure(&env, || client.set_loyalty_config(&loyalty)),
+    );
+    record(
+        "get_loyalty_config",
+        measure(&env, || {
+            client.get_loyalty_config();
+        }),
+    );
+    client.get_discount(&size, &buyer, &1, &market.token_id, &None);
+    record(
+        "get_points",
+        measure(&env, || {
+            client.get_points(&buyer);
+        }),
+    );
+    record(
+        "purchase_with_points",
+        measure(&env, || {
+            client.purchase_with_points(&size, &buyer, &2, &market.token_id, &50, &None);
+        }),
+    );
+    let code = symbol_short!("SALE");
+    let coupon = Coupon {
+        discount_bps: 1000,
+        expiry: u64::MAX,
+        uses_left: 1,
+    };
+    record(
+        "set_coupon",
+        measure(&env, || client.set_coupon(&code, &coupon)),
+    );
+    record(
+        "get_coupon",
+        measure(&env, || {
+            client.get_coupon(&code);
+        }),
+    );
+    record(
+        "remove_coupon",
+        measure(&env, || client.remove_coupon(&code)),
+    );
+    record(
+        "checkout",
+        measure(&env, || {
+            client.checkout(
+                &buyer,
+                &soroban_sdk::vec![&env, (size, 1)],
+                &market.token_id,
+                &None,
+                &None,
+            );
+        }),
+    );
+    record(
+        "get_order",
+        measure(&env, || {
+            client.get_order(&1);
+        }),
+    );
+    let hash = BytesN::from_array(&env, &[7; 32]);
+    record(
+        "review",
+        measure(&env, || client.review(&1, &size, &5, &hash)),
+    );
+    record(
+        "get_review",
+        measure(&env, || {
+            client.get_review(&1, &size);
+        }),
+    );
+    record(
+        "review_purchase",
+        measure(&env, || client.review_purchase(&buyer, &size, &5, &hash)),
+    );
+    record(
+        "get_purchase_review",
+        measure(&env, || {
+            client.get_purchase_review(&buyer, &size);
+        }),
+    );
+    record(
+        "get_rating",
+        measure(&env, || {
+            client.get_rating(&size);
+        }),
+    );
+    let config = DisputeConfig {
+        window: 1000,
+        response_window: 500,
+    };
+    record(
+        "set_dispute_config",
+        measure(&env, || client.set_dispute_config(&config)),
+    );
+    record(
+        "get_dispute_config",
+        measure(&env, || {
+            client.get_dispute_config();
+        }),
+    );
+    let arbiter = Address::generate(&env);
+    record(
+        "set_arbiter",
+        measure(&env, || client.set_arbiter(&arbiter)),
+    );
+    record(
+        "get_arbiter",
+        measure(&env, || {
+            client.get_arbiter();
+        }),
+    );
+    let escrowed = market.product().create();
+    let mut order_id = 0;
+    record(
+        "checkout_escrow",
+        measure(&env, || {
+            order_id = client.checkout(
+                &buyer,
+                &soroban_sdk::vec![&env, (escrowed.id, 1)],
+                &market.token_id,
+                &None,
+                &None,
+            );
+        }),
+    );
+    record(
+        "get_escrow",
+        measure(&env, || {
+            client.get_escrow(&order_id);
+        }),
+    );
+    record(
+        "open_dispute",
+        measure(&env, || client.open_dispute(&order_id, &hash)),
+    );
+    record(
+        "get_dispute",
+        measure(&env, || {
+            client.get_dispute(&order_id);
+        }),
+    );
+    record(
+        "respond_dispute",
+        measure(&env, || client.respond_dispute(&order_id, &hash)),
+    );
+    record(
+        "resolve_dispute",
+        measure(&env, || {
+            client.resolve_dispute(&order_id, &Ruling::Split(5000));
+        }),
+    );
+    let order_id = client.checkout(
+        &buyer,
+        &soroban_sdk::vec![&env, (escrowed.id, 1)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+    market.advance_time(config.window);
+    record(
+        "release_escrow",
+        measure(&env, || {
+            client.release_escrow(&order_id);
+        }),
+    );
+    client.set_dispute_config(&DisputeConfig::DEFAULT);
+    let mut bundle = None;
+    record(
+        "create_bundle",
+        measure(&env, || {
+            bundle = Some(client.create_bundle(
+                &String::from_str(&env, "Pair"),
+                &soroban_sdk::vec![&env, size, size],
+                &2,
+            ));
+        }),
+    );
+    let bundle = bundle.unwrap();
+    record(
+        "get_bundle",
+        measure(&env, || {
+            client.get_bundle(&bundle.id);
+        }),
+    );
+    record(
+        "purchase_bundle",
+        measure(&env, || {
+            client.purchase_bundle(&bundle.id, &buyer, &market.token_id, &None);
+        }),
+    );
+    // A one-leaf tree: the root is the buyer's leaf and the proof is empty.
+    let root = env.crypto().sha256(&buyer.clone().to_xdr(&env));
+    let phases = soroban_sdk::vec![
+        &env,
+        SalePhase {
+            access: PhaseAccess::Merkle(root),
+            start: 0,
+            end: u64::MAX,
+            cap: 0,
+        },
+    ];
+    record(
+        "set_sale_phases",
+        measure(&env, || client.set_sale_phases(&size, &phases)),
+    );
+    record(
+        "get_sale_phases",
+        measure(&env, || {
+            client.get_sale_phases(&size);
+        }),
+    );
+    record(
+        "purchase_with_proof",
+        measure(&env, || {
+            client.purchase_with_proof(&size, &buyer, &1, &market.token_id, &Vec::new(&env), &None);
+        }),
+    );
+    record(
+        "get_phase_sold",
+        measure(&env, || {
+            client.get_phase_sold(&size, &0);
+        }),
+    );
+    let allowed = soroban_sdk::vec![&env, buyer.clone()];
+    record(
+        "set_allowlist",
+        measure(&env, || client.set_allowlist(&size, &allowed, &true)),
+    );
+    record(
+        "is_allowlisted",
+        measure(&env, || {
+            client.is_allowlisted(&size, &buyer);
+        }),
+    );
+    record(
+        "add_variant",
+        measure(&env, || {
+            client.add_variant(&size, &String::from_str(&env, "Large"), &5, &3);
+        }),
+    );
+    record(
+        "purchase_variant",
+        measure(&env, || {
+            client.purchase_variant(&size, &1, &buyer, &market.token_id, &None);
+        }),
+    );
+    record(
+        "get_variants",
+        measure(&env, || {
+            client.get_variants(&size);
+        }),
+    );
+    record(
+        "get_claimable",
+        measure(&env, || {
+            client.get_claimable(&market.dev_acc, &market.token_id);
+        }),
+    );
+    record(
+        "withdraw",
+        measure(&env, || {
+            client.withdraw(&market.dev_acc, &market.token_id, &1);
+        }),
+    );
+    // Vest the launchpad share on a schedule that has already ended, so the
+    // purchase below leaves something to claim.
+    env.as_contract(&market.contract_id, || {
+        vesting::set_schedule(
+            &env,
+            market.launchpad_acc.clone(),
+            VestingSchedule {
+                start: 0,
+                cliff: 0,
+                duration: 1,
+            },
+        );
+    });
+    client.set_payout_mode(&PayoutMode::Push);
+    let product = market.product().create();
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    record(
+        "get_vested",
+        measure(&env, || {
+            client.get_vested(&market.launchpad_acc, &market.token_id);
+        }),
+    );
+    record(
+        "get_unvested",
+        measure(&env, || {
+            client.get_unvested(&market.launchpad_acc, &market.token_id);
+        }),
+    );
+    record(
+        "get_vesting_schedule",
+        measure(&env, || {
+            client.get_vesting_schedule(&market.launchpad_acc);
+        }),
+    );
+    record(
+        "claim_vested",
+        measure(&env, || {
+            client.claim_vested(&market.launchpad_acc, &market.token_id);
+        }),
+    );
+    let admins = soroban_sdk::vec![&env, market.admin.clone()];
+    record(
+        "set_reserve_acc",
+        measure(&env, || {
+            client.set_reserve_acc(&market.reserve_acc, &admins)
+        }),
+    );
+    record(
+        "set_dev_acc",
+        measure(&env, || client.set_dev_acc(&market.dev_acc, &admins)),
+    );
+    record(
+        "set_launchpad_acc",
+        measure(&env, || {
+            client.set_launchpad_acc(&market.launchpad_acc, &admins)
+        }),
+    );
+    record(
+        "get_split",
+        measure(&env, || {
+            client.get_split();
+        }),
+    );
+    record(
+        "get_reserve_acc",
+        measure(&env, || {
+            client.get_reserve_acc();
+        }),
+    );
+    record(
+        "get_dev_acc",
+        measure(&env, || {
+            client.get_dev_acc();
+        }),
+    );
+    record(
+        "get_launchpad_acc",
+        measure(&env, || {
+            client.get_launchpad_acc();
+        }),
+    );
+    record(
+        "get_admin",
+        measure(&env, || {
+            client.get_admin();
+        }),
+    );
+    record(
+        "get_signers",
+        measure(&env, || {
+            client.get_signers();
+        }),
+    );
+    record(
+        "get_threshold",
+        measure(&env, || {
+            client.get_threshold();
+        }),
+    );
+
+    let action = ProposalAction::SetDevAccount(market.dev_acc.clone());
+    let mut proposal_id = 0;
+    record(
+        "propose",
+        measure(&env, || {
+            proposal_id = client.propose(&market.admin, &action);
+        }),
+    );
+    record(
+        "approve",
+        measure(&env, || {
+            client.approve(&signer, &proposal_id);
+        }),
+    );
+    market.advance_time(multisig::PROPOSAL_TIMELOCK);
+    record("execute", measure(&env, || client.execute(&proposal_id)));
+    record(
+        "get_proposal",
+        measure(&env, || {
+            client.get_proposal(&proposal_id);
+        }),
+    );
+    record("set_paused", measure(&env, || client.set_paused(&true)));
+    record(
+        "is_paused",
+        measure(&env, || {
+            client.is_paused();
+        }),
+    );
+}
+
+fn baseline_path() -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(BASELINE)
+}
+
+fn parse_baseline(text: &str) -> BTreeMap<(StdString, u32), Cost> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: StdVec<&str> = line.split_whitespace().collect();
+            assert_eq!(fields.len(), 4, "malformed baseline line: {line}");
+            (
+                (StdString::from(fields[0]), fields[1].parse().unwrap()),
+                Cost {
+                    cpu: fields[2].parse().unwrap(),
+                    mem: fields[3].parse().unwrap(),
+                },
+            )
+        })
+        .collect()
+}
+
+fn render_baseline(costs: &BTreeMap<(StdString, u32), Cost>) -> StdString {
+    let mut out = StdString::from("# entrypoint catalog_size cpu_insns mem_bytes\n");
+    for ((name, size), cost) in costs {
+        out.push_str(&format!("{name} {size} {} {}\n", cost.cpu, cost.mem));
+    }
+    out
+}
+
+fn exceeds(measured: u64, baseline: u64) -> bool {
+    measured * 100 > baseline * (100 + TOLERANCE_PERCENT)
+}
+
+#[test]
+fn budget_does_not_regress() {
+    let mut costs = BTreeMap::new();
+    for size in CATALOG_SIZES {
+        measure_catalog(size, &mut costs);
+    }
+
+    if std::env::var_os("UPDATE_BUDGET_BASELINE").is_some() {
+        std::fs::write(baseline_path(), render_baseline(&costs)).unwrap();
+        return;
+    }
+
+    let baseline = parse_baseline(
+        &std::fs::read_to_string(baseline_path())
+            .expect("missing budget baseline, run with UPDATE_BUDGET_BASELINE=1"),
+    );
+
+    let mut regressions = StdVec::new();
+    for (key, cost) in &costs {
+        match baseline.get(key) {
+            None => regressions.push(format!("{} @ {}: not in baseline", key.0, key.1)),
+            Some(base) if exceeds(cost.cpu, base.cpu) || exceeds(cost.mem, base.mem) => regressions
+                .push(format!(
+                    "{} @ {}: cpu {} -> {}, mem {} -> {}",
+                    key.0, key.1, base.cpu, cost.cpu, base.mem, cost.mem
+                )),
+            Some(_) => {}
+        }
+    }
+
+    assert!(
+        regressions.is_empty(),
+        "budget regressions beyond {TOLERANCE_PERCENT}%:\n{}",
+        regressions.join("\n")
+    );
+}
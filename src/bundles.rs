@@ -0,0 +1,168 @@
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
+
+use crate::diagnostics::diag;
+use crate::events::{self, BundlePurchaseEvent};
+use crate::{
+    admin, catalog, loyalty, payout, phases, receipts, reviews, variants, vouchers, DataKey, Error,
+    Marketplace, MAX_BATCH_SIZE,
+};
+
+/// Several products sold together at `price`. A product listed twice is two
+/// units of it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Bundle {
+    pub id: u32,
+    pub title: String,
+    pub product_ids: Vec<u32>,
+    pub price: i128,
+}
+
+pub(crate) fn get(env: &Env, bundle_id: u32) -> Option<Bundle> {
+    env.storage().instance().get(&DataKey::Bundle(bundle_id))
+}
+
+/// Creates a bundle of existing products. Products sold in variants can't be
+/// bundled, since a bundle has no way to pick the variant.
+pub(crate) fn create(
+    env: &Env,
+    title: String,
+    product_ids: Vec<u32>,
+    price: i128,
+) -> Result<Bundle, Error> {
+    admin::require_admin(env);
+
+    if title.len() == 0 {
+        return Err(Error::EmptyTitle);
+    }
+    if title.len() > catalog::get_limits(env).title {
+        return Err(Error::TitleTooLong);
+    }
+    if price <= 0 {
+        return Err(Error::PriceMustBePositive);
+    }
+    if product_ids.is_empty() {
+        return Err(Error::EmptyBatch);
+    }
+    if product_ids.len() > MAX_BATCH_SIZE {
+        return Err(Error::BatchTooLarge);
+    }
+    for product_id in product_ids.iter() {
+        let product = Marketplace::get_product(env.clone(), product_id);
+        if product_id == 0 || product.id != product_id {
+            return Err(Error::ProductNotExist);
+        }
+        if variants::required(env, product_id) {
+            return Err(Error::VariantRequired);
+        }
+    }
+
+    let bundle_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NoOfBundles)
+        .unwrap_or(0)
+        + 1;
+    let bundle = Bundle {
+        id: bundle_id,
+        title,
+        product_ids,
+        price,
+    };
+    env.storage()
+        .instance()
+        .set(&DataKey::NoOfBundles, &bundle_id);
+    env.storage()
+        .instance()
+        .set(&DataKey::Bundle(bundle_id), &bundle);
+
+    events::bundle_created(env, bundle.clone());
+
+    Ok(bundle)
+}
+
+/// Buys one bundle at its price, taking one unit from every constituent.
+/// Constituents are checked again, since they can have been given variants
+/// since the bundle was created, and the purchase fails as a whole if any of
+/// them is expired or sold out.
+pub(crate) fn purchase(
+    env: &Env,
+    bundle_id: u32,
+    customer: Address,
+    token_id: Address,
+    referrer: Option<Address>,
+) -> Result<(i128, i128, i128), Error> {
+    customer.require_auth();
+
+    if admin::is_paused(env) {
+        return Err(Error::Paused);
+    }
+    let bundle = get(env, bundle_id).ok_or(Error::BundleNotExist)?;
+
+    let mut list_total: i128 = 0;
+    let mut prices = Vec::new(env);
+    for product_id in bundle.product_ids.iter() {
+        let mut product = Marketplace::get_product(env.clone(), product_id);
+        if product.id != product_id {
+            return Err(Error::ProductNotExist);
+        }
+        if variants::required(env, product_id) {
+            return Err(Error::VariantRequired);
+        }
+        if product.expiry < env.ledger().timestamp() {
+            return Err(Error::DiscountExpired);
+        }
+        if product.remaining <= 0 {
+            return Err(Error::TargetReached);
+        }
+        phases::admit(env, product_id, &customer, 1, &Vec::new(env))?;
+        list_total += product.price;
+        prices.push_back(product.price);
+        // Stored right away so a product listed twice is checked against the
+        // stock left by its earlier entry.
+        product.remaining -= 1;
+        env.storage().instance().set(&product.id, &product);
+        events::product_updated(env, &product);
+    }
+
+    diag!(
+        env,
+        "purchase.bundle",
+        bundle = bundle_id,
+        buyer = customer,
+        price = bundle.price,
+    );
+    let (reserve_amount, launchpad_amount, dev_amount) = payout::split(
+        env,
+        &token_id,
+        &customer,
+        bundle.price * payout::UNIT,
+        referrer,
+    )?;
+    loyalty::earn(env, customer.clone(), bundle.price * payout::UNIT);
+    for (product_id, price) in bundle.product_ids.iter().zip(prices.iter()) {
+        receipts::mint(env, &customer, product_id, 0);
+        reviews::record_purchase(env, &customer, product_id);
+        // The bundle price is spread over its products by their list prices.
+        let amount = if list_total > 0 {
+            price * payout::UNIT * bundle.price / list_total
+        } else {
+            0
+        };
+        vouchers::issue(env, &customer, product_id, 0, &token_id, amount, 1);
+    }
+
+    events::bundle_purchase(
+        env,
+        BundlePurchaseEvent {
+            buyer: customer,
+            bundle_id,
+            token: token_id,
+            reserve_amount,
+            launchpad_amount,
+            dev_amount,
+        },
+    );
+
+    Ok((reserve_amount, launchpad_amount, dev_amount))
+}
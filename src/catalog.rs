@@ -0,0 +1,172 @@
+use soroban_sdk::{contracttype, Env, String, Vec};
+
+use crate::events;
+use crate::{admin, DataKey, Error, Marketplace, Product, NO_OF_PRODUCTS};
+
+/// Upper bound on the number of products `create_products_batch` accepts in
+/// one call, keeping a batch well inside a transaction's resource limits.
+pub const MAX_BATCH_SIZE: u32 = 25;
+
+/// The fields of a product as supplied by whoever lists it; the id is
+/// assigned on creation and `target` becomes the product's `remaining` stock.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductInput {
+    pub title: String,
+    pub description: String,
+    pub category: String,
+    pub expiry: u64,
+    pub image: String,
+    pub price: i128,
+    pub target: i128,
+}
+
+/// Maximum lengths, in bytes, of a product's text fields. Titles must also
+/// be non-empty.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductLimits {
+    pub title: u32,
+    pub description: u32,
+    pub category: u32,
+    pub image: u32,
+}
+
+impl ProductLimits {
+    pub const DEFAULT: ProductLimits = ProductLimits {
+        title: 64,
+        description: 1024,
+        category: 32,
+        image: 256,
+    };
+}
+
+pub(crate) fn get_limits(env: &Env) -> ProductLimits {
+    env.storage()
+        .instance()
+        .get(&DataKey::ProductLimits)
+        .unwrap_or(ProductLimits::DEFAULT)
+}
+
+pub(crate) fn set_limits(env: &Env, limits: ProductLimits) -> Result<(), Error> {
+    if limits.title == 0 {
+        return Err(Error::InvalidProductLimits);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::ProductLimits, &limits);
+    events::product_limits_changed(env, limits);
+    Ok(())
+}
+
+fn check(env: &Env, limits: &ProductLimits, input: &ProductInput) -> Result<(), Error> {
+    if input.title.len() == 0 {
+        return Err(Error::EmptyTitle);
+    }
+    if input.title.len() > limits.title {
+        return Err(Error::TitleTooLong);
+    }
+    if input.description.len() > limits.description {
+        return Err(Error::DescriptionTooLong);
+    }
+    if input.category.len() > limits.category {
+        return Err(Error::CategoryTooLong);
+    }
+    if input.image.len() > limits.image {
+        return Err(Error::ImageTooLong);
+    }
+    if input.price <= 0 {
+        return Err(Error::PriceMustBePositive);
+    }
+    if input.target <= 0 {
+        return Err(Error::TargetMustBePositive);
+    }
+    if input.expiry < env.ledger().timestamp() {
+        return Err(Error::ExpiryShouldBeFuture);
+    }
+    Ok(())
+}
+
+fn insert(env: &Env, input: ProductInput) -> Result<Product, Error> {
+    let mut count_id: u32 = env.storage().instance().get(&NO_OF_PRODUCTS).unwrap_or(0); // If no value set, assume 0.
+
+    count_id += 1;
+
+    let check_product = Marketplace::get_product(env.clone(), count_id);
+
+    if check_product.id == count_id {
+        return Err(Error::ProductAlreadyExist);
+    }
+    if count_id == 0 {
+        return Err(Error::IdProductMustNonZero);
+    }
+
+    let product = Product {
+        id: count_id,
+        title: input.title,
+        description: input.description,
+        remaining: input.target,
+        expiry: input.expiry,
+        category: input.category,
+        image: input.image,
+        price: input.price,
+    };
+
+    env.storage().instance().set(&NO_OF_PRODUCTS, &count_id);
+    env.storage().instance().set(&count_id, &product);
+
+    events::product_created(env, &product);
+
+    Ok(product)
+}
+
+pub(crate) fn create(env: &Env, input: ProductInput) -> Result<Product, Error> {
+    check(env, &get_limits(env), &input)?;
+    insert(env, input)
+}
+
+/// Validates every input before creating any product, so a bad entry
+/// rejects the whole batch without touching storage.
+pub(crate) fn create_batch(env: &Env, inputs: Vec<ProductInput>) -> Result<Vec<Product>, Error> {
+    admin::require_admin(env);
+    if inputs.is_empty() {
+        return Err(Error::EmptyBatch);
+    }
+    if inputs.len() > MAX_BATCH_SIZE {
+        return Err(Error::BatchTooLarge);
+    }
+    let limits = get_limits(env);
+    for input in inputs.iter() {
+        check(env, &limits, &input)?;
+    }
+
+    let mut products = Vec::new(env);
+    for input in inputs.iter() {
+        products.push_back(insert(env, input)?);
+    }
+    Ok(products)
+}
+
+/// Every product still on sale as an input that `create_products_batch`
+/// accepts, in id order, with the remaining stock as the target. Sold-out
+/// and expired products are left out since a batch would reject them, and
+/// so are variants and off-chain metadata, which a `ProductInput` can't hold.
+pub(crate) fn export(env: &Env) -> Vec<ProductInput> {
+    let now = env.ledger().timestamp();
+    let mut inputs = Vec::new(env);
+    for product in Marketplace::get_products(env.clone()).iter() {
+        if product.remaining <= 0 || product.expiry < now {
+            continue;
+        }
+        inputs.push_back(ProductInput {
+            title: product.title,
+            description: product.description,
+            category: product.category,
+            expiry: product.expiry,
+            image: product.image,
+            price: product.price,
+            target: product.remaining,
+        });
+    }
+    inputs
+}
@@ -0,0 +1,276 @@
+use soroban_sdk::{contracttype, Address, Env, Symbol, Vec};
+
+use crate::diagnostics::diag;
+use crate::events::{self, CouponEvent, OrderEvent};
+use crate::{
+    admin, disputes, loyalty, payout, phases, receipts, storage, variants, vouchers, DataKey,
+    Error, Marketplace, MAX_BATCH_SIZE,
+};
+
+/// A discount code: `discount_bps` off the whole order, usable `uses_left`
+/// more times until `expiry`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Coupon {
+    pub discount_bps: u32,
+    pub expiry: u64,
+    pub uses_left: u32,
+}
+
+/// A completed checkout. `total` and `discount` are in stroops; `total` is
+/// what the buyer paid after any coupon.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Order {
+    pub id: u32,
+    pub buyer: Address,
+    pub token: Address,
+    pub items: Vec<(u32, u32)>,
+    pub discount: i128,
+    pub total: i128,
+}
+
+/// What a checkout order handed out besides its units: the receipt and
+/// voucher ids in `first..end`, the loyalty points earned and, per line, the
+/// index and start of the sale phase it was counted against.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct OrderGrants {
+    pub receipts: (u32, u32),
+    pub vouchers: (u32, u32),
+    pub points: i128,
+    pub phases: Vec<(u32, u32, u64)>,
+}
+
+pub(crate) fn get_coupon(env: &Env, code: &Symbol) -> Option<Coupon> {
+    env.storage().instance().get(&DataKey::Coupon(code.clone()))
+}
+
+/// Adds or replaces the coupon `code`.
+pub(crate) fn set_coupon(env: &Env, code: Symbol, coupon: Coupon) -> Result<(), Error> {
+    admin::require_admin(env);
+    if coupon.discount_bps == 0
+        || coupon.discount_bps as i128 > payout::BPS
+        || coupon.uses_left == 0
+    {
+        return Err(Error::InvalidCoupon);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::Coupon(code.clone()), &coupon);
+    events::coupon_set(env, CouponEvent { code, coupon });
+    Ok(())
+}
+
+pub(crate) fn remove_coupon(env: &Env, code: Symbol) {
+    admin::require_admin(env);
+    env.storage()
+        .instance()
+        .remove(&DataKey::Coupon(code.clone()));
+    events::coupon_removed(env, code);
+}
+
+/// Uses up one redemption of `code` and returns its discount.
+fn redeem_coupon(env: &Env, code: &Symbol) -> Result<i128, Error> {
+    let mut coupon = get_coupon(env, code).ok_or(Error::InvalidCoupon)?;
+    if coupon.expiry < env.ledger().timestamp() || coupon.uses_left == 0 {
+        return Err(Error::InvalidCoupon);
+    }
+    coupon.uses_left -= 1;
+    env.storage()
+        .instance()
+        .set(&DataKey::Coupon(code.clone()), &coupon);
+    Ok(coupon.discount_bps as i128)
+}
+
+pub(crate) fn get_order(env: &Env, order_id: u32) -> Option<Order> {
+    storage::get(env, &DataKey::Order(order_id))
+}
+
+/// Buys `quantity` of each listed product at its price in one payment.
+///
+/// Every item is checked before anything is paid, and a failing item fails
+/// the whole order. The payment is split once over the order total, so each
+/// payout account receives a single transfer (or credit) however many items
+/// the order has. While a dispute window is set, the payment is held in
+/// escrow instead and split when it's released. Each line of a product that
+/// mints receipts gets one, pointing back at the order, and each unit of a
+/// voucher product gets a voucher worth its share of the total.
+pub(crate) fn checkout(
+    env: &Env,
+    buyer: Address,
+    items: Vec<(u32, u32)>,
+    token_id: Address,
+    coupon: Option<Symbol>,
+    referrer: Option<Address>,
+) -> Result<u32, Error> {
+    buyer.require_auth();
+
+    if admin::is_paused(env) {
+        return Err(Error::Paused);
+    }
+    if items.is_empty() {
+        return Err(Error::EmptyBatch);
+    }
+    if items.len() > MAX_BATCH_SIZE {
+        return Err(Error::BatchTooLarge);
+    }
+
+    let order_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NoOfOrders)
+        .unwrap_or(0)
+        + 1;
+    let mut subtotal: i128 = 0;
+    let mut prices = Vec::new(env);
+    let mut sold_in_phases = Vec::new(env);
+    for (product_id, quantity) in items.iter() {
+        let mut product = Marketplace::get_product(env.clone(), product_id);
+        if product_id == 0 || product.id != product_id {
+            return Err(Error::ProductNotExist);
+        }
+        if quantity == 0 {
+            return Err(Error::AmountMustBeGreaterThanZero);
+        }
+        if variants::required(env, product_id) {
+            return Err(Error::VariantRequired);
+        }
+        if product.expiry < env.ledger().timestamp() {
+            return Err(Error::DiscountExpired);
+        }
+        if product.remaining <= 0 {
+            return Err(Error::TargetReached);
+        }
+        if quantity as i128 > product.remaining {
+            return Err(Error::AmountExceedTargetLimit);
+        }
+        if let Some((index, start)) =
+            phases::admit(env, product_id, &buyer, quantity as i128, &Vec::new(env))?
+        {
+            sold_in_phases.push_back((product_id, index, start));
+        }
+
+        subtotal += product.price * quantity as i128;
+        prices.push_back(product.price);
+        // Stored right away so a product listed twice is checked against the
+        // stock left by its earlier line.
+        product.remaining -= quantity as i128;
+        env.storage().instance().set(&product.id, &product);
+        events::product_updated(env, &product);
+    }
+
+    let subtotal = subtotal * payout::UNIT;
+    let discount = match &coupon {
+        Some(code) => subtotal * redeem_coupon(env, code)? / payout::BPS,
+        None => 0,
+    };
+    let total = subtotal - discount;
+    diag!(
+        env,
+        "checkout.total",
+        buyer = buyer,
+        subtotal = subtotal,
+        discount = discount,
+    );
+    if total > 0 {
+        if disputes::get_config(env).window > 0 {
+            disputes::hold(env, order_id, &buyer, &token_id, total, referrer)?;
+        } else {
+            payout::split(env, &token_id, &buyer, total, referrer)?;
+        }
+    }
+    let points = loyalty::earn(env, buyer.clone(), total);
+    let order = Order {
+        id: order_id,
+        buyer,
+        token: token_id,
+        items,
+        discount,
+        total,
+    };
+    env.storage()
+        .instance()
+        .set(&DataKey::NoOfOrders, &order_id);
+    storage::set(env, &DataKey::Order(order_id), &order);
+
+    events::order(
+        env,
+        OrderEvent {
+            order_id,
+            buyer: order.buyer.clone(),
+            token: order.token.clone(),
+            discount,
+            total,
+        },
+    );
+    let first_receipt = receipts::next_id(env);
+    let first_voucher = vouchers::next_id(env);
+    for ((product_id, quantity), price) in order.items.iter().zip(prices.iter()) {
+        receipts::mint(env, &order.buyer, product_id, order_id);
+        // The coupon discount is spread over the units pro rata.
+        let amount = if subtotal > 0 {
+            price * payout::UNIT * total / subtotal
+        } else {
+            0
+        };
+        vouchers::issue(
+            env,
+            &order.buyer,
+            product_id,
+            order_id,
+            &order.token,
+            amount,
+            quantity,
+        );
+    }
+    storage::set(
+        env,
+        &DataKey::OrderGrants(order_id),
+        &OrderGrants {
+            receipts: (first_receipt, receipts::next_id(env)),
+            vouchers: (first_voucher, vouchers::next_id(env)),
+            points,
+            phases: sold_in_phases,
+        },
+    );
+
+    Ok(order_id)
+}
+
+/// Undoes `bps` basis points of an order refunded through a dispute: that
+/// share of each line's units goes back into stock and its sale phase, and
+/// that share of the order's vouchers is voided and of its loyalty points
+/// taken back. A full refund also burns its receipts.
+pub(crate) fn unwind(env: &Env, order_id: u32, bps: i128) {
+    let Some(order) = get_order(env, order_id) else {
+        return;
+    };
+    let Some(grants) = storage::get::<OrderGrants>(env, &DataKey::OrderGrants(order_id)) else {
+        return;
+    };
+    for (product_id, quantity) in order.items.iter() {
+        let units = quantity as i128 * bps / payout::BPS;
+        let mut product = Marketplace::get_product(env.clone(), product_id);
+        if units == 0 || product.id != product_id {
+            continue;
+        }
+        product.remaining += units;
+        env.storage().instance().set(&product.id, &product);
+        events::product_updated(env, &product);
+        if let Some((_, index, start)) = grants.phases.iter().find(|(id, _, _)| *id == product_id) {
+            phases::release(env, product_id, (index, start), units);
+        }
+    }
+    loyalty::forfeit(env, order.buyer.clone(), grants.points * bps / payout::BPS);
+    let (first, end) = grants.vouchers;
+    let count = (end - first) as i128 * bps / payout::BPS;
+    vouchers::void(env, first, end, count as u32);
+    if bps == payout::BPS {
+        let (first, end) = grants.receipts;
+        for receipt_id in first..end {
+            receipts::burn(env, receipt_id);
+        }
+    }
+    diag!(env, "order.unwind", order = order_id, bps = bps);
+}
@@ -0,0 +1,30 @@
+//! Opt-in diagnostics for the purchase and payout paths.
+//!
+//! `diag!` expands to nothing unless the crate is built with the
+//! `diagnostics` feature. Soroban only records logs when debug assertions are
+//! on, so the output is visible with:
+//!
+//! ```text
+//! cargo build --profile release-with-logs --features diagnostics
+//! ```
+//!
+//! Each entry is logged as `"<step> <key>={} ..."` followed by the values in
+//! the same order, e.g. `"purchase.split reserve={} launchpad={} dev={}"`.
+
+macro_rules! diag {
+    ($env:expr, $step:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "diagnostics")]
+        {
+            $env.logs().add(
+                concat!($step $(, " ", stringify!($key), "={}")*),
+                &[$(
+                    <_ as soroban_sdk::IntoVal<soroban_sdk::Env, soroban_sdk::Val>>::into_val(
+                        &$value, $env,
+                    )
+                ),*],
+            );
+        }
+    };
+}
+
+pub(crate) use diag;
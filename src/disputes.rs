@@ -0,0 +1,332 @@
+use soroban_sdk::{contracttype, token, Address, BytesN, Env};
+
+use crate::diagnostics::diag;
+use crate::events::{self, EscrowReleaseEvent};
+use crate::payout::{self, BPS};
+use crate::{checkout, storage, DataKey, Error, Marketplace, OrderError};
+
+/// How disputes of checkout orders work, in seconds.
+///
+/// While `window` is non-zero, each order's payment is held by the contract
+/// for `window` seconds, during which the buyer can dispute it. The seller
+/// then has `response_window` seconds to answer before the arbiter can rule
+/// without them. A `window` of 0 pays orders out straight away.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeConfig {
+    pub window: u64,
+    pub response_window: u64,
+}
+
+impl DisputeConfig {
+    /// Escrow is off until an admin sets a window.
+    pub const DEFAULT: DisputeConfig = DisputeConfig {
+        window: 0,
+        response_window: 0,
+    };
+}
+
+/// An order's payment held by the contract, in stroops of the order's
+/// token. Anyone can release it to the payout accounts once `release_at`
+/// has passed, unless the buyer has disputed it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Escrow {
+    pub order_id: u32,
+    pub amount: i128,
+    pub release_at: u64,
+}
+
+/// Where a dispute is. The last three are the arbiter's rulings.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DisputeStatus {
+    Open,
+    Answered,
+    Refunded,
+    Released,
+    Split,
+}
+
+/// What the arbiter decides for a dispute: pay the escrow back to the buyer,
+/// release it to the payout accounts, or refund the buyer this share of it
+/// in basis points and release the rest.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ruling {
+    Refund,
+    Release,
+    Split(u32),
+}
+
+/// A buyer's dispute of an escrowed order. `claim` and `response` are hashes
+/// of the buyer's and the seller's statements, which are kept off chain;
+/// `response` is all zeros until the seller answers. `refunded` is what the
+/// ruling paid back to the buyer.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dispute {
+    pub order_id: u32,
+    pub status: DisputeStatus,
+    pub claim: BytesN<32>,
+    pub response: BytesN<32>,
+    pub respond_by: u64,
+    pub refunded: i128,
+}
+
+pub(crate) fn get_config(env: &Env) -> DisputeConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::DisputeConfig)
+        .unwrap_or(DisputeConfig::DEFAULT)
+}
+
+/// Replaces the windows. Orders already in escrow keep their release time.
+pub(crate) fn set_config(env: &Env, config: DisputeConfig) -> Result<(), OrderError> {
+    if config.window > 0 && config.response_window == 0 {
+        return Err(OrderError::InvalidDisputeConfig);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::DisputeConfig, &config);
+    events::dispute_config_changed(env, config);
+    Ok(())
+}
+
+pub(crate) fn get_arbiter(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Arbiter)
+}
+
+pub(crate) fn set_arbiter(env: &Env, arbiter: Address) {
+    env.storage().instance().set(&DataKey::Arbiter, &arbiter);
+    events::arbiter_changed(env, arbiter);
+}
+
+pub(crate) fn get_escrow(env: &Env, order_id: u32) -> Option<Escrow> {
+    storage::get(env, &DataKey::Escrow(order_id))
+}
+
+/// Takes `amount` back out of an order's escrow to refund part of the
+/// order from it. Returns false, leaving the escrow alone, if the order has
+/// no escrow or less than `amount` left in it.
+pub(crate) fn take(env: &Env, order_id: u32, amount: i128) -> bool {
+    let Some(mut escrow) = get_escrow(env, order_id) else {
+        return false;
+    };
+    if escrow.amount < amount {
+        return false;
+    }
+    escrow.amount -= amount;
+    if escrow.amount == 0 {
+        close(env, order_id);
+    } else {
+        storage::set(env, &DataKey::Escrow(order_id), &escrow);
+    }
+    true
+}
+
+pub(crate) fn get(env: &Env, order_id: u32) -> Option<Dispute> {
+    storage::get(env, &DataKey::Dispute(order_id))
+}
+
+/// Moves an order's payment of `amount` from the buyer into escrow. The
+/// referrer is kept with it and paid when the escrow is released.
+pub(crate) fn hold(
+    env: &Env,
+    order_id: u32,
+    buyer: &Address,
+    token_id: &Address,
+    amount: i128,
+    referrer: Option<Address>,
+) -> Result<(), Error> {
+    if let Some(referrer) = referrer {
+        if referrer == *buyer {
+            return Err(Error::SelfReferral);
+        }
+        storage::set(env, &DataKey::EscrowReferrer(order_id), &referrer);
+    }
+    token::Client::new(env, token_id).transfer(buyer, &env.current_contract_address(), &amount);
+
+    let escrow = Escrow {
+        order_id,
+        amount,
+        release_at: env
+            .ledger()
+            .timestamp()
+            .saturating_add(get_config(env).window),
+    };
+    storage::set(env, &DataKey::Escrow(order_id), &escrow);
+    diag!(
+        env,
+        "escrow.hold",
+        order = order_id,
+        amount = amount,
+        release_at = escrow.release_at,
+    );
+    events::escrowed(env, escrow);
+    Ok(())
+}
+
+/// Pays an undisputed escrow out to the payout accounts once its window has
+/// passed. Returns the reserve, launchpad and dev amounts. Fails with an
+/// `OrderError`, or with the `Error` of paying the shares out.
+pub(crate) fn release(env: &Env, order_id: u32) -> Result<(i128, i128, i128), soroban_sdk::Error> {
+    let escrow = get_escrow(env, order_id).ok_or(OrderError::NoEscrow)?;
+    if get(env, order_id).is_some() {
+        return Err(OrderError::AlreadyDisputed.into());
+    }
+    if env.ledger().timestamp() < escrow.release_at {
+        return Err(OrderError::DisputeWindowOpen.into());
+    }
+
+    let (reserve_amount, launchpad_amount, dev_amount) = pay_out(env, order_id, escrow.amount)?;
+    events::escrow_released(
+        env,
+        EscrowReleaseEvent {
+            order_id,
+            reserve_amount,
+            launchpad_amount,
+            dev_amount,
+        },
+    );
+    Ok((reserve_amount, launchpad_amount, dev_amount))
+}
+
+/// Disputes an escrowed order before its payment is released. Only the
+/// buyer can open a dispute, once per order.
+pub(crate) fn open(env: &Env, order_id: u32, claim: BytesN<32>) -> Result<(), OrderError> {
+    let order = checkout::get_order(env, order_id).ok_or(OrderError::OrderNotExist)?;
+    order.buyer.require_auth();
+    if get(env, order_id).is_some() {
+        return Err(OrderError::AlreadyDisputed);
+    }
+    let escrow = get_escrow(env, order_id).ok_or(OrderError::NoEscrow)?;
+    let now = env.ledger().timestamp();
+    if now >= escrow.release_at {
+        return Err(OrderError::DisputeWindowClosed);
+    }
+
+    let dispute = Dispute {
+        order_id,
+        status: DisputeStatus::Open,
+        claim,
+        response: BytesN::from_array(env, &[0; 32]),
+        respond_by: now.saturating_add(get_config(env).response_window),
+        refunded: 0,
+    };
+    store(env, &dispute);
+    diag!(
+        env,
+        "dispute.open",
+        order = order_id,
+        respond_by = dispute.respond_by,
+    );
+    events::dispute_opened(env, dispute);
+    Ok(())
+}
+
+/// Records the seller's answer to an open dispute. The seller is the reserve
+/// account and has to answer before `respond_by`.
+pub(crate) fn respond(env: &Env, order_id: u32, response: BytesN<32>) -> Result<(), OrderError> {
+    Marketplace::get_reserve_acc(env.clone()).require_auth();
+    let mut dispute = get(env, order_id).ok_or(OrderError::NoDispute)?;
+    if dispute.status != DisputeStatus::Open {
+        return Err(OrderError::DisputeClosed);
+    }
+    if env.ledger().timestamp() >= dispute.respond_by {
+        return Err(OrderError::ResponseWindowClosed);
+    }
+
+    dispute.status = DisputeStatus::Answered;
+    dispute.response = response;
+    store(env, &dispute);
+    events::dispute_answered(env, dispute);
+    Ok(())
+}
+
+/// Settles a dispute by the arbiter's `ruling` and returns what was refunded
+/// to the buyer. The arbiter can rule once the seller has answered or has
+/// run out of time to. Whatever share is refunded is undone with
+/// `checkout::unwind`. Fails with an `OrderError`, with `InvalidSplit` for a
+/// split that isn't strictly between 0 and 10000 basis points, or with the
+/// `Error` of paying the rest out.
+pub(crate) fn resolve(
+    env: &Env,
+    order_id: u32,
+    ruling: Ruling,
+) -> Result<i128, soroban_sdk::Error> {
+    get_arbiter(env)
+        .ok_or(OrderError::NoArbiter)?
+        .require_auth();
+    let mut dispute = get(env, order_id).ok_or(OrderError::NoDispute)?;
+    match dispute.status {
+        DisputeStatus::Open if env.ledger().timestamp() < dispute.respond_by => {
+            return Err(OrderError::ResponsePending.into());
+        }
+        DisputeStatus::Open | DisputeStatus::Answered => {}
+        _ => return Err(OrderError::DisputeClosed.into()),
+    }
+    let escrow = get_escrow(env, order_id).ok_or(OrderError::NoEscrow)?;
+    // The share of the order refunded, in basis points.
+    let (status, bps) = match ruling {
+        Ruling::Refund => (DisputeStatus::Refunded, BPS),
+        Ruling::Release => (DisputeStatus::Released, 0),
+        Ruling::Split(bps) if bps > 0 && (bps as i128) < BPS => (DisputeStatus::Split, bps as i128),
+        Ruling::Split(_) => return Err(Error::InvalidSplit.into()),
+    };
+    let refund = escrow.amount * bps / BPS;
+
+    if refund > 0 {
+        let order = checkout::get_order(env, order_id).ok_or(OrderError::OrderNotExist)?;
+        token::Client::new(env, &order.token).transfer(
+            &env.current_contract_address(),
+            &order.buyer,
+            &refund,
+        );
+    }
+    if refund < escrow.amount {
+        pay_out(env, order_id, escrow.amount - refund)?;
+    } else {
+        close(env, order_id);
+    }
+    if bps > 0 {
+        checkout::unwind(env, order_id, bps);
+    }
+
+    dispute.status = status;
+    dispute.refunded = refund;
+    store(env, &dispute);
+    diag!(env, "dispute.resolve", order = order_id, refunded = refund);
+    events::dispute_resolved(env, dispute);
+    Ok(refund)
+}
+
+fn store(env: &Env, dispute: &Dispute) {
+    storage::set(env, &DataKey::Dispute(dispute.order_id), dispute);
+}
+
+/// Splits `amount` of an order's escrow between the payout accounts, as the
+/// order would have been paid without escrow, and closes the escrow.
+fn pay_out(
+    env: &Env,
+    order_id: u32,
+    amount: i128,
+) -> Result<(i128, i128, i128), soroban_sdk::Error> {
+    let order = checkout::get_order(env, order_id).ok_or(OrderError::OrderNotExist)?;
+    let referrer = storage::get(env, &DataKey::EscrowReferrer(order_id));
+    let shares = payout::split_from(
+        env,
+        &order.token,
+        &env.current_contract_address(),
+        &order.buyer,
+        amount,
+        referrer,
+    )?;
+    close(env, order_id);
+    Ok(shares)
+}
+
+fn close(env: &Env, order_id: u32) {
+    storage::remove(env, &DataKey::Escrow(order_id));
+    storage::remove(env, &DataKey::EscrowReferrer(order_id));
+}
@@ -0,0 +1,511 @@
+//! Typed contract events.
+//!
+//! Every event is published with three topics:
+//!
+//! ```text
+//! ("market", <version>, <name>)
+//! ```
+//!
+//! `version` is [`EVENTS_VERSION`] and is bumped whenever a payload changes
+//! shape, so indexers can keep decoders for older ledgers side by side. The
+//! data of each event is one of the `#[contracttype]` structs below:
+//!
+//! | name        | payload                  |
+//! |-------------|--------------------------|
+//! | `init`      | [`InitEvent`]            |
+//! | `prod_new`  | [`ProductEvent`]         |
+//! | `prod_upd`  | [`ProductEvent`]         |
+//! | `purchase`  | [`PurchaseEvent`]        |
+//! | `var_upd`   | [`VariantEvent`]         |
+//! | `order`     | [`OrderEvent`]           |
+//! | `bundle`    | [`Bundle`]               |
+//! | `bndl_buy`  | [`BundlePurchaseEvent`]  |
+//! | `review`    | [`Review`]               |
+//! | `escrow`    | [`Escrow`]               |
+//! | `release`   | [`EscrowReleaseEvent`]   |
+//! | `disp_open` | [`Dispute`]              |
+//! | `disp_ans`  | [`Dispute`]              |
+//! | `disp_rule` | [`Dispute`]              |
+//! | `receipt`   | [`Receipt`]              |
+//! | `rcpt_xfer` | [`ReceiptTransferEvent`] |
+//! | `rcpt_appr` | [`ReceiptApprovalEvent`] |
+//! | `rcpt_rvk`  | `u32` receipt id         |
+//! | `rcpt_burn` | `u32` receipt id         |
+//! | `voucher`   | [`Voucher`]              |
+//! | `redeem`    | [`RedeemEvent`]          |
+//! | `vchr_void` | `u32` voucher id         |
+//! | `referral`  | [`ReferralEvent`]        |
+//! | `points`    | [`PointsEvent`]          |
+//! | `refund`    | [`RefundEvent`]          |
+//! | `withdraw`  | [`WithdrawEvent`]        |
+//! | `vested`    | [`VestingClaimEvent`]    |
+//! | `acct_set`  | [`AccountChangedEvent`]  |
+//! | `split_set` | [`SplitChangedEvent`]    |
+//! | `mode_set`  | [`PayoutMode`]           |
+//! | `paused`    | `bool`                   |
+//! | `limits`    | [`ProductLimits`]        |
+//! | `phases`    | [`SalePhasesEvent`]      |
+//! | `allowlist` | [`AllowlistEvent`]       |
+//! | `rcpt_mode` | [`ReceiptModeEvent`]     |
+//! | `vchr_set`  | [`VoucherValidityEvent`] |
+//! | `vchr_off`  | `u32` product id         |
+//! | `redeemer`  | [`RedeemerEvent`]        |
+//! | `ref_cfg`   | [`ReferralConfig`]       |
+//! | `loyalty`   | [`LoyaltyConfig`]        |
+//! | `disp_cfg`  | [`DisputeConfig`]        |
+//! | `arbiter`   | `Address` arbiter        |
+//! | `coupon`    | [`CouponEvent`]          |
+//! | `coupon_rm` | `Symbol` coupon code     |
+//! | `upgrade`   | `BytesN<32>` wasm hash   |
+//! | `vest_set`  | [`VestingEvent`]         |
+//! | `vest_rm`   | `Address` recipient      |
+//! | `prop_new`  | [`ProposalEvent`]        |
+//! | `prop_appr` | [`ProposalEvent`]        |
+//! | `prop_exec` | [`Proposal`]             |
+
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, IntoVal, Symbol, Val, Vec};
+
+use crate::{
+    Bundle, Coupon, Dispute, DisputeConfig, Escrow, LoyaltyConfig, PayoutMode, Product,
+    ProductLimits, Proposal, Receipt, ReceiptMode, ReferralConfig, Review, SalePhase, Split,
+    VestingSchedule, Voucher,
+};
+
+pub const EVENTS_NAMESPACE: Symbol = symbol_short!("market");
+pub const EVENTS_VERSION: Symbol = symbol_short!("v1");
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InitEvent {
+    pub admin: Address,
+    pub reserve_acc: Address,
+    pub dev_acc: Address,
+    pub launchpad_acc: Address,
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductEvent {
+    pub product_id: u32,
+    pub price: i128,
+    pub remaining: i128,
+    pub expiry: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PurchaseEvent {
+    pub buyer: Address,
+    pub product_id: u32,
+    pub token: Address,
+    pub quantity: u32,
+    pub reserve_amount: i128,
+    pub launchpad_amount: i128,
+    pub dev_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundEvent {
+    pub buyer: Address,
+    pub product_id: u32,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawEvent {
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub remaining: i128,
+}
+
+/// `unvested` is what stays locked in the vault after the claim.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingClaimEvent {
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub unvested: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccountChangedEvent {
+    pub role: Symbol,
+    pub old: Address,
+    pub new: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitChangedEvent {
+    pub old: Split,
+    pub new: Split,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingEvent {
+    pub recipient: Address,
+    pub schedule: VestingSchedule,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalEvent {
+    pub proposal_id: u32,
+    pub signer: Address,
+}
+
+/// A variant was added or sold; `price` is what one unit costs.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VariantEvent {
+    pub product_id: u32,
+    pub variant_id: u32,
+    pub price: i128,
+    pub remaining: i128,
+}
+
+/// A checkout was paid; amounts are in stroops, `total` after `discount`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OrderEvent {
+    pub order_id: u32,
+    pub buyer: Address,
+    pub token: Address,
+    pub discount: i128,
+    pub total: i128,
+}
+
+/// A bundle was bought; the constituents' new stock follows as `prod_upd`
+/// events.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BundlePurchaseEvent {
+    pub buyer: Address,
+    pub bundle_id: u32,
+    pub token: Address,
+    pub reserve_amount: i128,
+    pub launchpad_amount: i128,
+    pub dev_amount: i128,
+}
+
+/// An undisputed escrow was paid out to the payout accounts.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowReleaseEvent {
+    pub order_id: u32,
+    pub reserve_amount: i128,
+    pub launchpad_amount: i128,
+    pub dev_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptTransferEvent {
+    pub receipt_id: u32,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptApprovalEvent {
+    pub receipt_id: u32,
+    pub owner: Address,
+    pub spender: Address,
+}
+
+/// A referrer was paid `amount` stroops of commission on a purchase.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralEvent {
+    pub referrer: Address,
+    pub buyer: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// A buyer's loyalty balance changed by `change` points: earned when
+/// positive, redeemed when negative.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PointsEvent {
+    pub account: Address,
+    pub change: i128,
+    pub balance: i128,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SalePhasesEvent {
+    pub product_id: u32,
+    pub phases: Vec<SalePhase>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowlistEvent {
+    pub product_id: u32,
+    pub accounts: Vec<Address>,
+    pub allowed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptModeEvent {
+    pub product_id: u32,
+    pub mode: ReceiptMode,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedeemEvent {
+    pub voucher_id: u32,
+    pub product_id: u32,
+    pub redeemer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoucherValidityEvent {
+    pub product_id: u32,
+    pub validity: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedeemerEvent {
+    pub product_id: u32,
+    pub redeemer: Address,
+    pub allowed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CouponEvent {
+    pub code: Symbol,
+    pub coupon: Coupon,
+}
+
+fn publish<D: IntoVal<Env, Val>>(env: &Env, name: Symbol, data: D) {
+    env.events()
+        .publish((EVENTS_NAMESPACE, EVENTS_VERSION, name), data);
+}
+
+pub(crate) fn init(env: &Env, event: InitEvent) {
+    publish(env, symbol_short!("init"), event);
+}
+
+fn product_event(product: &Product) -> ProductEvent {
+    ProductEvent {
+        product_id: product.id,
+        price: product.price,
+        remaining: product.remaining,
+        expiry: product.expiry,
+    }
+}
+
+pub(crate) fn product_created(env: &Env, product: &Product) {
+    publish(env, symbol_short!("prod_new"), product_event(product));
+}
+
+pub(crate) fn product_updated(env: &Env, product: &Product) {
+    publish(env, symbol_short!("prod_upd"), product_event(product));
+}
+
+pub(crate) fn purchase(env: &Env, event: PurchaseEvent) {
+    publish(env, symbol_short!("purchase"), event);
+}
+
+pub(crate) fn variant_updated(env: &Env, event: VariantEvent) {
+    publish(env, symbol_short!("var_upd"), event);
+}
+
+pub(crate) fn order(env: &Env, event: OrderEvent) {
+    publish(env, symbol_short!("order"), event);
+}
+
+pub(crate) fn bundle_created(env: &Env, bundle: Bundle) {
+    publish(env, symbol_short!("bundle"), bundle);
+}
+
+pub(crate) fn bundle_purchase(env: &Env, event: BundlePurchaseEvent) {
+    publish(env, symbol_short!("bndl_buy"), event);
+}
+
+pub(crate) fn reviewed(env: &Env, review: Review) {
+    publish(env, symbol_short!("review"), review);
+}
+
+pub(crate) fn escrowed(env: &Env, escrow: Escrow) {
+    publish(env, symbol_short!("escrow"), escrow);
+}
+
+pub(crate) fn escrow_released(env: &Env, event: EscrowReleaseEvent) {
+    publish(env, symbol_short!("release"), event);
+}
+
+pub(crate) fn dispute_opened(env: &Env, dispute: Dispute) {
+    publish(env, symbol_short!("disp_open"), dispute);
+}
+
+pub(crate) fn dispute_answered(env: &Env, dispute: Dispute) {
+    publish(env, symbol_short!("disp_ans"), dispute);
+}
+
+pub(crate) fn dispute_resolved(env: &Env, dispute: Dispute) {
+    publish(env, symbol_short!("disp_rule"), dispute);
+}
+
+pub(crate) fn receipt_minted(env: &Env, receipt: Receipt) {
+    publish(env, symbol_short!("receipt"), receipt);
+}
+
+pub(crate) fn receipt_transferred(env: &Env, event: ReceiptTransferEvent) {
+    publish(env, symbol_short!("rcpt_xfer"), event);
+}
+
+pub(crate) fn receipt_approved(env: &Env, event: ReceiptApprovalEvent) {
+    publish(env, symbol_short!("rcpt_appr"), event);
+}
+
+pub(crate) fn receipt_approval_revoked(env: &Env, receipt_id: u32) {
+    publish(env, symbol_short!("rcpt_rvk"), receipt_id);
+}
+
+pub(crate) fn receipt_burned(env: &Env, receipt_id: u32) {
+    publish(env, symbol_short!("rcpt_burn"), receipt_id);
+}
+
+pub(crate) fn voucher_issued(env: &Env, voucher: Voucher) {
+    publish(env, symbol_short!("voucher"), voucher);
+}
+
+pub(crate) fn voucher_redeemed(env: &Env, event: RedeemEvent) {
+    publish(env, symbol_short!("redeem"), event);
+}
+
+pub(crate) fn voucher_voided(env: &Env, voucher_id: u32) {
+    publish(env, symbol_short!("vchr_void"), voucher_id);
+}
+
+pub(crate) fn referral(env: &Env, event: ReferralEvent) {
+    publish(env, symbol_short!("referral"), event);
+}
+
+pub(crate) fn points_changed(env: &Env, event: PointsEvent) {
+    publish(env, symbol_short!("points"), event);
+}
+
+pub(crate) fn refund(env: &Env, event: RefundEvent) {
+    publish(env, symbol_short!("refund"), event);
+}
+
+pub(crate) fn withdraw(env: &Env, event: WithdrawEvent) {
+    publish(env, symbol_short!("withdraw"), event);
+}
+
+pub(crate) fn vesting_claimed(env: &Env, event: VestingClaimEvent) {
+    publish(env, symbol_short!("vested"), event);
+}
+
+pub(crate) fn account_changed(env: &Env, event: AccountChangedEvent) {
+    publish(env, symbol_short!("acct_set"), event);
+}
+
+pub(crate) fn split_changed(env: &Env, event: SplitChangedEvent) {
+    publish(env, symbol_short!("split_set"), event);
+}
+
+pub(crate) fn payout_mode_changed(env: &Env, mode: PayoutMode) {
+    publish(env, symbol_short!("mode_set"), mode);
+}
+
+pub(crate) fn paused_changed(env: &Env, paused: bool) {
+    publish(env, symbol_short!("paused"), paused);
+}
+
+pub(crate) fn product_limits_changed(env: &Env, limits: ProductLimits) {
+    publish(env, symbol_short!("limits"), limits);
+}
+
+pub(crate) fn referral_config_changed(env: &Env, config: ReferralConfig) {
+    publish(env, symbol_short!("ref_cfg"), config);
+}
+
+pub(crate) fn loyalty_config_changed(env: &Env, config: LoyaltyConfig) {
+    publish(env, symbol_short!("loyalty"), config);
+}
+
+pub(crate) fn dispute_config_changed(env: &Env, config: DisputeConfig) {
+    publish(env, symbol_short!("disp_cfg"), config);
+}
+
+pub(crate) fn arbiter_changed(env: &Env, arbiter: Address) {
+    publish(env, symbol_short!("arbiter"), arbiter);
+}
+
+pub(crate) fn sale_phases_changed(env: &Env, event: SalePhasesEvent) {
+    publish(env, symbol_short!("phases"), event);
+}
+
+pub(crate) fn allowlist_changed(env: &Env, event: AllowlistEvent) {
+    publish(env, symbol_short!("allowlist"), event);
+}
+
+pub(crate) fn receipt_mode_changed(env: &Env, event: ReceiptModeEvent) {
+    publish(env, symbol_short!("rcpt_mode"), event);
+}
+
+pub(crate) fn voucher_validity_set(env: &Env, event: VoucherValidityEvent) {
+    publish(env, symbol_short!("vchr_set"), event);
+}
+
+pub(crate) fn vouchers_disabled(env: &Env, product_id: u32) {
+    publish(env, symbol_short!("vchr_off"), product_id);
+}
+
+pub(crate) fn redeemer_changed(env: &Env, event: RedeemerEvent) {
+    publish(env, symbol_short!("redeemer"), event);
+}
+
+pub(crate) fn coupon_set(env: &Env, event: CouponEvent) {
+    publish(env, symbol_short!("coupon"), event);
+}
+
+pub(crate) fn coupon_removed(env: &Env, code: Symbol) {
+    publish(env, symbol_short!("coupon_rm"), code);
+}
+
+pub(crate) fn upgraded(env: &Env, wasm_hash: BytesN<32>) {
+    publish(env, symbol_short!("upgrade"), wasm_hash);
+}
+
+pub(crate) fn vesting_set(env: &Env, event: VestingEvent) {
+    publish(env, symbol_short!("vest_set"), event);
+}
+
+pub(crate) fn vesting_removed(env: &Env, recipient: Address) {
+    publish(env, symbol_short!("vest_rm"), recipient);
+}
+
+pub(crate) fn proposal_created(env: &Env, event: ProposalEvent) {
+    publish(env, symbol_short!("prop_new"), event);
+}
+
+pub(crate) fn proposal_approved(env: &Env, event: ProposalEvent) {
+    publish(env, symbol_short!("prop_appr"), event);
+}
+
+pub(crate) fn proposal_executed(env: &Env, proposal: Proposal) {
+    publish(env, symbol_short!("prop_exec"), proposal);
+}
@@ -1,15 +1,56 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, log, symbol_short, token, Address, Env,
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Bytes, BytesN, Env,
     String, Symbol, Vec,
 };
 
+mod admin;
+mod bundles;
+mod catalog;
+mod checkout;
+mod diagnostics;
+mod disputes;
+pub mod events;
+mod loyalty;
+mod metadata;
+mod multisig;
+mod payout;
+mod phases;
+mod purchase;
+mod receipts;
+mod referrals;
+mod reviews;
+mod storage;
+mod variants;
+mod vesting;
+mod vouchers;
+
+pub use bundles::Bundle;
+pub use catalog::{ProductInput, ProductLimits, MAX_BATCH_SIZE};
+pub use checkout::{Coupon, Order};
+pub use disputes::{Dispute, DisputeConfig, DisputeStatus, Escrow, Ruling};
+pub use loyalty::{LoyaltyConfig, Points};
+pub use metadata::ProductMetadata;
+pub use multisig::{Proposal, ProposalAction};
+pub use payout::PayoutMode;
+pub use phases::{PhaseAccess, SalePhase, MAX_PHASES};
+pub use receipts::{Receipt, ReceiptMode};
+pub use referrals::{ReferralConfig, ReferralSource};
+pub use reviews::{Rating, Review, MAX_RATING};
+pub use variants::{Variant, MAX_VARIANTS};
+pub use vesting::{VestingBalance, VestingSchedule};
+pub use vouchers::{Voucher, VoucherStatus};
+
+use events::InitEvent;
+
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
 pub enum Error {
     DiscountExpired = 1,
     ProductNotExist = 2,
+    /// No longer returned; `get_discount` reports a zero amount as
+    /// `AmountMustBeGreaterThanZero`. Kept so the other codes stay put.
     AmountMustNonZero = 3,
     TargetReached = 4,
     AmountExceedTargetLimit = 5,
@@ -19,6 +60,67 @@ pub enum Error {
     ExpiryShouldBeFuture = 9,
     AlreadyInitialized = 10,
     AmountMustBeGreaterThanZero = 11,
+    InvalidThreshold = 12,
+    NotSigner = 13,
+    ProposalNotExist = 14,
+    AlreadyApproved = 15,
+    ThresholdNotReached = 16,
+    TimelockNotElapsed = 17,
+    ProposalAlreadyExecuted = 18,
+    InvalidSplit = 19,
+    InsufficientClaimable = 20,
+    Paused = 21,
+    EmptyBatch = 22,
+    BatchTooLarge = 23,
+    EmptyTitle = 24,
+    TitleTooLong = 25,
+    DescriptionTooLong = 26,
+    CategoryTooLong = 27,
+    ImageTooLong = 28,
+    PriceMustBePositive = 29,
+    TargetMustBePositive = 30,
+    InvalidProductLimits = 31,
+    NoMetadata = 32,
+    VariantNotExist = 33,
+    VariantRequired = 34,
+    TooManyVariants = 35,
+    InvalidCoupon = 36,
+    BundleNotExist = 37,
+    SelfReferral = 38,
+    InsufficientPoints = 39,
+    InvalidLoyaltyConfig = 40,
+    SaleNotActive = 41,
+    NotAllowlisted = 42,
+    InvalidSchedule = 43,
+    ReceiptNotExist = 44,
+    NotReceiptOwner = 45,
+    NotTransferable = 46,
+    VoucherNotExist = 47,
+    InvalidVoucherState = 48,
+    NotRedeemer = 49,
+}
+
+/// Errors of reviews and disputes of checkout orders. A contract error enum
+/// can have at most 50 cases, so these carry on from `Error`'s codes.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum OrderError {
+    OrderNotExist = 51,
+    NotBuyer = 52,
+    AlreadyReviewed = 53,
+    InvalidRating = 54,
+    NoEscrow = 55,
+    DisputeWindowClosed = 56,
+    DisputeWindowOpen = 57,
+    AlreadyDisputed = 58,
+    NoDispute = 59,
+    NoArbiter = 60,
+    ResponsePending = 61,
+    ResponseWindowClosed = 62,
+    DisputeClosed = 63,
+    InvalidDisputeConfig = 64,
+    OrderRefunded = 65,
 }
 
 #[contracttype]
@@ -34,6 +136,26 @@ pub struct Product {
     pub remaining: i128,
 }
 
+/// A product as a storefront shows it, with its variants and rating.
+/// Assembled on read so that stored `Product`s keep their shape.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductView {
+    pub product: Product,
+    pub variants: Vec<Variant>,
+    pub rating: Rating,
+}
+
+/// Percentage shares used by `get_discount` to split a payment between the
+/// payout accounts. The shares always add up to 100.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Split {
+    pub reserve: i128,
+    pub launchpad: i128,
+    pub dev: i128,
+}
+
 const NO_OF_PRODUCTS: Symbol = symbol_short!("PRODUCTS");
 
 const RESERVE_PER: i128 = 60;
@@ -47,6 +169,64 @@ pub enum DataKey {
     DevAccount,
     LaunchpadAccount,
     Admin,
+    Split,
+    Signers,
+    Threshold,
+    NoOfProposals,
+    Proposal(u32),
+    PayoutMode,
+    Claimable(Address, Address),
+    Paused,
+    ProductLimits,
+    Metadata(u32),
+    Coupon(Symbol),
+    NoOfOrders,
+    Order(u32),
+    NoOfBundles,
+    Bundle(u32),
+    ReferralConfig,
+    ReferralEarnings(Address, Address),
+    LoyaltyConfig,
+    Points(Address),
+    SalePhases(u32),
+    PhaseSold(u32, u32),
+    Allowlist(u32, Address),
+    VestingSchedule(Address),
+    Vesting(Address, Address),
+    ReceiptMode(u32),
+    NoOfReceipts,
+    Receipt(u32),
+    ReceiptApproval(u32),
+    VoucherValidity(u32),
+    NoOfVouchers,
+    Voucher(u32),
+    /// Number of vouchers a product has issued.
+    ProductVouchers(u32),
+    /// Keyed by product id and the voucher's index among the product's;
+    /// holds the voucher id.
+    ProductVoucher(u32, u32),
+    Redeemer(u32, Address),
+    /// Keyed by order id and product id.
+    Review(u32, u32),
+    DisputeConfig,
+    Arbiter,
+    Escrow(u32),
+    EscrowReferrer(u32),
+    Dispute(u32),
+    /// A product's variants, kept apart from the product so that products
+    /// stored before variants existed still decode.
+    Variants(u32),
+    /// A product's rating, aggregated from buyers' reviews and kept apart
+    /// from the product for the same reason.
+    Rating(u32),
+    /// Set once an account buys a product outside `checkout`, which lets it
+    /// review the product with `review_purchase`.
+    Purchased(Address, u32),
+    /// An account's review of a product it bought outside `checkout`.
+    PurchaseReview(Address, u32),
+    /// What a checkout order handed out besides its units, kept so that a
+    /// dispute refunding the order can take it back.
+    OrderGrants(u32),
 }
 
 #[contract]
@@ -60,11 +240,14 @@ impl Marketplace {
         dev_acc: Address,
         launchpad_acc: Address,
         admin: Address,
+        signers: Vec<Address>,
+        threshold: u32,
     ) -> Result<String, Error> {
         admin.require_auth();
         if env.storage().instance().has(&DataKey::Admin) {
             return Err(Error::AlreadyInitialized);
         }
+        multisig::check_signers(&signers, threshold)?;
 
         env.storage()
             .instance()
@@ -74,9 +257,30 @@ impl Marketplace {
             .instance()
             .set(&DataKey::LaunchpadAccount, &launchpad_acc);
         env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(
+            &DataKey::Split,
+            &Split {
+                reserve: RESERVE_PER,
+                launchpad: LAUNCHPAD_PER,
+                dev: DEV_PER,
+            },
+        );
+        env.storage().instance().set(&DataKey::Signers, &signers);
+        env.storage()
+            .instance()
+            .set(&DataKey::Threshold, &threshold);
 
-        env.events()
-            .publish((symbol_short!("INIT"), symbol_short!("accounts")), admin);
+        events::init(
+            &env,
+            InitEvent {
+                admin,
+                reserve_acc,
+                dev_acc,
+                launchpad_acc,
+                signers,
+                threshold,
+            },
+        );
 
         Ok(String::from_str(&env, "Initialized"))
     }
@@ -91,43 +295,48 @@ impl Marketplace {
         product_price: i128,
         product_target: i128,
     ) -> Result<Product, Error> {
-        if product_expiry < env.ledger().timestamp() {
-            return Err(Error::ExpiryShouldBeFuture);
-        }
-
-        let mut count_id: u32 = env.storage().instance().get(&NO_OF_PRODUCTS).unwrap_or(0); // If no value set, assume 0.
-
-        count_id += 1;
-
-        let check_product = Self::get_product(env.clone(), count_id.clone());
-
-        if check_product.id == count_id {
-            return Err(Error::ProductAlreadyExist);
-        }
-        if count_id == 0 {
-            return Err(Error::IdProductMustNonZero);
-        }
+        catalog::create(
+            &env,
+            ProductInput {
+                title: product_title,
+                description: product_description,
+                category: product_category,
+                expiry: product_expiry,
+                image: product_image,
+                price: product_price,
+                target: product_target,
+            },
+        )
+    }
 
-        let product = Product {
-            id: count_id,
-            title: product_title,
-            description: product_description,
-            remaining: product_target,
-            expiry: product_expiry,
-            category: product_category,
-            image: product_image,
-            price: product_price,
-        };
-
-        env.storage().instance().set(&NO_OF_PRODUCTS, &count_id);
-        env.storage().instance().set(&count_id, &product);
-
-        env.events().publish(
-            (symbol_short!("create"), symbol_short!("product")),
-            count_id,
-        );
+    /// Creates a product described by an off-chain blob instead of an
+    /// on-chain description and image.
+    pub fn create_product_with_metadata(
+        env: Env,
+        product_title: String,
+        product_category: String,
+        product_expiry: u64,
+        product_price: i128,
+        product_target: i128,
+        metadata: ProductMetadata,
+    ) -> Result<Product, Error> {
+        metadata::create(
+            &env,
+            product_title,
+            product_category,
+            product_expiry,
+            product_price,
+            product_target,
+            metadata,
+        )
+    }
 
-        return Ok(product);
+    /// Creates all of `products` or, if any of them is invalid, none.
+    pub fn create_products_batch(
+        env: Env,
+        products: Vec<ProductInput>,
+    ) -> Result<Vec<Product>, Error> {
+        catalog::create_batch(&env, products)
     }
 
     pub fn get_products(env: Env) -> Vec<Product> {
@@ -141,7 +350,13 @@ impl Marketplace {
             products.push_back(product);
         }
 
-        return products;
+        products
+    }
+
+    /// The products still on sale in the shape `create_products_batch`
+    /// takes, for seeding another deployment.
+    pub fn export_products(env: Env) -> Vec<ProductInput> {
+        catalog::export(&env)
     }
 
     pub fn get_product(env: Env, product_id: u32) -> Product {
@@ -160,72 +375,425 @@ impl Marketplace {
                 expiry: 0,
             });
 
-        return product;
+        product
+    }
+
+    /// The product along with its variants and rating.
+    pub fn get_product_view(env: Env, product_id: u32) -> ProductView {
+        ProductView {
+            product: Self::get_product(env.clone(), product_id),
+            variants: variants::get_all(&env, product_id),
+            rating: reviews::get_rating(&env, product_id),
+        }
+    }
+
+    /// Every product as `get_product_view` shows it, in id order.
+    pub fn get_product_views(env: Env) -> Vec<ProductView> {
+        let mut views = Vec::new(&env);
+        let total_products: u32 = env.storage().instance().get(&NO_OF_PRODUCTS).unwrap_or(0);
+        for product_id in 1..=total_products {
+            views.push_back(Self::get_product_view(env.clone(), product_id));
+        }
+        views
+    }
+
+    pub fn get_product_metadata(env: Env, product_id: u32) -> Option<ProductMetadata> {
+        metadata::get(&env, product_id)
+    }
+
+    /// Checks `bytes` against the hash in the product's metadata, e.g. after
+    /// fetching them from the metadata URI.
+    pub fn verify_metadata(env: Env, product_id: u32, bytes: Bytes) -> Result<bool, Error> {
+        metadata::verify(&env, product_id, bytes)
     }
 
+    /// Buys one unit of product `id`, paying `amount` whole tokens split
+    /// between the payout accounts. A `referrer` earns the referral
+    /// commission out of that split, and the buyer earns loyalty points.
     pub fn get_discount(
         env: Env,
         id: u32,
         customer_address: Address,
         amount: i128,
         token_id: Address,
+        referrer: Option<Address>,
     ) -> Result<(i128, i128, i128), Error> {
-        customer_address.require_auth();
+        purchase::buy(
+            &env,
+            id,
+            customer_address,
+            amount,
+            token_id,
+            referrer,
+            0,
+            &Vec::new(&env),
+        )
+    }
 
-        if amount <= 0 {
-            return Err(Error::AmountMustBeGreaterThanZero);
-        }
+    /// Like `get_discount`, but spends `points` of the buyer's loyalty
+    /// balance for a discount at the configured burn rate.
+    pub fn purchase_with_points(
+        env: Env,
+        id: u32,
+        customer_address: Address,
+        amount: i128,
+        token_id: Address,
+        points: i128,
+        referrer: Option<Address>,
+    ) -> Result<(i128, i128, i128), Error> {
+        purchase::buy(
+            &env,
+            id,
+            customer_address,
+            amount,
+            token_id,
+            referrer,
+            points,
+            &Vec::new(&env),
+        )
+    }
 
-        // log!(&env, "amount: {}", amount);
-        let mut check_product = Self::get_product(env.clone(), id.clone());
+    /// Like `get_discount`, with a Merkle `proof` that the buyer is on the
+    /// allowlist of the product's current sale phase.
+    pub fn purchase_with_proof(
+        env: Env,
+        id: u32,
+        customer_address: Address,
+        amount: i128,
+        token_id: Address,
+        proof: Vec<BytesN<32>>,
+        referrer: Option<Address>,
+    ) -> Result<(i128, i128, i128), Error> {
+        purchase::buy(
+            &env,
+            id,
+            customer_address,
+            amount,
+            token_id,
+            referrer,
+            0,
+            &proof,
+        )
+    }
 
-        if check_product.id != id || id == 0 {
-            return Err(Error::ProductNotExist);
-        }
-        if amount <= 0 {
-            return Err(Error::AmountMustNonZero);
-        }
-        if check_product.remaining <= 0 {
-            return Err(Error::TargetReached);
-        }
+    /// Replaces the product's sale phases. With no phases the product sells
+    /// to anyone until it expires.
+    pub fn set_sale_phases(env: Env, product_id: u32, phases: Vec<SalePhase>) -> Result<(), Error> {
+        phases::set(&env, product_id, phases)
+    }
 
-        log!(&env, "amount: {}", amount);
-        let total_percentage = RESERVE_PER + LAUNCHPAD_PER + DEV_PER;
-        let real_amount = amount * 10000000;
-        // Payment splitting and calculating the percentage
-        let reserve_amount = (real_amount * RESERVE_PER) / total_percentage;
-        let launchpad_amount = (real_amount * LAUNCHPAD_PER) / total_percentage;
-        let dev_amount = (real_amount * DEV_PER) / total_percentage;
+    pub fn get_sale_phases(env: Env, product_id: u32) -> Vec<SalePhase> {
+        phases::get(&env, product_id)
+    }
 
-        if reserve_amount == 0 || launchpad_amount == 0 || dev_amount == 0 {
-            return Err(Error::LowAmountForSplitter);
-        }
+    /// Units sold so far in phase `index` of the product.
+    pub fn get_phase_sold(env: Env, product_id: u32, index: u32) -> i128 {
+        phases::get_sold(&env, product_id, index)
+    }
 
-        // get accounts
-        let reserve_acc = Self::get_reserve_acc(env.clone());
-        let dev_acc = Self::get_dev_acc(env.clone());
-        let launchpad_acc = Self::get_launchpad_acc(env.clone());
-
-        // transfer splitted Tokens to artist, dev, launchpad accounts
-        let client: token::TokenClient = token::Client::new(&env.clone(), &token_id);
-        log!(&env, "reserve_acc: ", reserve_acc);
-        log!(&env, "reserve_amount: ", reserve_amount);
-        client.transfer(&customer_address, &reserve_acc, &reserve_amount);
-        client.transfer(&customer_address, &dev_acc, &dev_amount);
-        client.transfer(&customer_address, &launchpad_acc, &launchpad_amount);
-        log!(&env, "Haris: ", amount);
-        // Save data
-        check_product.remaining -= 1;
-        env.storage()
-            .instance()
-            .set(&check_product.id, &check_product);
+    pub fn set_allowlist(
+        env: Env,
+        product_id: u32,
+        accounts: Vec<Address>,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        phases::set_allowlist(&env, product_id, accounts, allowed)
+    }
 
-        env.events().publish(
-            (symbol_short!("buy"), symbol_short!("discount")),
-            check_product.id,
-        );
+    pub fn is_allowlisted(env: Env, product_id: u32, account: Address) -> bool {
+        phases::is_allowlisted(&env, product_id, &account)
+    }
+
+    /// Sets whether purchases of the product through `get_discount` and
+    /// `checkout` mint receipts, and whether those can be transferred.
+    pub fn set_receipt_mode(env: Env, product_id: u32, mode: ReceiptMode) -> Result<(), Error> {
+        receipts::set_mode(&env, product_id, mode)
+    }
+
+    pub fn get_receipt_mode(env: Env, product_id: u32) -> ReceiptMode {
+        receipts::get_mode(&env, product_id)
+    }
+
+    pub fn get_receipt(env: Env, receipt_id: u32) -> Result<Receipt, Error> {
+        receipts::get(&env, receipt_id)
+    }
+
+    pub fn owner_of(env: Env, receipt_id: u32) -> Result<Address, Error> {
+        receipts::get(&env, receipt_id).map(|receipt| receipt.owner)
+    }
+
+    pub fn transfer_receipt(
+        env: Env,
+        from: Address,
+        to: Address,
+        receipt_id: u32,
+    ) -> Result<(), Error> {
+        receipts::transfer(&env, from.clone(), from, to, receipt_id)
+    }
+
+    /// Transfers a receipt on its owner's behalf; `spender` must hold the
+    /// receipt's approval.
+    pub fn transfer_receipt_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        receipt_id: u32,
+    ) -> Result<(), Error> {
+        receipts::transfer(&env, spender, from, to, receipt_id)
+    }
+
+    /// Approves `spender` to transfer the receipt, or clears the approval.
+    /// Transfers clear it too.
+    pub fn approve_receipt(
+        env: Env,
+        owner: Address,
+        receipt_id: u32,
+        spender: Option<Address>,
+    ) -> Result<(), Error> {
+        receipts::approve(&env, owner, receipt_id, spender)
+    }
+
+    pub fn get_receipt_approval(env: Env, receipt_id: u32) -> Option<Address> {
+        receipts::get_approval(&env, receipt_id)
+    }
+
+    /// Makes each unit of the product sold, by any purchase path, issue a
+    /// voucher, redeemable for `validity` seconds (0 for no limit).
+    pub fn set_voucher_validity(env: Env, product_id: u32, validity: u64) -> Result<(), Error> {
+        vouchers::set_validity(&env, product_id, validity)
+    }
+
+    pub fn disable_vouchers(env: Env, product_id: u32) {
+        vouchers::disable(&env, product_id)
+    }
+
+    pub fn get_voucher_validity(env: Env, product_id: u32) -> Option<u64> {
+        vouchers::get_validity(&env, product_id)
+    }
+
+    pub fn set_redeemer(
+        env: Env,
+        product_id: u32,
+        redeemer: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        vouchers::set_redeemer(&env, product_id, redeemer, allowed)
+    }
+
+    pub fn is_redeemer(env: Env, product_id: u32, account: Address) -> bool {
+        vouchers::is_redeemer(&env, product_id, &account)
+    }
+
+    pub fn get_voucher(env: Env, voucher_id: u32) -> Result<Voucher, Error> {
+        vouchers::get(&env, voucher_id)
+    }
+
+    pub fn get_voucher_count(env: Env, product_id: u32) -> u32 {
+        vouchers::count(&env, product_id)
+    }
+
+    /// Pages through the product's vouchers from its `start`th issued one,
+    /// keeping those that can still be redeemed. `limit` is capped at
+    /// `MAX_BATCH_SIZE`.
+    pub fn get_outstanding_vouchers(
+        env: Env,
+        product_id: u32,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Voucher> {
+        vouchers::get_outstanding(&env, product_id, start, limit)
+    }
+
+    /// Redeems an issued voucher. `redeemer` is the admin or one of the
+    /// product's redeemers.
+    pub fn redeem(env: Env, redeemer: Address, voucher_id: u32) -> Result<(), Error> {
+        vouchers::redeem(&env, redeemer, voucher_id)
+    }
+
+    /// Refunds an unused voucher from its order's escrow, or the reserve
+    /// account's share of it from the reserve, and returns the amount paid
+    /// back.
+    pub fn refund_voucher(env: Env, voucher_id: u32) -> Result<i128, Error> {
+        vouchers::refund(&env, voucher_id)
+    }
+
+    /// Unexpired loyalty points held by `address`.
+    pub fn get_points(env: Env, address: Address) -> i128 {
+        loyalty::get_points(&env, &address)
+    }
+
+    /// Adds a variant with its own stock to a product. Once a product has
+    /// variants, purchases must name one.
+    pub fn add_variant(
+        env: Env,
+        product_id: u32,
+        name: String,
+        price_delta: i128,
+        stock: i128,
+    ) -> Result<Variant, Error> {
+        variants::add(&env, product_id, name, price_delta, stock)
+    }
+
+    /// Lists a product's variants in id order. Empty unless the product is
+    /// sold in variants, which are then bought with `purchase_variant`.
+    pub fn get_variants(env: Env, product_id: u32) -> Vec<Variant> {
+        variants::get_all(&env, product_id)
+    }
+
+    /// Buys one unit of a variant, paying the variant's price split like
+    /// `get_discount`.
+    pub fn purchase_variant(
+        env: Env,
+        product_id: u32,
+        variant_id: u32,
+        customer_address: Address,
+        token_id: Address,
+        referrer: Option<Address>,
+    ) -> Result<(i128, i128, i128), Error> {
+        variants::purchase(
+            &env,
+            product_id,
+            variant_id,
+            customer_address,
+            token_id,
+            referrer,
+        )
+    }
 
-        return Ok((reserve_amount, launchpad_amount, dev_amount));
+    /// Lists existing products together at a bundle price.
+    pub fn create_bundle(
+        env: Env,
+        title: String,
+        product_ids: Vec<u32>,
+        price: i128,
+    ) -> Result<Bundle, Error> {
+        bundles::create(&env, title, product_ids, price)
+    }
+
+    pub fn get_bundle(env: Env, bundle_id: u32) -> Option<Bundle> {
+        bundles::get(&env, bundle_id)
+    }
+
+    /// Buys one bundle, paying the bundle price split like `get_discount` and
+    /// taking one unit of every product in it.
+    pub fn purchase_bundle(
+        env: Env,
+        bundle_id: u32,
+        customer_address: Address,
+        token_id: Address,
+        referrer: Option<Address>,
+    ) -> Result<(i128, i128, i128), Error> {
+        bundles::purchase(&env, bundle_id, customer_address, token_id, referrer)
+    }
+
+    /// Buys several products in one order, optionally with a coupon, and
+    /// returns the order id. `items` are `(product_id, quantity)` pairs.
+    pub fn checkout(
+        env: Env,
+        buyer: Address,
+        items: Vec<(u32, u32)>,
+        token_id: Address,
+        coupon: Option<Symbol>,
+        referrer: Option<Address>,
+    ) -> Result<u32, Error> {
+        checkout::checkout(&env, buyer, items, token_id, coupon, referrer)
+    }
+
+    pub fn get_order(env: Env, order_id: u32) -> Option<Order> {
+        checkout::get_order(&env, order_id)
+    }
+
+    /// Rates a product from one of the buyer's orders from 1 to
+    /// `MAX_RATING` stars, with `hash` pointing at the review text.
+    pub fn review(
+        env: Env,
+        order_id: u32,
+        product_id: u32,
+        rating: u32,
+        hash: BytesN<32>,
+    ) -> Result<(), OrderError> {
+        reviews::submit(&env, order_id, product_id, rating, hash)
+    }
+
+    pub fn get_review(env: Env, order_id: u32, product_id: u32) -> Option<Review> {
+        reviews::get(&env, order_id, product_id)
+    }
+
+    /// Rates a product the buyer bought through `get_discount`,
+    /// `purchase_variant` or `purchase_bundle`, once per buyer and product.
+    pub fn review_purchase(
+        env: Env,
+        buyer: Address,
+        product_id: u32,
+        rating: u32,
+        hash: BytesN<32>,
+    ) -> Result<(), OrderError> {
+        reviews::submit_purchase(&env, buyer, product_id, rating, hash)
+    }
+
+    pub fn get_purchase_review(env: Env, buyer: Address, product_id: u32) -> Option<Review> {
+        reviews::get_purchase(&env, &buyer, product_id)
+    }
+
+    /// The product's rating, aggregated from buyers' reviews.
+    pub fn get_rating(env: Env, product_id: u32) -> Rating {
+        reviews::get_rating(&env, product_id)
+    }
+
+    /// The order's payment while it's held in escrow.
+    pub fn get_escrow(env: Env, order_id: u32) -> Option<Escrow> {
+        disputes::get_escrow(&env, order_id)
+    }
+
+    /// Pays an undisputed escrow out once its dispute window has passed.
+    /// Anyone can call it. Fails with an `OrderError` or an `Error`.
+    pub fn release_escrow(
+        env: Env,
+        order_id: u32,
+    ) -> Result<(i128, i128, i128), soroban_sdk::Error> {
+        disputes::release(&env, order_id)
+    }
+
+    /// Disputes an escrowed order, with `claim` hashing the buyer's
+    /// statement.
+    pub fn open_dispute(env: Env, order_id: u32, claim: BytesN<32>) -> Result<(), OrderError> {
+        disputes::open(&env, order_id, claim)
+    }
+
+    /// The seller's (reserve account's) answer to a dispute.
+    pub fn respond_dispute(
+        env: Env,
+        order_id: u32,
+        response: BytesN<32>,
+    ) -> Result<(), OrderError> {
+        disputes::respond(&env, order_id, response)
+    }
+
+    /// The arbiter's ruling on a dispute. Returns the amount refunded to the
+    /// buyer. Fails with an `OrderError` or an `Error`.
+    pub fn resolve_dispute(
+        env: Env,
+        order_id: u32,
+        ruling: Ruling,
+    ) -> Result<i128, soroban_sdk::Error> {
+        disputes::resolve(&env, order_id, ruling)
+    }
+
+    pub fn get_dispute(env: Env, order_id: u32) -> Option<Dispute> {
+        disputes::get(&env, order_id)
+    }
+
+    pub fn set_coupon(env: Env, code: Symbol, coupon: Coupon) -> Result<(), Error> {
+        checkout::set_coupon(&env, code, coupon)
+    }
+
+    pub fn remove_coupon(env: Env, code: Symbol) {
+        checkout::remove_coupon(&env, code)
+    }
+
+    pub fn get_coupon(env: Env, code: Symbol) -> Option<Coupon> {
+        checkout::get_coupon(&env, &code)
     }
 
     pub fn get_reserve_acc(e: Env) -> Address {
@@ -255,7 +823,214 @@ impl Marketplace {
             .get::<DataKey, Address>(&DataKey::Admin)
             .expect("none")
     }
+
+    pub fn get_split(e: Env) -> Split {
+        e.storage()
+            .instance()
+            .get::<DataKey, Split>(&DataKey::Split)
+            .expect("none")
+    }
+
+    /// Replaces the reserve account, authorized by the admin and by
+    /// `threshold` of the signers in `approvers`. Unlike a
+    /// `SetReserveAccount` proposal this skips the timelock: a payout account
+    /// is usually moved because its key leaked, and waiting out the delay
+    /// would keep paying whoever holds it. Needing the admin on top of the
+    /// quorum is what makes up for the delay.
+    pub fn set_reserve_acc(
+        env: Env,
+        reserve_acc: Address,
+        approvers: Vec<Address>,
+    ) -> Result<(), Error> {
+        let admin = admin::require_admin(&env);
+        multisig::require_quorum(&env, &admin, &approvers)?;
+        admin::set_payout_acc(
+            &env,
+            DataKey::ReserveAccount,
+            symbol_short!("reserve"),
+            reserve_acc,
+        );
+        Ok(())
+    }
+
+    /// Like `set_reserve_acc`, for the dev account.
+    pub fn set_dev_acc(env: Env, dev_acc: Address, approvers: Vec<Address>) -> Result<(), Error> {
+        let admin = admin::require_admin(&env);
+        multisig::require_quorum(&env, &admin, &approvers)?;
+        admin::set_payout_acc(&env, DataKey::DevAccount, symbol_short!("dev"), dev_acc);
+        Ok(())
+    }
+
+    /// Like `set_reserve_acc`, for the launchpad account.
+    pub fn set_launchpad_acc(
+        env: Env,
+        launchpad_acc: Address,
+        approvers: Vec<Address>,
+    ) -> Result<(), Error> {
+        let admin = admin::require_admin(&env);
+        multisig::require_quorum(&env, &admin, &approvers)?;
+        admin::set_payout_acc(
+            &env,
+            DataKey::LaunchpadAccount,
+            symbol_short!("launchpad"),
+            launchpad_acc,
+        );
+        Ok(())
+    }
+
+    pub fn set_payout_mode(env: Env, mode: PayoutMode) {
+        admin::require_admin(&env);
+        env.storage().instance().set(&DataKey::PayoutMode, &mode);
+        events::payout_mode_changed(&env, mode);
+    }
+
+    pub fn get_payout_mode(env: Env) -> PayoutMode {
+        payout::get_mode(&env)
+    }
+
+    /// Stops (or resumes) purchases. Withdrawals of accrued funds and admin
+    /// operations keep working while paused.
+    pub fn set_paused(env: Env, paused: bool) {
+        admin::require_admin(&env);
+        env.storage().instance().set(&DataKey::Paused, &paused);
+        events::paused_changed(&env, paused);
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        admin::is_paused(&env)
+    }
+
+    /// Sets the length limits checked when products are created. Existing
+    /// products are left as they are.
+    pub fn set_product_limits(env: Env, limits: ProductLimits) -> Result<(), Error> {
+        admin::require_admin(&env);
+        catalog::set_limits(&env, limits)
+    }
+
+    pub fn get_product_limits(env: Env) -> ProductLimits {
+        catalog::get_limits(&env)
+    }
+
+    /// Sets the commission referrers earn on the purchases they refer.
+    pub fn set_referral_config(env: Env, config: ReferralConfig) -> Result<(), Error> {
+        admin::require_admin(&env);
+        referrals::set_config(&env, config)
+    }
+
+    pub fn set_loyalty_config(env: Env, config: LoyaltyConfig) -> Result<(), Error> {
+        admin::require_admin(&env);
+        loyalty::set_config(&env, config)
+    }
+
+    pub fn get_loyalty_config(env: Env) -> LoyaltyConfig {
+        loyalty::get_config(&env)
+    }
+
+    /// Sets how long checkout payments are held in escrow and how long
+    /// sellers have to answer disputes.
+    pub fn set_dispute_config(env: Env, config: DisputeConfig) -> Result<(), OrderError> {
+        admin::require_admin(&env);
+        disputes::set_config(&env, config)
+    }
+
+    pub fn get_dispute_config(env: Env) -> DisputeConfig {
+        disputes::get_config(&env)
+    }
+
+    /// Appoints the account that rules on disputes.
+    pub fn set_arbiter(env: Env, arbiter: Address) {
+        admin::require_admin(&env);
+        disputes::set_arbiter(&env, arbiter);
+    }
+
+    pub fn get_arbiter(env: Env) -> Option<Address> {
+        disputes::get_arbiter(&env)
+    }
+
+    pub fn get_referral_config(env: Env) -> ReferralConfig {
+        referrals::get_config(&env)
+    }
+
+    /// Total commission `referrer` has earned in `token_id`.
+    pub fn get_referral_earnings(env: Env, referrer: Address, token_id: Address) -> i128 {
+        referrals::get_earnings(&env, &referrer, &token_id)
+    }
+
+    pub fn withdraw(
+        env: Env,
+        recipient: Address,
+        token_id: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        payout::withdraw(&env, recipient, token_id, amount)
+    }
+
+    pub fn get_claimable(env: Env, recipient: Address, token_id: Address) -> i128 {
+        payout::get_claimable(&env, &recipient, &token_id)
+    }
+
+    /// Transfers the vested part of `recipient`'s vault in `token_id` that
+    /// hasn't been claimed yet, and returns the amount.
+    pub fn claim_vested(env: Env, recipient: Address, token_id: Address) -> Result<i128, Error> {
+        vesting::claim(&env, recipient, token_id)
+    }
+
+    /// Vested and not yet claimed.
+    pub fn get_vested(env: Env, recipient: Address, token_id: Address) -> i128 {
+        vesting::get_vested(&env, &recipient, &token_id)
+    }
+
+    /// Still locked in the vault.
+    pub fn get_unvested(env: Env, recipient: Address, token_id: Address) -> i128 {
+        vesting::get_unvested(&env, &recipient, &token_id)
+    }
+
+    pub fn get_vesting_schedule(env: Env, recipient: Address) -> Option<VestingSchedule> {
+        vesting::get_schedule(&env, &recipient)
+    }
+
+    pub fn propose(env: Env, proposer: Address, action: ProposalAction) -> Result<u32, Error> {
+        multisig::propose(&env, proposer, action)
+    }
+
+    pub fn approve(env: Env, signer: Address, proposal_id: u32) -> Result<u32, Error> {
+        multisig::approve(&env, signer, proposal_id)
+    }
+
+    pub fn execute(env: Env, proposal_id: u32) -> Result<(), Error> {
+        multisig::execute(&env, proposal_id)
+    }
+
+    pub fn get_proposal(env: Env, proposal_id: u32) -> Result<Proposal, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Proposal(proposal_id))
+            .ok_or(Error::ProposalNotExist)
+    }
+
+    pub fn get_signers(e: Env) -> Vec<Address> {
+        e.storage()
+            .instance()
+            .get::<DataKey, Vec<Address>>(&DataKey::Signers)
+            .expect("none")
+    }
+
+    pub fn get_threshold(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .get::<DataKey, u32>(&DataKey::Threshold)
+            .expect("none")
+    }
 }
 
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+
 #[cfg(test)]
 mod test;
+
+#[cfg(test)]
+mod prop_test;
+
+#[cfg(test)]
+mod budget_test;
@@ -0,0 +1,139 @@
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::diagnostics::diag;
+use crate::events::{self, PointsEvent};
+use crate::{payout, storage, DataKey, Error};
+
+/// How loyalty points are earned and spent.
+///
+/// Buyers earn `earn_rate` points per whole token paid for any purchase,
+/// and `burn_rate` points redeem for one whole token off a
+/// later purchase. A balance expires `ttl` seconds after the last time points
+/// were earned into it; a `ttl` of 0 keeps points forever.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoyaltyConfig {
+    pub earn_rate: u32,
+    pub burn_rate: u32,
+    pub ttl: u64,
+}
+
+impl LoyaltyConfig {
+    /// Earning is off until an admin sets a rate.
+    pub const DEFAULT: LoyaltyConfig = LoyaltyConfig {
+        earn_rate: 0,
+        burn_rate: 100,
+        ttl: 0,
+    };
+}
+
+/// A buyer's stored points; the balance is void once `expires_at` passes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Points {
+    pub balance: i128,
+    pub expires_at: u64,
+}
+
+pub(crate) fn get_config(env: &Env) -> LoyaltyConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::LoyaltyConfig)
+        .unwrap_or(LoyaltyConfig::DEFAULT)
+}
+
+/// Replaces the rates. Balances already earned keep their expiry.
+pub(crate) fn set_config(env: &Env, config: LoyaltyConfig) -> Result<(), Error> {
+    if config.burn_rate == 0 {
+        return Err(Error::InvalidLoyaltyConfig);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::LoyaltyConfig, &config);
+    events::loyalty_config_changed(env, config);
+    Ok(())
+}
+
+fn load(env: &Env, account: &Address) -> Points {
+    let points: Option<Points> = storage::get(env, &DataKey::Points(account.clone()));
+    match points {
+        Some(points) if points.expires_at >= env.ledger().timestamp() => points,
+        _ => Points {
+            balance: 0,
+            expires_at: 0,
+        },
+    }
+}
+
+fn store(env: &Env, account: Address, points: Points, change: i128) {
+    storage::set(env, &DataKey::Points(account.clone()), &points);
+    diag!(
+        env,
+        "loyalty.points",
+        account = account,
+        change = change,
+        balance = points.balance,
+    );
+    events::points_changed(
+        env,
+        PointsEvent {
+            account,
+            change,
+            balance: points.balance,
+            expires_at: points.expires_at,
+        },
+    );
+}
+
+/// The points `account` can redeem right now.
+pub(crate) fn get_points(env: &Env, account: &Address) -> i128 {
+    load(env, account).balance
+}
+
+/// Credits the points earned on a payment of `paid` stroops and pushes the
+/// balance's expiry out by the configured `ttl`. Returns the points earned.
+pub(crate) fn earn(env: &Env, account: Address, paid: i128) -> i128 {
+    let config = get_config(env);
+    let earned = paid * config.earn_rate as i128 / payout::UNIT;
+    if earned <= 0 {
+        return 0;
+    }
+    let points = load(env, &account);
+    let expires_at = match config.ttl {
+        0 => u64::MAX,
+        ttl => env.ledger().timestamp().saturating_add(ttl),
+    };
+    let points = Points {
+        balance: points.balance + earned,
+        expires_at,
+    };
+    store(env, account, points, earned);
+    earned
+}
+
+/// Takes back up to `points` of what `account` earned, e.g. when the
+/// purchase is refunded. Points already spent or expired are gone.
+pub(crate) fn forfeit(env: &Env, account: Address, points: i128) {
+    let mut balance = load(env, &account);
+    let taken = points.min(balance.balance);
+    if taken <= 0 {
+        return;
+    }
+    balance.balance -= taken;
+    store(env, account, balance, -taken);
+}
+
+/// Spends `points` of `account`'s balance and returns the discount they buy,
+/// in stroops.
+pub(crate) fn redeem(env: &Env, account: Address, points: i128) -> Result<i128, Error> {
+    if points <= 0 {
+        return Err(Error::AmountMustBeGreaterThanZero);
+    }
+    let mut balance = load(env, &account);
+    if points > balance.balance {
+        return Err(Error::InsufficientPoints);
+    }
+    balance.balance -= points;
+    store(env, account, balance, -points);
+    Ok(points * payout::UNIT / get_config(env).burn_rate as i128)
+}
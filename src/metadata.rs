@@ -0,0 +1,59 @@
+use soroban_sdk::{contracttype, Bytes, BytesN, Env, String};
+
+use crate::catalog::{self, ProductInput};
+use crate::{DataKey, Error, Marketplace, Product};
+
+/// Off-chain description of a product: `uri` points at a blob (on IPFS or
+/// similar) whose SHA-256 is `hash`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProductMetadata {
+    pub hash: BytesN<32>,
+    pub uri: String,
+}
+
+/// Creates a product whose description and image live off chain. The
+/// on-chain `description` and `image` are left empty and the URI is held to
+/// the `image` length limit.
+pub(crate) fn create(
+    env: &Env,
+    title: String,
+    category: String,
+    expiry: u64,
+    price: i128,
+    target: i128,
+    metadata: ProductMetadata,
+) -> Result<Product, Error> {
+    if metadata.uri.len() > catalog::get_limits(env).image {
+        return Err(Error::ImageTooLong);
+    }
+    let product = catalog::create(
+        env,
+        ProductInput {
+            title,
+            description: String::from_str(env, ""),
+            category,
+            expiry,
+            image: String::from_str(env, ""),
+            price,
+            target,
+        },
+    )?;
+    env.storage()
+        .instance()
+        .set(&DataKey::Metadata(product.id), &metadata);
+    Ok(product)
+}
+
+pub(crate) fn get(env: &Env, product_id: u32) -> Option<ProductMetadata> {
+    env.storage().instance().get(&DataKey::Metadata(product_id))
+}
+
+/// Whether `bytes` are the content the product's metadata hash commits to.
+pub(crate) fn verify(env: &Env, product_id: u32, bytes: Bytes) -> Result<bool, Error> {
+    if product_id == 0 || Marketplace::get_product(env.clone(), product_id).id != product_id {
+        return Err(Error::ProductNotExist);
+    }
+    let metadata = get(env, product_id).ok_or(Error::NoMetadata)?;
+    Ok(env.crypto().sha256(&bytes) == metadata.hash)
+}
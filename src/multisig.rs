@@ -0,0 +1,233 @@
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, Vec};
+
+use crate::events::{self, ProposalEvent, SplitChangedEvent};
+use crate::vesting::{self, VestingSchedule};
+use crate::{admin, DataKey, Error, Marketplace, Split};
+
+/// Delay between a proposal being created and it becoming executable.
+pub const PROPOSAL_TIMELOCK: u64 = 24 * 60 * 60;
+
+/// Sensitive operations that can only be carried out through a proposal
+/// approved by `threshold` of the configured signers.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProposalAction {
+    SetReserveAccount(Address),
+    SetDevAccount(Address),
+    SetLaunchpadAccount(Address),
+    SetSplit(Split),
+    Upgrade(BytesN<32>),
+    /// Routes the recipient's future payout shares into the vesting vault.
+    SetVesting(Address, VestingSchedule),
+    /// Pays the recipient directly again and unlocks their vault.
+    RemoveVesting(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    pub id: u32,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub approvals: Vec<Address>,
+    pub eta: u64,
+    pub executed: bool,
+}
+
+pub(crate) fn check_signers(signers: &Vec<Address>, threshold: u32) -> Result<(), Error> {
+    if threshold == 0 || threshold > signers.len() {
+        return Err(Error::InvalidThreshold);
+    }
+    for (i, signer) in signers.iter().enumerate() {
+        if signers.first_index_of(&signer) != Some(i as u32) {
+            return Err(Error::InvalidThreshold);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn check_split(split: &Split) -> Result<(), Error> {
+    if split.reserve <= 0
+        || split.launchpad <= 0
+        || split.dev <= 0
+        || split.reserve + split.launchpad + split.dev != 100
+    {
+        return Err(Error::InvalidSplit);
+    }
+    Ok(())
+}
+
+fn require_signer(env: &Env, signer: &Address) -> Result<(), Error> {
+    signer.require_auth();
+    let signers = Marketplace::get_signers(env.clone());
+    if !signers.contains(signer) {
+        return Err(Error::NotSigner);
+    }
+    Ok(())
+}
+
+/// Requires every one of `approvers` to be a distinct signer authorizing
+/// the call, and at least `threshold` of them. `authorized` has already
+/// authorized the call, so it counts without authorizing twice.
+pub(crate) fn require_quorum(
+    env: &Env,
+    authorized: &Address,
+    approvers: &Vec<Address>,
+) -> Result<(), Error> {
+    if approvers.len() < Marketplace::get_threshold(env.clone()) {
+        return Err(Error::ThresholdNotReached);
+    }
+    let signers = Marketplace::get_signers(env.clone());
+    for (i, approver) in approvers.iter().enumerate() {
+        if approvers.first_index_of(&approver) != Some(i as u32) {
+            return Err(Error::AlreadyApproved);
+        }
+        if approver != *authorized {
+            approver.require_auth();
+        }
+        if !signers.contains(&approver) {
+            return Err(Error::NotSigner);
+        }
+    }
+    Ok(())
+}
+
+fn apply(env: &Env, action: &ProposalAction) {
+    match action {
+        ProposalAction::SetReserveAccount(acc) => admin::set_payout_acc(
+            env,
+            DataKey::ReserveAccount,
+            symbol_short!("reserve"),
+            acc.clone(),
+        ),
+        ProposalAction::SetDevAccount(acc) => {
+            admin::set_payout_acc(env, DataKey::DevAccount, symbol_short!("dev"), acc.clone())
+        }
+        ProposalAction::SetLaunchpadAccount(acc) => admin::set_payout_acc(
+            env,
+            DataKey::LaunchpadAccount,
+            symbol_short!("launchpad"),
+            acc.clone(),
+        ),
+        ProposalAction::SetSplit(split) => {
+            let old = Marketplace::get_split(env.clone());
+            env.storage().instance().set(&DataKey::Split, split);
+            events::split_changed(
+                env,
+                SplitChangedEvent {
+                    old,
+                    new: split.clone(),
+                },
+            );
+        }
+        ProposalAction::Upgrade(wasm_hash) => {
+            events::upgraded(env, wasm_hash.clone());
+            env.deployer()
+                .update_current_contract_wasm(wasm_hash.clone());
+        }
+        ProposalAction::SetVesting(recipient, schedule) => {
+            vesting::set_schedule(env, recipient.clone(), schedule.clone())
+        }
+        ProposalAction::RemoveVesting(recipient) => {
+            vesting::remove_schedule(env, recipient.clone())
+        }
+    }
+}
+
+pub(crate) fn propose(env: &Env, proposer: Address, action: ProposalAction) -> Result<u32, Error> {
+    require_signer(env, &proposer)?;
+    match &action {
+        ProposalAction::SetSplit(split) => check_split(split)?,
+        ProposalAction::SetVesting(_, schedule) => vesting::check_schedule(schedule)?,
+        _ => {}
+    }
+
+    let mut proposal_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NoOfProposals)
+        .unwrap_or(0);
+    proposal_id += 1;
+
+    // The proposer implicitly approves their own proposal.
+    let mut approvals = Vec::new(env);
+    approvals.push_back(proposer.clone());
+
+    let proposal = Proposal {
+        id: proposal_id,
+        proposer,
+        action,
+        approvals,
+        eta: env.ledger().timestamp() + PROPOSAL_TIMELOCK,
+        executed: false,
+    };
+
+    env.storage()
+        .instance()
+        .set(&DataKey::NoOfProposals, &proposal_id);
+    env.storage()
+        .instance()
+        .set(&DataKey::Proposal(proposal_id), &proposal);
+
+    events::proposal_created(
+        env,
+        ProposalEvent {
+            proposal_id,
+            signer: proposal.proposer,
+        },
+    );
+
+    Ok(proposal_id)
+}
+
+pub(crate) fn approve(env: &Env, signer: Address, proposal_id: u32) -> Result<u32, Error> {
+    require_signer(env, &signer)?;
+
+    let mut proposal = Marketplace::get_proposal(env.clone(), proposal_id)?;
+    if proposal.executed {
+        return Err(Error::ProposalAlreadyExecuted);
+    }
+    if proposal.approvals.contains(&signer) {
+        return Err(Error::AlreadyApproved);
+    }
+
+    proposal.approvals.push_back(signer.clone());
+    env.storage()
+        .instance()
+        .set(&DataKey::Proposal(proposal_id), &proposal);
+
+    events::proposal_approved(
+        env,
+        ProposalEvent {
+            proposal_id,
+            signer,
+        },
+    );
+
+    Ok(proposal.approvals.len())
+}
+
+pub(crate) fn execute(env: &Env, proposal_id: u32) -> Result<(), Error> {
+    let mut proposal = Marketplace::get_proposal(env.clone(), proposal_id)?;
+    if proposal.executed {
+        return Err(Error::ProposalAlreadyExecuted);
+    }
+    if proposal.approvals.len() < Marketplace::get_threshold(env.clone()) {
+        return Err(Error::ThresholdNotReached);
+    }
+    if env.ledger().timestamp() < proposal.eta {
+        return Err(Error::TimelockNotElapsed);
+    }
+
+    // Mark as executed before applying so an upgrade cannot replay it.
+    proposal.executed = true;
+    env.storage()
+        .instance()
+        .set(&DataKey::Proposal(proposal_id), &proposal);
+
+    apply(env, &proposal.action);
+
+    events::proposal_executed(env, proposal);
+
+    Ok(())
+}
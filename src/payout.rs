@@ -0,0 +1,229 @@
+use soroban_sdk::{contracttype, token, Address, Env};
+
+use crate::diagnostics::diag;
+use crate::events::{self, WithdrawEvent};
+use crate::{referrals, storage, vesting, DataKey, Error, Marketplace};
+
+/// How purchase proceeds reach the payout accounts.
+///
+/// `Push` transfers each share straight from the buyer to its recipient.
+/// `Accrue` moves the whole payment into the contract once and credits each
+/// share to an internal balance that the recipient later pulls with
+/// `withdraw`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PayoutMode {
+    Push,
+    Accrue,
+}
+
+pub(crate) fn get_mode(env: &Env) -> PayoutMode {
+    env.storage()
+        .instance()
+        .get(&DataKey::PayoutMode)
+        .unwrap_or(PayoutMode::Push)
+}
+
+pub(crate) fn get_claimable(env: &Env, recipient: &Address, token_id: &Address) -> i128 {
+    storage::get(
+        env,
+        &DataKey::Claimable(recipient.clone(), token_id.clone()),
+    )
+    .unwrap_or(0)
+}
+
+fn set_claimable(env: &Env, recipient: &Address, token_id: &Address, amount: i128) {
+    storage::set(
+        env,
+        &DataKey::Claimable(recipient.clone(), token_id.clone()),
+        &amount,
+    );
+}
+
+/// Pays every `(recipient, amount)` share from `from` according to the
+/// configured `PayoutMode`. Shares of recipients with a vesting schedule go
+/// into the contract's vesting vault in either mode. `from` can be the
+/// contract itself, for payments it holds in escrow.
+pub(crate) fn pay<const N: usize>(
+    env: &Env,
+    token_id: &Address,
+    from: &Address,
+    shares: [(Address, i128); N],
+) {
+    let client = token::Client::new(env, token_id);
+    let held = *from == env.current_contract_address();
+
+    match get_mode(env) {
+        PayoutMode::Push => {
+            for (recipient, amount) in shares.iter() {
+                if vesting::get_schedule(env, recipient).is_some() {
+                    if !held {
+                        client.transfer(from, &env.current_contract_address(), amount);
+                    }
+                    vesting::deposit(env, recipient, token_id, *amount);
+                    continue;
+                }
+                client.transfer(from, recipient, amount);
+                diag!(env, "payout.push", recipient = *recipient, amount = *amount);
+            }
+        }
+        PayoutMode::Accrue => {
+            if !held {
+                let total: i128 = shares.iter().map(|(_, amount)| amount).sum();
+                client.transfer(from, &env.current_contract_address(), &total);
+                diag!(env, "payout.escrow", from = *from, total = total);
+            }
+            for (recipient, amount) in shares.iter() {
+                if vesting::get_schedule(env, recipient).is_some() {
+                    vesting::deposit(env, recipient, token_id, *amount);
+                    continue;
+                }
+                let claimable = get_claimable(env, recipient, token_id);
+                set_claimable(env, recipient, token_id, claimable + amount);
+                diag!(
+                    env,
+                    "payout.accrue",
+                    recipient = *recipient,
+                    amount = *amount,
+                    claimable = claimable + amount,
+                );
+            }
+        }
+    }
+}
+
+/// Stroops per whole token; prices and purchase amounts are in whole tokens.
+pub(crate) const UNIT: i128 = 10000000;
+
+/// Basis points in 100%.
+pub(crate) const BPS: i128 = 10000;
+
+/// Splits a payment of `real_amount` stroops between the payout accounts by
+/// the configured `Split` and pays each share from `customer`, one transfer
+/// (or credit) per account. A `referrer` is paid its commission out of the
+/// configured share. Returns the reserve, launchpad and dev amounts, net of
+/// any commission.
+pub(crate) fn split(
+    env: &Env,
+    token_id: &Address,
+    customer: &Address,
+    real_amount: i128,
+    referrer: Option<Address>,
+) -> Result<(i128, i128, i128), Error> {
+    split_from(env, token_id, customer, customer, real_amount, referrer)
+}
+
+/// Like `split`, but pays the shares from `payer` rather than the customer
+/// the purchase is credited to.
+pub(crate) fn split_from(
+    env: &Env,
+    token_id: &Address,
+    payer: &Address,
+    customer: &Address,
+    real_amount: i128,
+    referrer: Option<Address>,
+) -> Result<(i128, i128, i128), Error> {
+    let split = Marketplace::get_split(env.clone());
+    let total_percentage = split.reserve + split.launchpad + split.dev;
+    // Payment splitting and calculating the percentage
+    let mut reserve_amount = (real_amount * split.reserve) / total_percentage;
+    let mut launchpad_amount = (real_amount * split.launchpad) / total_percentage;
+    let dev_amount = (real_amount * split.dev) / total_percentage;
+
+    if reserve_amount == 0 || launchpad_amount == 0 || dev_amount == 0 {
+        return Err(Error::LowAmountForSplitter);
+    }
+    let commission = match &referrer {
+        Some(referrer) => referrals::carve(
+            env,
+            referrer,
+            customer,
+            &mut reserve_amount,
+            &mut launchpad_amount,
+        )?,
+        None => 0,
+    };
+    diag!(
+        env,
+        "purchase.split",
+        reserve = reserve_amount,
+        launchpad = launchpad_amount,
+        dev = dev_amount,
+    );
+
+    // get accounts
+    let reserve_acc = Marketplace::get_reserve_acc(env.clone());
+    let dev_acc = Marketplace::get_dev_acc(env.clone());
+    let launchpad_acc = Marketplace::get_launchpad_acc(env.clone());
+
+    // transfer (or credit) splitted Tokens to artist, dev, launchpad accounts
+    let shares = [
+        (reserve_acc, reserve_amount),
+        (dev_acc, dev_amount),
+        (launchpad_acc, launchpad_amount),
+    ];
+    match referrer {
+        Some(referrer) if commission > 0 => {
+            let [reserve, dev, launchpad] = shares;
+            pay(
+                env,
+                token_id,
+                payer,
+                [reserve, dev, launchpad, (referrer.clone(), commission)],
+            );
+            referrals::record(
+                env,
+                referrer,
+                customer.clone(),
+                token_id.clone(),
+                commission,
+            );
+        }
+        _ => pay(env, token_id, payer, shares),
+    }
+    Ok((reserve_amount, launchpad_amount, dev_amount))
+}
+
+pub(crate) fn withdraw(
+    env: &Env,
+    recipient: Address,
+    token_id: Address,
+    amount: i128,
+) -> Result<i128, Error> {
+    recipient.require_auth();
+
+    if amount <= 0 {
+        return Err(Error::AmountMustBeGreaterThanZero);
+    }
+    let claimable = get_claimable(env, &recipient, &token_id);
+    if amount > claimable {
+        return Err(Error::InsufficientClaimable);
+    }
+
+    let remaining = claimable - amount;
+    set_claimable(env, &recipient, &token_id, remaining);
+    diag!(
+        env,
+        "payout.withdraw",
+        recipient = recipient,
+        amount = amount,
+        remaining = remaining,
+    );
+    token::Client::new(env, &token_id).transfer(
+        &env.current_contract_address(),
+        &recipient,
+        &amount,
+    );
+
+    events::withdraw(
+        env,
+        WithdrawEvent {
+            recipient,
+            token: token_id,
+            amount,
+            remaining,
+        },
+    );
+
+    Ok(remaining)
+}
@@ -0,0 +1,190 @@
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+use crate::diagnostics::diag;
+use crate::events::{self, AllowlistEvent, SalePhasesEvent};
+use crate::{admin, storage, DataKey, Error, Marketplace};
+
+/// Most phases a single product can have.
+pub const MAX_PHASES: u32 = 10;
+
+/// Who may buy during a phase.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PhaseAccess {
+    Public,
+    /// Buyers added to the product's stored allowlist.
+    Allowlist,
+    /// Buyers proving membership of a Merkle tree with this root. Leaves are
+    /// the SHA-256 of the buyer's address as `ScVal` XDR, and each parent is
+    /// the SHA-256 of its two children in ascending order.
+    Merkle(BytesN<32>),
+}
+
+/// A window `[start, end)` in which the product sells to `access`, capped
+/// at `cap` units across all buyers. A `cap` of 0 leaves the phase
+/// uncapped.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SalePhase {
+    pub access: PhaseAccess,
+    pub start: u64,
+    pub end: u64,
+    pub cap: i128,
+}
+
+pub(crate) fn get(env: &Env, product_id: u32) -> Vec<SalePhase> {
+    env.storage()
+        .instance()
+        .get(&DataKey::SalePhases(product_id))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Units sold so far in phase `index` of a product.
+pub(crate) fn get_sold(env: &Env, product_id: u32, index: u32) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::PhaseSold(product_id, index))
+        .unwrap_or(0)
+}
+
+/// Replaces a product's phases, which must be in order and not overlap.
+/// Sales counted against the old phases are forgotten; no phases at all
+/// means the product is on open sale.
+pub(crate) fn set(env: &Env, product_id: u32, phases: Vec<SalePhase>) -> Result<(), Error> {
+    admin::require_admin(env);
+    if product_id == 0 || Marketplace::get_product(env.clone(), product_id).id != product_id {
+        return Err(Error::ProductNotExist);
+    }
+    if phases.len() > MAX_PHASES {
+        return Err(Error::InvalidSchedule);
+    }
+    let mut previous_end = 0;
+    for phase in phases.iter() {
+        if phase.start >= phase.end || phase.start < previous_end || phase.cap < 0 {
+            return Err(Error::InvalidSchedule);
+        }
+        previous_end = phase.end;
+    }
+
+    for index in 0..get(env, product_id).len() {
+        env.storage()
+            .instance()
+            .remove(&DataKey::PhaseSold(product_id, index));
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::SalePhases(product_id), &phases);
+    events::sale_phases_changed(env, SalePhasesEvent { product_id, phases });
+    Ok(())
+}
+
+pub(crate) fn is_allowlisted(env: &Env, product_id: u32, account: &Address) -> bool {
+    storage::get(env, &DataKey::Allowlist(product_id, account.clone())).unwrap_or(false)
+}
+
+/// Adds `accounts` to a product's stored allowlist, or removes them.
+pub(crate) fn set_allowlist(
+    env: &Env,
+    product_id: u32,
+    accounts: Vec<Address>,
+    allowed: bool,
+) -> Result<(), Error> {
+    admin::require_admin(env);
+    if product_id == 0 || Marketplace::get_product(env.clone(), product_id).id != product_id {
+        return Err(Error::ProductNotExist);
+    }
+    for account in accounts.iter() {
+        let key = DataKey::Allowlist(product_id, account);
+        if allowed {
+            storage::set(env, &key, &true);
+        } else {
+            storage::remove(env, &key);
+        }
+    }
+    events::allowlist_changed(
+        env,
+        AllowlistEvent {
+            product_id,
+            accounts,
+            allowed,
+        },
+    );
+    Ok(())
+}
+
+fn verify_proof(env: &Env, root: &BytesN<32>, account: &Address, proof: &Vec<BytesN<32>>) -> bool {
+    let mut node = env.crypto().sha256(&account.clone().to_xdr(env));
+    for sibling in proof.iter() {
+        let mut pair = Bytes::new(env);
+        if node <= sibling {
+            pair.append(&node.into());
+            pair.append(&sibling.into());
+        } else {
+            pair.append(&sibling.into());
+            pair.append(&node.into());
+        }
+        node = env.crypto().sha256(&pair);
+    }
+    node == *root
+}
+
+/// Checks that `buyer` may buy `quantity` units of a product now and counts
+/// them against the current phase, whose index and start are returned.
+/// Products without phases always pass.
+pub(crate) fn admit(
+    env: &Env,
+    product_id: u32,
+    buyer: &Address,
+    quantity: i128,
+    proof: &Vec<BytesN<32>>,
+) -> Result<Option<(u32, u64)>, Error> {
+    let now = env.ledger().timestamp();
+    let phases = get(env, product_id);
+    if phases.is_empty() {
+        return Ok(None);
+    }
+    let (index, phase) = phases
+        .iter()
+        .enumerate()
+        .find(|(_, phase)| phase.start <= now && now < phase.end)
+        .ok_or(Error::SaleNotActive)?;
+    let index = index as u32;
+
+    let admitted = match &phase.access {
+        PhaseAccess::Public => true,
+        PhaseAccess::Allowlist => is_allowlisted(env, product_id, buyer),
+        PhaseAccess::Merkle(root) => verify_proof(env, root, buyer, proof),
+    };
+    if !admitted {
+        return Err(Error::NotAllowlisted);
+    }
+
+    let sold = get_sold(env, product_id, index) + quantity;
+    if phase.cap > 0 && sold > phase.cap {
+        return Err(Error::TargetReached);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::PhaseSold(product_id, index), &sold);
+    diag!(
+        env,
+        "purchase.phase",
+        product = product_id,
+        phase = index,
+        sold = sold,
+    );
+    Ok(Some((index, phase.start)))
+}
+
+/// Uncounts `quantity` units sold in the phase `admit` returned, unless the
+/// product's phases were replaced since, which forgot the count anyway.
+pub(crate) fn release(env: &Env, product_id: u32, (index, start): (u32, u64), quantity: i128) {
+    match get(env, product_id).get(index) {
+        Some(phase) if phase.start == start => {}
+        _ => return,
+    }
+    let sold = (get_sold(env, product_id, index) - quantity).max(0);
+    env.storage()
+        .instance()
+        .set(&DataKey::PhaseSold(product_id, index), &sold);
+}
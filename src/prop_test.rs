@@ -0,0 +1,242 @@
+#![cfg(test)]
+
+//! Property tests for the payment splitter and the product lifecycle.
+//!
+//! Everything runs against the in-memory test host, so the suite needs no
+//! network. Case counts are kept low because every case spins up a fresh
+//! `Env`.
+
+use super::*;
+use crate::testutils;
+use proptest::prelude::*;
+use soroban_sdk::testutils::Address as _;
+
+extern crate std;
+
+const FUNDS: i128 = 1_000_000_000_000_000_000_000_000_000_000;
+
+fn config() -> ProptestConfig {
+    ProptestConfig {
+        cases: 32,
+        ..ProptestConfig::default()
+    }
+}
+
+fn split_strategy() -> impl Strategy<Value = Split> {
+    (1i128..=98)
+        .prop_flat_map(|reserve| (Just(reserve), 1i128..=(99 - reserve)))
+        .prop_map(|(reserve, launchpad)| Split {
+            reserve,
+            launchpad,
+            dev: 100 - reserve - launchpad,
+        })
+}
+
+fn set_split(market: &testutils::Marketplace, split: &Split) {
+    let client = market.client();
+    let proposal_id = client.propose(&market.admin, &ProposalAction::SetSplit(split.clone()));
+    market.advance_time(multisig::PROPOSAL_TIMELOCK);
+    client.execute(&proposal_id);
+}
+
+/// Addresses whose authorization the last top-level call required.
+fn authorizers(env: &Env) -> std::vec::Vec<Address> {
+    env.auths()
+        .into_iter()
+        .map(|(address, _)| address)
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Create { target: i128 },
+    Buy { product: u32, amount: i128 },
+    Withdraw { recipient: usize, amount: i128 },
+    SetReserve,
+    SetMode(bool),
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        1 => (1i128..4).prop_map(|target| Op::Create { target }),
+        4 => (0u32..6, -2i128..50).prop_map(|(product, amount)| Op::Buy { product, amount }),
+        2 => (0usize..8, -1i128..500_000_000).prop_map(|(recipient, amount)| Op::Withdraw {
+            recipient,
+            amount
+        }),
+        1 => Just(Op::SetReserve),
+        1 => any::<bool>().prop_map(Op::SetMode),
+    ]
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    /// Whatever leaves the buyer arrives at the payout accounts (or the
+    /// contract, when accruing), and the returned shares match the split.
+    #[test]
+    fn splitter_conserves_funds(
+        amount in 1i128..1_000_000_000_000,
+        split in split_strategy(),
+        accrue in any::<bool>(),
+    ) {
+        let env = Env::default();
+        let market = testutils::Marketplace::setup(&env);
+        let client = market.client();
+        let token = market.token();
+        set_split(&market, &split);
+        if accrue {
+            client.set_payout_mode(&PayoutMode::Accrue);
+        }
+        let product = market.product().create();
+        let buyer = market.funded_buyer(FUNDS);
+
+        let result = client.try_get_discount(&product.id, &buyer, &amount, &market.token_id, &None);
+        let (reserve, launchpad, dev) = match result {
+            Ok(Ok(shares)) => shares,
+            Err(Ok(Error::LowAmountForSplitter)) => return Ok(()),
+            other => panic!("unexpected result {other:?}"),
+        };
+
+        let real_amount = amount * 10000000;
+        prop_assert_eq!(reserve, real_amount * split.reserve / 100);
+        prop_assert_eq!(launchpad, real_amount * split.launchpad / 100);
+        prop_assert_eq!(dev, real_amount * split.dev / 100);
+        prop_assert!(reserve + launchpad + dev <= real_amount);
+
+        let paid = FUNDS - token.balance(&buyer);
+        prop_assert_eq!(paid, reserve + launchpad + dev);
+        let received = token.balance(&market.reserve_acc)
+            + token.balance(&market.launchpad_acc)
+            + token.balance(&market.dev_acc)
+            + token.balance(&market.contract_id);
+        prop_assert_eq!(received, paid);
+
+        if accrue {
+            prop_assert_eq!(token.balance(&market.contract_id), paid);
+            prop_assert_eq!(client.get_claimable(&market.reserve_acc, &market.token_id), reserve);
+            prop_assert_eq!(client.get_claimable(&market.launchpad_acc, &market.token_id), launchpad);
+            prop_assert_eq!(client.get_claimable(&market.dev_acc, &market.token_id), dev);
+        }
+    }
+
+    /// Non-positive amounts are always rejected before anything is touched.
+    #[test]
+    fn non_positive_amounts_are_rejected(amount in i128::MIN..=0) {
+        let env = Env::default();
+        let market = testutils::Marketplace::setup(&env);
+        let client = market.client();
+        let product = market.product().create();
+        let buyer = market.funded_buyer(FUNDS);
+
+        prop_assert_eq!(
+            client.try_get_discount(&product.id, &buyer, &amount, &market.token_id, &None),
+            Err(Ok(Error::AmountMustBeGreaterThanZero))
+        );
+        prop_assert_eq!(client.get_product(&product.id).remaining, product.remaining);
+    }
+
+    /// Ids come from `NO_OF_PRODUCTS`: consecutive, unique and listed in order.
+    #[test]
+    fn product_ids_are_unique(count in 1u32..20) {
+        let env = Env::default();
+        let market = testutils::Marketplace::setup(&env);
+
+        for expected in 1..=count {
+            prop_assert_eq!(market.product().create().id, expected);
+        }
+
+        let products = market.client().get_products();
+        prop_assert_eq!(products.len(), count);
+        for (i, product) in products.iter().enumerate() {
+            prop_assert_eq!(product.id, i as u32 + 1);
+        }
+    }
+
+    /// Random call sequences never increase `remaining`, never drive it
+    /// below zero, never lose funds, and every mutating call is authorized by
+    /// the expected address.
+    #[test]
+    fn random_call_sequences_hold_invariants(
+        ops in proptest::collection::vec(op_strategy(), 1..25),
+    ) {
+        let env = Env::default();
+        let market = testutils::Marketplace::setup(&env);
+        let client = market.client();
+        let token = market.token();
+        let buyer = market.funded_buyer(FUNDS);
+        let mut recipients = std::vec![
+            market.reserve_acc.clone(),
+            market.launchpad_acc.clone(),
+            market.dev_acc.clone(),
+        ];
+        let mut remaining: std::vec::Vec<i128> = std::vec::Vec::new();
+
+        for op in ops {
+            match op {
+                Op::Create { target } => {
+                    let product = market.product().target(target).create();
+                    prop_assert_eq!(product.id as usize, remaining.len() + 1);
+                    remaining.push(target);
+                }
+                Op::Buy { product, amount } => {
+                    let result = client.try_get_discount(&product, &buyer, &amount, &market.token_id, &None);
+                    let exists = product >= 1 && product as usize <= remaining.len();
+                    match result {
+                        Ok(Ok(_)) => {
+                            prop_assert_eq!(authorizers(&env), std::vec![buyer.clone()]);
+                            prop_assert!(exists && amount > 0);
+                            let before = remaining[product as usize - 1];
+                            prop_assert!(before > 0);
+                            remaining[product as usize - 1] = before - 1;
+                        }
+                        Err(Ok(Error::AmountMustBeGreaterThanZero)) => prop_assert!(amount <= 0),
+                        Err(Ok(Error::ProductNotExist)) => prop_assert!(!exists),
+                        Err(Ok(Error::TargetReached)) => {
+                            prop_assert!(remaining[product as usize - 1] <= 0)
+                        }
+                        other => panic!("unexpected result {other:?}"),
+                    }
+                }
+                Op::Withdraw { recipient, amount } => {
+                    let recipient = recipients[recipient % recipients.len()].clone();
+                    let claimable = client.get_claimable(&recipient, &market.token_id);
+                    let result = client.try_withdraw(&recipient, &market.token_id, &amount);
+                    match result {
+                        Ok(Ok(left)) => {
+                            prop_assert_eq!(authorizers(&env), std::vec![recipient.clone()]);
+                            prop_assert!(amount > 0 && amount <= claimable);
+                            prop_assert_eq!(left, claimable - amount);
+                        }
+                        Err(Ok(Error::AmountMustBeGreaterThanZero)) => prop_assert!(amount <= 0),
+                        Err(Ok(Error::InsufficientClaimable)) => prop_assert!(amount > claimable),
+                        other => panic!("unexpected result {other:?}"),
+                    }
+                }
+                Op::SetReserve => {
+                    let new_reserve = Address::generate(&env);
+                    client.set_reserve_acc(&new_reserve, &soroban_sdk::vec![&env, market.admin.clone()]);
+                    prop_assert_eq!(authorizers(&env), std::vec![market.admin.clone()]);
+                    recipients.push(new_reserve);
+                }
+                Op::SetMode(accrue) => {
+                    let mode = if accrue { PayoutMode::Accrue } else { PayoutMode::Push };
+                    client.set_payout_mode(&mode);
+                    prop_assert_eq!(authorizers(&env), std::vec![market.admin.clone()]);
+                }
+            }
+
+            for (i, expected) in remaining.iter().enumerate() {
+                prop_assert_eq!(client.get_product(&(i as u32 + 1)).remaining, *expected);
+            }
+
+            let held: i128 = recipients.iter().map(|r| token.balance(r)).sum();
+            let owed: i128 = recipients
+                .iter()
+                .map(|r| client.get_claimable(r, &market.token_id))
+                .sum();
+            prop_assert_eq!(token.balance(&market.contract_id), owed);
+            prop_assert_eq!(token.balance(&buyer) + held + owed, FUNDS);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+use crate::diagnostics::diag;
+use crate::events::{self, PurchaseEvent};
+use crate::{
+    admin, loyalty, payout, phases, receipts, reviews, variants, vouchers, Error, Marketplace,
+};
+
+/// Buys one unit of product `id` for `amount` whole tokens, less whatever
+/// the redeemed `points` are worth. The buyer earns loyalty points on what
+/// is actually paid, plus a receipt and a voucher if the product issues
+/// them. `proof` admits the buyer to a Merkle-gated sale phase.
+pub(crate) fn buy(
+    env: &Env,
+    id: u32,
+    customer_address: Address,
+    amount: i128,
+    token_id: Address,
+    referrer: Option<Address>,
+    points: i128,
+    proof: &Vec<BytesN<32>>,
+) -> Result<(i128, i128, i128), Error> {
+    customer_address.require_auth();
+
+    if admin::is_paused(env) {
+        return Err(Error::Paused);
+    }
+    if amount <= 0 {
+        return Err(Error::AmountMustBeGreaterThanZero);
+    }
+
+    diag!(
+        env,
+        "purchase.start",
+        product = id,
+        buyer = customer_address,
+        amount = amount,
+    );
+    let mut check_product = Marketplace::get_product(env.clone(), id);
+
+    if check_product.id != id || id == 0 {
+        return Err(Error::ProductNotExist);
+    }
+    if check_product.remaining <= 0 {
+        return Err(Error::TargetReached);
+    }
+    if variants::required(env, id) {
+        return Err(Error::VariantRequired);
+    }
+    phases::admit(env, id, &customer_address, 1, proof)?;
+
+    let mut real_amount = amount * payout::UNIT;
+    if points != 0 {
+        real_amount -= loyalty::redeem(env, customer_address.clone(), points)?;
+        if real_amount <= 0 {
+            return Err(Error::LowAmountForSplitter);
+        }
+    }
+    let (reserve_amount, launchpad_amount, dev_amount) =
+        payout::split(env, &token_id, &customer_address, real_amount, referrer)?;
+    loyalty::earn(env, customer_address.clone(), real_amount);
+    // Save data
+    check_product.remaining -= 1;
+    env.storage()
+        .instance()
+        .set(&check_product.id, &check_product);
+    diag!(
+        env,
+        "purchase.stock",
+        product = check_product.id,
+        remaining = check_product.remaining,
+    );
+
+    events::purchase(
+        env,
+        PurchaseEvent {
+            buyer: customer_address.clone(),
+            product_id: check_product.id,
+            token: token_id.clone(),
+            quantity: 1,
+            reserve_amount,
+            launchpad_amount,
+            dev_amount,
+        },
+    );
+    events::product_updated(env, &check_product);
+    receipts::mint(env, &customer_address, check_product.id, 0);
+    reviews::record_purchase(env, &customer_address, check_product.id);
+    vouchers::issue(
+        env,
+        &customer_address,
+        check_product.id,
+        0,
+        &token_id,
+        real_amount,
+        1,
+    );
+
+    Ok((reserve_amount, launchpad_amount, dev_amount))
+}
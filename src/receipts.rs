@@ -0,0 +1,164 @@
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::diagnostics::diag;
+use crate::events::{self, ReceiptApprovalEvent, ReceiptModeEvent, ReceiptTransferEvent};
+use crate::{admin, storage, DataKey, Error, Marketplace};
+
+/// Whether buying a product mints a receipt, and whether that receipt can
+/// change hands afterwards.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReceiptMode {
+    Off,
+    NonTransferable,
+    Transferable,
+}
+
+/// A non-fungible proof of purchase. `order_id` is the checkout order the
+/// unit was bought in, or 0 for a purchase made outside checkout.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Receipt {
+    pub id: u32,
+    pub owner: Address,
+    pub product_id: u32,
+    pub order_id: u32,
+}
+
+pub(crate) fn get_mode(env: &Env, product_id: u32) -> ReceiptMode {
+    env.storage()
+        .instance()
+        .get(&DataKey::ReceiptMode(product_id))
+        .unwrap_or(ReceiptMode::Off)
+}
+
+/// Turns receipts on or off for a product. The transferability of receipts
+/// already minted follows the product's current mode.
+pub(crate) fn set_mode(env: &Env, product_id: u32, mode: ReceiptMode) -> Result<(), Error> {
+    admin::require_admin(env);
+    if product_id == 0 || Marketplace::get_product(env.clone(), product_id).id != product_id {
+        return Err(Error::ProductNotExist);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::ReceiptMode(product_id), &mode);
+    events::receipt_mode_changed(env, ReceiptModeEvent { product_id, mode });
+    Ok(())
+}
+
+pub(crate) fn get(env: &Env, receipt_id: u32) -> Result<Receipt, Error> {
+    storage::get(env, &DataKey::Receipt(receipt_id)).ok_or(Error::ReceiptNotExist)
+}
+
+pub(crate) fn get_approval(env: &Env, receipt_id: u32) -> Option<Address> {
+    storage::get(env, &DataKey::ReceiptApproval(receipt_id))
+}
+
+/// Id the next receipt minted will get.
+pub(crate) fn next_id(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get::<DataKey, u32>(&DataKey::NoOfReceipts)
+        .unwrap_or(0)
+        + 1
+}
+
+/// Destroys a receipt, along with any approval on it.
+pub(crate) fn burn(env: &Env, receipt_id: u32) {
+    storage::remove(env, &DataKey::Receipt(receipt_id));
+    storage::remove(env, &DataKey::ReceiptApproval(receipt_id));
+    events::receipt_burned(env, receipt_id);
+}
+
+/// Mints a receipt to `owner` if the product has receipts turned on.
+pub(crate) fn mint(env: &Env, owner: &Address, product_id: u32, order_id: u32) {
+    if get_mode(env, product_id) == ReceiptMode::Off {
+        return;
+    }
+    let receipt_id = next_id(env);
+    let receipt = Receipt {
+        id: receipt_id,
+        owner: owner.clone(),
+        product_id,
+        order_id,
+    };
+    env.storage()
+        .instance()
+        .set(&DataKey::NoOfReceipts, &receipt_id);
+    storage::set(env, &DataKey::Receipt(receipt_id), &receipt);
+    diag!(
+        env,
+        "receipt.mint",
+        receipt = receipt_id,
+        owner = *owner,
+        product = product_id,
+    );
+    events::receipt_minted(env, receipt);
+}
+
+/// Lets `spender` transfer the receipt once on the owner's behalf, or
+/// revokes the current approval when `spender` is `None`.
+pub(crate) fn approve(
+    env: &Env,
+    owner: Address,
+    receipt_id: u32,
+    spender: Option<Address>,
+) -> Result<(), Error> {
+    owner.require_auth();
+    let receipt = get(env, receipt_id)?;
+    if receipt.owner != owner {
+        return Err(Error::NotReceiptOwner);
+    }
+    let key = DataKey::ReceiptApproval(receipt_id);
+    match spender {
+        Some(spender) => {
+            storage::set(env, &key, &spender);
+            events::receipt_approved(
+                env,
+                ReceiptApprovalEvent {
+                    receipt_id,
+                    owner,
+                    spender,
+                },
+            );
+        }
+        None => {
+            storage::remove(env, &key);
+            events::receipt_approval_revoked(env, receipt_id);
+        }
+    }
+    Ok(())
+}
+
+/// Moves the receipt from `from` to `to`. `spender` must be the owner or
+/// the approved address, and has to authorize the call.
+pub(crate) fn transfer(
+    env: &Env,
+    spender: Address,
+    from: Address,
+    to: Address,
+    receipt_id: u32,
+) -> Result<(), Error> {
+    spender.require_auth();
+    let mut receipt = get(env, receipt_id)?;
+    if receipt.owner != from || (spender != from && get_approval(env, receipt_id) != Some(spender))
+    {
+        return Err(Error::NotReceiptOwner);
+    }
+    if get_mode(env, receipt.product_id) != ReceiptMode::Transferable {
+        return Err(Error::NotTransferable);
+    }
+
+    receipt.owner = to.clone();
+    storage::set(env, &DataKey::Receipt(receipt_id), &receipt);
+    storage::remove(env, &DataKey::ReceiptApproval(receipt_id));
+    events::receipt_transferred(
+        env,
+        ReceiptTransferEvent {
+            receipt_id,
+            from,
+            to,
+        },
+    );
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::diagnostics::diag;
+use crate::events::{self, ReferralEvent};
+use crate::payout::BPS;
+use crate::{storage, DataKey, Error};
+
+/// The payout share a referral commission is taken from.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReferralSource {
+    Launchpad,
+    Reserve,
+}
+
+/// Referrers earn `bps` of the `source` share of every purchase they refer.
+/// Defaults to nothing.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralConfig {
+    pub bps: u32,
+    pub source: ReferralSource,
+}
+
+impl ReferralConfig {
+    pub const DEFAULT: ReferralConfig = ReferralConfig {
+        bps: 0,
+        source: ReferralSource::Launchpad,
+    };
+}
+
+pub(crate) fn get_config(env: &Env) -> ReferralConfig {
+    env.storage()
+        .instance()
+        .get(&DataKey::ReferralConfig)
+        .unwrap_or(ReferralConfig::DEFAULT)
+}
+
+pub(crate) fn set_config(env: &Env, config: ReferralConfig) -> Result<(), Error> {
+    if config.bps as i128 > BPS {
+        return Err(Error::InvalidSplit);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::ReferralConfig, &config);
+    events::referral_config_changed(env, config);
+    Ok(())
+}
+
+/// Everything `referrer` has earned in `token_id`, paid out or accrued.
+pub(crate) fn get_earnings(env: &Env, referrer: &Address, token_id: &Address) -> i128 {
+    storage::get(
+        env,
+        &DataKey::ReferralEarnings(referrer.clone(), token_id.clone()),
+    )
+    .unwrap_or(0)
+}
+
+/// Takes the referral commission out of the configured source share, given
+/// as `(reserve, launchpad)`, and returns the commission.
+pub(crate) fn carve(
+    env: &Env,
+    referrer: &Address,
+    customer: &Address,
+    reserve: &mut i128,
+    launchpad: &mut i128,
+) -> Result<i128, Error> {
+    if referrer == customer {
+        return Err(Error::SelfReferral);
+    }
+    let config = get_config(env);
+    let share = match config.source {
+        ReferralSource::Launchpad => launchpad,
+        ReferralSource::Reserve => reserve,
+    };
+    let commission = *share * config.bps as i128 / BPS;
+    *share -= commission;
+    Ok(commission)
+}
+
+/// Adds a paid commission to the referrer's earnings.
+pub(crate) fn record(
+    env: &Env,
+    referrer: Address,
+    buyer: Address,
+    token_id: Address,
+    amount: i128,
+) {
+    let earned = get_earnings(env, &referrer, &token_id) + amount;
+    storage::set(
+        env,
+        &DataKey::ReferralEarnings(referrer.clone(), token_id.clone()),
+        &earned,
+    );
+    diag!(
+        env,
+        "payout.referral",
+        referrer = referrer,
+        amount = amount,
+        earned = earned,
+    );
+    events::referral(
+        env,
+        ReferralEvent {
+            referrer,
+            buyer,
+            token: token_id,
+            amount,
+        },
+    );
+}
@@ -0,0 +1,141 @@
+use soroban_sdk::{contracttype, Address, BytesN, Env};
+
+use crate::diagnostics::diag;
+use crate::events;
+use crate::{checkout, disputes, storage, DataKey, DisputeStatus, OrderError};
+
+/// Best rating a buyer can give; the worst is 1.
+pub const MAX_RATING: u32 = 5;
+
+/// A product's aggregated reviews. `average` is in hundredths of a star,
+/// so 450 is 4.5 stars, and is 0 while `count` is.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rating {
+    pub count: u32,
+    pub total: u32,
+    pub average: u32,
+}
+
+impl Rating {
+    pub const NONE: Rating = Rating {
+        count: 0,
+        total: 0,
+        average: 0,
+    };
+}
+
+/// A buyer's review of one product in one of their orders, or of a
+/// product bought outside `checkout` with `order_id` 0. `hash` points at
+/// the review text, which is kept off chain.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Review {
+    pub order_id: u32,
+    pub product_id: u32,
+    pub rating: u32,
+    pub hash: BytesN<32>,
+}
+
+pub(crate) fn get_rating(env: &Env, product_id: u32) -> Rating {
+    storage::get(env, &DataKey::Rating(product_id)).unwrap_or(Rating::NONE)
+}
+
+pub(crate) fn get(env: &Env, order_id: u32, product_id: u32) -> Option<Review> {
+    storage::get(env, &DataKey::Review(order_id, product_id))
+}
+
+pub(crate) fn get_purchase(env: &Env, buyer: &Address, product_id: u32) -> Option<Review> {
+    storage::get(env, &DataKey::PurchaseReview(buyer.clone(), product_id))
+}
+
+/// Lets `buyer` review a product it just bought outside `checkout`.
+pub(crate) fn record_purchase(env: &Env, buyer: &Address, product_id: u32) {
+    storage::set(env, &DataKey::Purchased(buyer.clone(), product_id), &true);
+}
+
+/// Records the buyer's review of `product_id` in order `order_id` and folds
+/// the rating into the product's. Each product in an order can be reviewed
+/// once, and not at all once a dispute refunded the order.
+pub(crate) fn submit(
+    env: &Env,
+    order_id: u32,
+    product_id: u32,
+    rating: u32,
+    hash: BytesN<32>,
+) -> Result<(), OrderError> {
+    let order = checkout::get_order(env, order_id).ok_or(OrderError::OrderNotExist)?;
+    order.buyer.require_auth();
+    if rating == 0 || rating > MAX_RATING {
+        return Err(OrderError::InvalidRating);
+    }
+    if !order.items.iter().any(|(id, _)| id == product_id) {
+        return Err(OrderError::NotBuyer);
+    }
+    if disputes::get(env, order_id).is_some_and(|dispute| dispute.status == DisputeStatus::Refunded)
+    {
+        return Err(OrderError::OrderRefunded);
+    }
+    if get(env, order_id, product_id).is_some() {
+        return Err(OrderError::AlreadyReviewed);
+    }
+
+    let review = Review {
+        order_id,
+        product_id,
+        rating,
+        hash,
+    };
+    storage::set(env, &DataKey::Review(order_id, product_id), &review);
+    fold(env, review);
+    Ok(())
+}
+
+/// Records `buyer`'s review of a product it bought through `get_discount`,
+/// `purchase_variant` or `purchase_bundle` and folds the rating into the
+/// product's. Each buyer can review each such product once.
+pub(crate) fn submit_purchase(
+    env: &Env,
+    buyer: Address,
+    product_id: u32,
+    rating: u32,
+    hash: BytesN<32>,
+) -> Result<(), OrderError> {
+    buyer.require_auth();
+    if rating == 0 || rating > MAX_RATING {
+        return Err(OrderError::InvalidRating);
+    }
+    if storage::get::<bool>(env, &DataKey::Purchased(buyer.clone(), product_id)).is_none() {
+        return Err(OrderError::NotBuyer);
+    }
+    if get_purchase(env, &buyer, product_id).is_some() {
+        return Err(OrderError::AlreadyReviewed);
+    }
+
+    let review = Review {
+        order_id: 0,
+        product_id,
+        rating,
+        hash,
+    };
+    storage::set(env, &DataKey::PurchaseReview(buyer, product_id), &review);
+    fold(env, review);
+    Ok(())
+}
+
+/// Adds a stored review's rating to its product's.
+fn fold(env: &Env, review: Review) {
+    let mut aggregate = get_rating(env, review.product_id);
+    aggregate.count += 1;
+    aggregate.total += review.rating;
+    aggregate.average = aggregate.total * 100 / aggregate.count;
+    storage::set(env, &DataKey::Rating(review.product_id), &aggregate);
+    diag!(
+        env,
+        "review.submit",
+        product = review.product_id,
+        rating = review.rating,
+        average = aggregate.average,
+    );
+    events::reviewed(env, review);
+}
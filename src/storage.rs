@@ -0,0 +1,39 @@
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val};
+
+use crate::DataKey;
+
+/// Ledgers closed in about a day, at five seconds a ledger.
+const DAY_IN_LEDGERS: u32 = 17280;
+
+/// How many ledgers a persistent entry is kept alive for after each use.
+pub(crate) const TTL: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Entries are only bumped back up to `TTL` once fewer ledgers than this
+/// are left, so that busy entries aren't extended on every call.
+const TTL_THRESHOLD: u32 = TTL - DAY_IN_LEDGERS;
+
+// Per-buyer and per-order entries live in persistent storage rather than the
+// instance, whose size is bounded and which is loaded in full on every call.
+
+pub(crate) fn get<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        extend(env, key);
+    }
+    value
+}
+
+pub(crate) fn set<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    extend(env, key);
+}
+
+pub(crate) fn remove(env: &Env, key: &DataKey) {
+    env.storage().persistent().remove(key);
+}
+
+fn extend(env: &Env, key: &DataKey) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL);
+}
@@ -1,10 +1,11 @@
 #![cfg(test)]
 
-
 use super::*;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger, LedgerInfo},
-    token, Address, Env,
+    testutils::{Address as _, Events, Ledger, LedgerInfo},
+    token, vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, TryFromVal,
 };
 extern crate std;
 
@@ -22,6 +23,8 @@ fn test_intialize() {
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
 
@@ -45,6 +48,8 @@ fn test_create_product() {
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
 
@@ -95,6 +100,8 @@ fn test_get_product() {
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
 
@@ -145,6 +152,8 @@ fn test_get_products() {
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
 
@@ -210,6 +219,8 @@ fn test_get_discount() {
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
     let token = token::StellarAssetClient::new(
@@ -242,8 +253,13 @@ fn test_get_discount() {
         &target,
     );
 
-    let (reserve_amount, launchpad_amount, dev_amount) =
-        client.get_discount(&1, &customer.clone(), &amount, &token.address.clone());
+    let (reserve_amount, launchpad_amount, dev_amount) = client.get_discount(
+        &1,
+        &customer.clone(),
+        &amount,
+        &token.address.clone(),
+        &None,
+    );
 
     let total_amount = amount * 10000000;
     let expected_reserve_amount =
@@ -275,6 +291,8 @@ fn test_initialize_twice() {
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
 
@@ -285,7 +303,9 @@ fn test_initialize_twice() {
             &admin.clone(),
             &admin.clone(),
             &admin.clone(),
-            &admin.clone()
+            &admin.clone(),
+            &vec![&env, admin.clone()],
+            &1
         )),
         Err(Error::AlreadyInitialized)
     );
@@ -306,6 +326,8 @@ fn test_create_product_with_past_expiry() {
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
 
@@ -364,11 +386,13 @@ fn test_get_discount_with_zero_amount() {
     let amount = 0;
     let token_id = Address::generate(&env);
 
-    let initialized = client.initialize(
+    client.initialize(
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
         &admin.clone(),
+        &vec![&env, admin.clone()],
+        &1,
     );
     // std::println!("{:?}",initialized);
     let token = token::StellarAssetClient::new(
@@ -392,7 +416,2345 @@ fn test_get_discount_with_zero_amount() {
     // let (reserve_amount, launchpad_amount, dev_amount) =
 
     assert_eq!(
-        Ok(client.get_discount(&1, &customer.clone(), &amount, &token.address.clone())),
+        Ok(client.get_discount(
+            &1,
+            &customer.clone(),
+            &amount,
+            &token.address.clone(),
+            &None
+        )),
         Err(Error::AmountMustNonZero)
     );
 }
+
+fn setup_multisig(env: &Env) -> (MarketplaceClient<'_>, std::vec::Vec<Address>) {
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(env, &contract_id);
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let signers = std::vec![
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+
+    client.initialize(
+        &admin,
+        &admin,
+        &admin,
+        &admin,
+        &vec![
+            env,
+            signers[0].clone(),
+            signers[1].clone(),
+            signers[2].clone(),
+        ],
+        &2,
+    );
+
+    (client, signers)
+}
+
+#[test]
+fn test_initialize_with_invalid_threshold() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let result = client.try_initialize(
+        &admin,
+        &admin,
+        &admin,
+        &admin,
+        &vec![&env, admin.clone()],
+        &2,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidThreshold)));
+
+    let result = client.try_initialize(
+        &admin,
+        &admin,
+        &admin,
+        &admin,
+        &vec![&env, admin.clone(), admin.clone()],
+        &2,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidThreshold)));
+}
+
+#[test]
+fn test_proposal_updates_dev_account() {
+    let env = Env::default();
+    let (client, signers) = setup_multisig(&env);
+    let new_dev = Address::generate(&env);
+
+    let proposal_id = client.propose(&signers[0], &ProposalAction::SetDevAccount(new_dev.clone()));
+    assert_eq!(proposal_id, 1);
+
+    // One approval from the proposer is below the threshold of two.
+    env.ledger()
+        .with_mut(|l| l.timestamp += multisig::PROPOSAL_TIMELOCK);
+    assert_eq!(
+        client.try_execute(&proposal_id),
+        Err(Ok(Error::ThresholdNotReached))
+    );
+
+    assert_eq!(client.approve(&signers[1], &proposal_id), 2);
+    client.execute(&proposal_id);
+
+    assert_eq!(client.get_dev_acc(), new_dev);
+    assert!(client.get_proposal(&proposal_id).executed);
+    assert_eq!(
+        client.try_execute(&proposal_id),
+        Err(Ok(Error::ProposalAlreadyExecuted))
+    );
+}
+
+#[test]
+fn test_proposal_respects_timelock() {
+    let env = Env::default();
+    let (client, signers) = setup_multisig(&env);
+    let split = Split {
+        reserve: 50,
+        launchpad: 20,
+        dev: 30,
+    };
+
+    let proposal_id = client.propose(&signers[0], &ProposalAction::SetSplit(split.clone()));
+    client.approve(&signers[2], &proposal_id);
+
+    assert_eq!(
+        client.try_execute(&proposal_id),
+        Err(Ok(Error::TimelockNotElapsed))
+    );
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += multisig::PROPOSAL_TIMELOCK);
+    client.execute(&proposal_id);
+
+    assert_eq!(client.get_split(), split);
+}
+
+#[test]
+fn test_proposal_rejects_non_signers() {
+    let env = Env::default();
+    let (client, signers) = setup_multisig(&env);
+    let outsider = Address::generate(&env);
+
+    assert_eq!(
+        client.try_propose(
+            &outsider,
+            &ProposalAction::SetReserveAccount(outsider.clone())
+        ),
+        Err(Ok(Error::NotSigner))
+    );
+
+    let proposal_id = client.propose(
+        &signers[0],
+        &ProposalAction::Upgrade(BytesN::from_array(&env, &[0; 32])),
+    );
+    assert_eq!(
+        client.try_approve(&outsider, &proposal_id),
+        Err(Ok(Error::NotSigner))
+    );
+    assert_eq!(
+        client.try_approve(&signers[0], &proposal_id),
+        Err(Ok(Error::AlreadyApproved))
+    );
+    assert_eq!(
+        client.try_propose(
+            &signers[1],
+            &ProposalAction::SetSplit(Split {
+                reserve: 90,
+                launchpad: 0,
+                dev: 10,
+            })
+        ),
+        Err(Ok(Error::InvalidSplit))
+    );
+}
+
+#[test]
+fn test_set_payout_accounts() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let signer = Address::generate(&env);
+    let cosigner = Address::generate(&env);
+    let old_acc = Address::generate(&env);
+
+    client.initialize(
+        &old_acc,
+        &old_acc,
+        &old_acc,
+        &admin,
+        &vec![&env, signer.clone(), cosigner.clone()],
+        &2,
+    );
+
+    let reserve = Address::generate(&env);
+    let dev = Address::generate(&env);
+    let launchpad = Address::generate(&env);
+    let approvers = vec![&env, signer.clone(), cosigner.clone()];
+
+    assert_eq!(
+        client.try_set_reserve_acc(&reserve, &vec![&env, signer.clone()]),
+        Err(Ok(Error::ThresholdNotReached))
+    );
+    assert_eq!(
+        client.try_set_reserve_acc(&reserve, &vec![&env, signer.clone(), signer.clone()]),
+        Err(Ok(Error::AlreadyApproved))
+    );
+    assert_eq!(
+        client.try_set_reserve_acc(&reserve, &vec![&env, signer.clone(), old_acc.clone()]),
+        Err(Ok(Error::NotSigner))
+    );
+    assert_eq!(client.get_reserve_acc(), old_acc);
+
+    client.set_reserve_acc(&reserve, &approvers);
+    let events = env.events().all();
+    assert_eq!(
+        env.auths()
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect::<std::vec::Vec<_>>(),
+        std::vec![admin.clone(), signer.clone(), cosigner.clone()],
+        "setting a payout account must be authorized by the admin and the approving signers"
+    );
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (
+                    events::EVENTS_NAMESPACE,
+                    events::EVENTS_VERSION,
+                    symbol_short!("acct_set")
+                )
+                    .into_val(&env),
+                events::AccountChangedEvent {
+                    role: symbol_short!("reserve"),
+                    old: old_acc.clone(),
+                    new: reserve.clone(),
+                }
+                .into_val(&env),
+            )
+        ]
+    );
+
+    client.set_dev_acc(&dev, &approvers);
+    client.set_launchpad_acc(&launchpad, &approvers);
+
+    assert_eq!(client.get_reserve_acc(), reserve);
+    assert_eq!(client.get_dev_acc(), dev);
+    assert_eq!(client.get_launchpad_acc(), launchpad);
+}
+
+#[test]
+fn test_accrued_payouts_and_withdraw() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let reserve = Address::generate(&env);
+    let dev = Address::generate(&env);
+    let launchpad = Address::generate(&env);
+    let customer = Address::generate(&env);
+
+    client.initialize(
+        &reserve,
+        &dev,
+        &launchpad,
+        &admin,
+        &vec![&env, admin.clone()],
+        &1,
+    );
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin);
+    token::StellarAssetClient::new(&env, &token_address).mint(&customer, &10000000000);
+    let token = token::Client::new(&env, &token_address);
+
+    client.set_payout_mode(&PayoutMode::Accrue);
+    assert_eq!(client.get_payout_mode(), PayoutMode::Accrue);
+
+    client.create_product(
+        &String::from_str(&env, "Product 1"),
+        &String::from_str(&env, "Description 1"),
+        &String::from_str(&env, "Category 1"),
+        &(env.ledger().timestamp() + 10000),
+        &String::from_str(&env, "image.png"),
+        &1000,
+        &10,
+    );
+    let (reserve_amount, launchpad_amount, dev_amount) =
+        client.get_discount(&1, &customer, &1, &token_address, &None);
+
+    // Nothing has been pushed to the recipients, the contract holds it all.
+    assert_eq!(token.balance(&reserve), 0);
+    assert_eq!(token.balance(&contract_id), 10000000);
+    assert_eq!(
+        client.get_claimable(&reserve, &token_address),
+        reserve_amount
+    );
+    assert_eq!(client.get_claimable(&dev, &token_address), dev_amount);
+    assert_eq!(
+        client.get_claimable(&launchpad, &token_address),
+        launchpad_amount
+    );
+
+    assert_eq!(
+        client.withdraw(&dev, &token_address, &1000000),
+        dev_amount - 1000000
+    );
+    assert_eq!(token.balance(&dev), 1000000);
+    assert_eq!(
+        client.try_withdraw(&dev, &token_address, &dev_amount),
+        Err(Ok(Error::InsufficientClaimable))
+    );
+    assert_eq!(
+        client.try_withdraw(&dev, &token_address, &0),
+        Err(Ok(Error::AmountMustBeGreaterThanZero))
+    );
+
+    client.withdraw(&reserve, &token_address, &reserve_amount);
+    assert_eq!(token.balance(&reserve), reserve_amount);
+    assert_eq!(client.get_claimable(&reserve, &token_address), 0);
+}
+
+#[test]
+fn test_purchase_event_payload() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let customer = Address::generate(&env);
+
+    client.initialize(
+        &admin,
+        &admin,
+        &admin,
+        &admin,
+        &vec![&env, admin.clone()],
+        &1,
+    );
+    let token_address = env.register_stellar_asset_contract(Address::generate(&env));
+    token::StellarAssetClient::new(&env, &token_address).mint(&customer, &10000000000);
+
+    client.create_product(
+        &String::from_str(&env, "Product 1"),
+        &String::from_str(&env, "Description 1"),
+        &String::from_str(&env, "Category 1"),
+        &(env.ledger().timestamp() + 10000),
+        &String::from_str(&env, "image.png"),
+        &1000,
+        &10,
+    );
+    let (reserve_amount, launchpad_amount, dev_amount) =
+        client.get_discount(&1, &customer, &1, &token_address, &None);
+
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 2..),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (
+                    events::EVENTS_NAMESPACE,
+                    events::EVENTS_VERSION,
+                    symbol_short!("purchase")
+                )
+                    .into_val(&env),
+                events::PurchaseEvent {
+                    buyer: customer.clone(),
+                    product_id: 1,
+                    token: token_address.clone(),
+                    quantity: 1,
+                    reserve_amount,
+                    launchpad_amount,
+                    dev_amount,
+                }
+                .into_val(&env),
+            ),
+            (
+                contract_id.clone(),
+                (
+                    events::EVENTS_NAMESPACE,
+                    events::EVENTS_VERSION,
+                    symbol_short!("prod_upd")
+                )
+                    .into_val(&env),
+                events::ProductEvent {
+                    product_id: 1,
+                    price: 1000,
+                    remaining: 9,
+                    expiry: env.ledger().timestamp() + 10000,
+                }
+                .into_val(&env),
+            ),
+        ]
+    );
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_purchase_diagnostics() {
+    use soroban_sdk::testutils::Logs;
+
+    let env = Env::default();
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let customer = Address::generate(&env);
+
+    client.initialize(
+        &admin,
+        &admin,
+        &admin,
+        &admin,
+        &vec![&env, admin.clone()],
+        &1,
+    );
+    let token_address = env.register_stellar_asset_contract(Address::generate(&env));
+    token::StellarAssetClient::new(&env, &token_address).mint(&customer, &10000000000);
+    client.create_product(
+        &String::from_str(&env, "Product 1"),
+        &String::from_str(&env, "Description 1"),
+        &String::from_str(&env, "Category 1"),
+        &(env.ledger().timestamp() + 10000),
+        &String::from_str(&env, "image.png"),
+        &1000,
+        &10,
+    );
+    client.get_discount(&1, &customer, &1, &token_address, &None);
+
+    let logs = env.logs().all();
+    for step in [
+        "purchase.start",
+        "purchase.split",
+        "payout.push",
+        "purchase.stock",
+    ] {
+        assert!(
+            logs.iter().any(|log| log.contains(step)),
+            "missing {step} in {logs:?}"
+        );
+    }
+}
+
+#[test]
+fn test_harness_setup_and_builders() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+
+    assert_eq!(client.get_admin(), market.admin);
+    assert_eq!(client.get_reserve_acc(), market.reserve_acc);
+
+    let now = env.ledger().timestamp();
+    let product = market
+        .product()
+        .title("Ticket")
+        .price(25)
+        .target(2)
+        .expiry(now + 100)
+        .create();
+    assert_eq!(product.title, String::from_str(&env, "Ticket"));
+    assert_eq!(product.remaining, 2);
+
+    let buyer = market.funded_buyer(10000000000);
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(market.token().balance(&buyer), 10000000000 - 10000000);
+    assert_eq!(market.token().balance(&market.dev_acc), 3000000);
+
+    market.advance_time(101);
+    assert_eq!(env.ledger().timestamp(), now + 101);
+    assert_eq!(
+        market.client().try_create_product(
+            &product.title,
+            &product.description,
+            &product.category,
+            &(now + 100),
+            &product.image,
+            &product.price,
+            &product.remaining,
+        ),
+        Err(Ok(Error::ExpiryShouldBeFuture))
+    );
+}
+
+#[test]
+fn test_pause_blocks_purchases() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().create();
+    let buyer = market.funded_buyer(10000000000);
+
+    assert!(!client.is_paused());
+    client.set_paused(&true);
+    assert_eq!(env.auths()[0].0, market.admin);
+    assert!(client.is_paused());
+    assert_eq!(
+        client.try_get_discount(&product.id, &buyer, &1, &market.token_id, &None),
+        Err(Ok(Error::Paused))
+    );
+    assert_eq!(client.get_product(&product.id).remaining, product.remaining);
+
+    client.set_paused(&false);
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(
+        client.get_product(&product.id).remaining,
+        product.remaining - 1
+    );
+}
+
+#[test]
+fn test_create_products_batch_and_export() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let existing = market.product().title("First").create();
+    let now = env.ledger().timestamp();
+
+    let inputs = vec![
+        &env,
+        market.product().title("Shirt").price(20).target(3).input(),
+        market.product().title("Cap").price(5).target(8).input(),
+        market.product().title("Pin").target(1).input(),
+        market.product().title("Flyer").expiry(now + 10).input(),
+    ];
+    let created = client.create_products_batch(&inputs);
+    assert_eq!(env.auths()[0].0, market.admin);
+    assert_eq!(created.len(), 4);
+    assert_eq!(created.get_unchecked(0).id, existing.id + 1);
+    assert_eq!(created.get_unchecked(1).id, existing.id + 2);
+    assert_eq!(client.get_products().slice(1..), created);
+
+    let buyer = market.funded_buyer(10000000000);
+    for product in [created.get_unchecked(1), created.get_unchecked(2)] {
+        client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    }
+    market.advance_time(11);
+
+    // The sold-out pin and the expired flyer would fail the batch, so they
+    // are not exported.
+    let exported = client.export_products();
+    assert_eq!(exported.len(), 3);
+    assert_eq!(exported.get_unchecked(1), inputs.get_unchecked(0));
+    assert_eq!(exported.get_unchecked(2).target, 7);
+
+    let other = testutils::Marketplace::setup(&env);
+    other.client().create_products_batch(&exported);
+    assert_eq!(other.client().export_products(), exported);
+}
+
+#[test]
+fn test_create_products_batch_is_all_or_nothing() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let now = env.ledger().timestamp();
+
+    let inputs = vec![
+        &env,
+        market.product().title("Fine").input(),
+        market.product().title("Expired").expiry(now - 1).input(),
+    ];
+    assert_eq!(
+        client.try_create_products_batch(&inputs),
+        Err(Ok(Error::ExpiryShouldBeFuture))
+    );
+    assert_eq!(client.get_products().len(), 0);
+
+    assert_eq!(
+        client.try_create_products_batch(&vec![&env]),
+        Err(Ok(Error::EmptyBatch))
+    );
+    let mut too_many = vec![&env];
+    for _ in 0..=MAX_BATCH_SIZE {
+        too_many.push_back(market.product().input());
+    }
+    assert_eq!(
+        client.try_create_products_batch(&too_many),
+        Err(Ok(Error::BatchTooLarge))
+    );
+    assert_eq!(client.get_products().len(), 0);
+}
+
+#[test]
+fn test_create_product_validates_metadata() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let long = |len| "x".repeat(len);
+    let limits = ProductLimits::DEFAULT;
+
+    let cases = [
+        (market.product().title(""), Error::EmptyTitle),
+        (
+            market.product().title(&long(limits.title as usize + 1)),
+            Error::TitleTooLong,
+        ),
+        (
+            market
+                .product()
+                .description(&long(limits.description as usize + 1)),
+            Error::DescriptionTooLong,
+        ),
+        (
+            market
+                .product()
+                .category(&long(limits.category as usize + 1)),
+            Error::CategoryTooLong,
+        ),
+        (
+            market.product().image(&long(limits.image as usize + 1)),
+            Error::ImageTooLong,
+        ),
+        (market.product().price(0), Error::PriceMustBePositive),
+        (market.product().price(-5), Error::PriceMustBePositive),
+        (market.product().target(0), Error::TargetMustBePositive),
+        (market.product().target(-1), Error::TargetMustBePositive),
+    ];
+    for (product, error) in cases {
+        let input = product.input();
+        assert_eq!(
+            client.try_create_product(
+                &input.title,
+                &input.description,
+                &input.category,
+                &input.expiry,
+                &input.image,
+                &input.price,
+                &input.target,
+            ),
+            Err(Ok(error))
+        );
+        assert_eq!(
+            client.try_create_products_batch(&vec![&env, input]),
+            Err(Ok(error))
+        );
+    }
+    assert_eq!(client.get_products().len(), 0);
+
+    let at_limit = market
+        .product()
+        .title(&long(limits.title as usize))
+        .description(&long(limits.description as usize))
+        .category(&long(limits.category as usize))
+        .image(&long(limits.image as usize))
+        .create();
+    assert_eq!(at_limit.id, 1);
+}
+
+#[test]
+fn test_set_product_limits() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    assert_eq!(client.get_product_limits(), ProductLimits::DEFAULT);
+
+    let limits = ProductLimits {
+        title: 4,
+        description: 8,
+        category: 8,
+        image: 16,
+    };
+    client.set_product_limits(&limits);
+    assert_eq!(env.auths()[0].0, market.admin);
+    assert_eq!(client.get_product_limits(), limits);
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &env,
+            (
+                market.contract_id.clone(),
+                (
+                    events::EVENTS_NAMESPACE,
+                    events::EVENTS_VERSION,
+                    symbol_short!("limits")
+                )
+                    .into_val(&env),
+                limits.clone().into_val(&env),
+            ),
+        ]
+    );
+
+    let input = market.product().title("Hat").description("Warm").input();
+    assert_eq!(client.create_products_batch(&vec![&env, input]).len(), 1);
+    let input = market.product().title("Beanie").input();
+    assert_eq!(
+        client.try_create_products_batch(&vec![&env, input]),
+        Err(Ok(Error::TitleTooLong))
+    );
+
+    assert_eq!(
+        client.try_set_product_limits(&ProductLimits { title: 0, ..limits }),
+        Err(Ok(Error::InvalidProductLimits))
+    );
+}
+
+#[test]
+fn test_content_addressed_metadata() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let blob = Bytes::from_slice(&env, br#"{"description":"A warm hat","image":"hat.png"}"#);
+    let metadata = ProductMetadata {
+        hash: env.crypto().sha256(&blob),
+        uri: String::from_str(
+            &env,
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+        ),
+    };
+    let expiry = env.ledger().timestamp() + 1000;
+
+    let product = client.create_product_with_metadata(
+        &String::from_str(&env, "Hat"),
+        &String::from_str(&env, "Clothes"),
+        &expiry,
+        &500,
+        &3,
+        &metadata,
+    );
+    assert_eq!(product.description, String::from_str(&env, ""));
+    assert_eq!(product.image, String::from_str(&env, ""));
+    assert_eq!(client.get_product(&product.id), product);
+    assert_eq!(
+        client.get_product_metadata(&product.id),
+        Some(metadata.clone())
+    );
+
+    assert!(client.verify_metadata(&product.id, &blob));
+    let mut tampered = blob.clone();
+    tampered.push_back(b' ');
+    assert!(!client.verify_metadata(&product.id, &tampered));
+
+    let plain = market.product().create();
+    assert_eq!(client.get_product_metadata(&plain.id), None);
+    assert_eq!(
+        client.try_verify_metadata(&plain.id, &blob),
+        Err(Ok(Error::NoMetadata))
+    );
+    assert_eq!(
+        client.try_verify_metadata(&99, &blob),
+        Err(Ok(Error::ProductNotExist))
+    );
+
+    let long_uri = ProductMetadata {
+        uri: String::from_str(&env, &"x".repeat(ProductLimits::DEFAULT.image as usize + 1)),
+        ..metadata
+    };
+    assert_eq!(
+        client.try_create_product_with_metadata(
+            &String::from_str(&env, "Scarf"),
+            &String::from_str(&env, "Clothes"),
+            &expiry,
+            &500,
+            &3,
+            &long_uri,
+        ),
+        Err(Ok(Error::ImageTooLong))
+    );
+}
+
+#[test]
+fn test_variants_have_their_own_stock() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().price(20).target(5).create();
+    let buyer = market.funded_buyer(10000000000);
+
+    let small = client.add_variant(&product.id, &String::from_str(&env, "Small"), &-5, &1);
+    let large = client.add_variant(&product.id, &String::from_str(&env, "Large"), &10, &3);
+    assert_eq!(env.auths()[0].0, market.admin);
+    assert_eq!((small.id, large.id), (1, 2));
+    assert_eq!(
+        client.get_variants(&product.id),
+        vec![&env, small.clone(), large.clone()]
+    );
+    assert_eq!(
+        client.get_product_view(&product.id),
+        ProductView {
+            product: client.get_product(&product.id),
+            variants: vec![&env, small.clone(), large.clone()],
+            rating: Rating::NONE,
+        }
+    );
+    assert_eq!(
+        client.get_product_views().get_unchecked(0).variants.len(),
+        2
+    );
+
+    assert_eq!(
+        client.try_get_discount(&product.id, &buyer, &1, &market.token_id, &None),
+        Err(Ok(Error::VariantRequired))
+    );
+
+    let shares = client.purchase_variant(&product.id, &small.id, &buyer, &market.token_id, &None);
+    assert_eq!(shares, (90000000, 15000000, 45000000));
+    assert_eq!(market.token().balance(&buyer), 10000000000 - 150000000);
+    assert_eq!(
+        client.try_purchase_variant(&product.id, &small.id, &buyer, &market.token_id, &None),
+        Err(Ok(Error::TargetReached))
+    );
+    client.purchase_variant(&product.id, &large.id, &buyer, &market.token_id, &None);
+
+    assert_eq!(client.get_product(&product.id).remaining, 3);
+    let variants = client.get_variants(&product.id);
+    assert_eq!(variants.get_unchecked(0).remaining, 0);
+    assert_eq!(variants.get_unchecked(1).remaining, 2);
+    let events = env.events().all();
+    assert_eq!(
+        events.slice(events.len() - 1..),
+        vec![
+            &env,
+            (
+                market.contract_id.clone(),
+                (
+                    events::EVENTS_NAMESPACE,
+                    events::EVENTS_VERSION,
+                    symbol_short!("var_upd")
+                )
+                    .into_val(&env),
+                events::VariantEvent {
+                    product_id: product.id,
+                    variant_id: large.id,
+                    price: 30,
+                    remaining: 2,
+                }
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    for variant_id in [0, 3] {
+        assert_eq!(
+            client.try_purchase_variant(&product.id, &variant_id, &buyer, &market.token_id, &None),
+            Err(Ok(Error::VariantNotExist))
+        );
+    }
+
+    market.set_time(product.expiry + 1);
+    assert_eq!(
+        client.try_purchase_variant(&product.id, &large.id, &buyer, &market.token_id, &None),
+        Err(Ok(Error::DiscountExpired))
+    );
+}
+
+#[test]
+fn test_add_variant_validation() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().price(20).create();
+    let name = String::from_str(&env, "Tier");
+
+    assert_eq!(
+        client.try_add_variant(&99, &name, &0, &1),
+        Err(Ok(Error::ProductNotExist))
+    );
+    assert_eq!(
+        client.try_add_variant(&product.id, &String::from_str(&env, ""), &0, &1),
+        Err(Ok(Error::EmptyTitle))
+    );
+    assert_eq!(
+        client.try_add_variant(&product.id, &name, &-20, &1),
+        Err(Ok(Error::PriceMustBePositive))
+    );
+    assert_eq!(
+        client.try_add_variant(&product.id, &name, &0, &0),
+        Err(Ok(Error::TargetMustBePositive))
+    );
+    for _ in 0..MAX_VARIANTS {
+        client.add_variant(&product.id, &name, &0, &1);
+    }
+    assert_eq!(
+        client.try_add_variant(&product.id, &name, &0, &1),
+        Err(Ok(Error::TooManyVariants))
+    );
+}
+
+#[test]
+fn test_checkout_pays_each_recipient_once() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let hat = market.product().price(20).target(5).create();
+    let scarf = market.product().price(10).target(2).create();
+    let buyer = market.funded_buyer(10000000000);
+
+    let order_id = client.checkout(
+        &buyer,
+        &vec![&env, (hat.id, 2), (scarf.id, 1), (hat.id, 1)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+    assert_eq!(order_id, 1);
+    let transfers = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract_id, topics, _)| {
+            *contract_id == market.token_id
+                && Symbol::try_from_val(&env, &topics.get_unchecked(0))
+                    == Ok(symbol_short!("transfer"))
+        })
+        .count();
+    assert_eq!(transfers, 3);
+    assert_eq!(market.token().balance(&buyer), 10000000000 - 700000000);
+    assert_eq!(market.token().balance(&market.reserve_acc), 420000000);
+    assert_eq!(client.get_product(&hat.id).remaining, 2);
+    assert_eq!(client.get_product(&scarf.id).remaining, 1);
+
+    let order = client.get_order(&order_id).unwrap();
+    assert_eq!(
+        market.persistent_ttl(&DataKey::Order(order_id)),
+        Some(storage::TTL)
+    );
+    assert_eq!(order.buyer, buyer);
+    assert_eq!(order.total, 700000000);
+    assert_eq!(order.discount, 0);
+    assert_eq!(client.get_order(&2), None);
+
+    let items = [
+        (vec![&env], Error::EmptyBatch),
+        (vec![&env, (99, 1)], Error::ProductNotExist),
+        (vec![&env, (hat.id, 0)], Error::AmountMustBeGreaterThanZero),
+        (vec![&env, (hat.id, 3)], Error::AmountExceedTargetLimit),
+        (
+            vec![&env, (scarf.id, 1), (hat.id, 2), (hat.id, 1)],
+            Error::TargetReached,
+        ),
+    ];
+    for (items, error) in items {
+        assert_eq!(
+            client.try_checkout(&buyer, &items, &market.token_id, &None, &None),
+            Err(Ok(error))
+        );
+    }
+    assert_eq!(client.get_product(&hat.id).remaining, 2);
+    assert_eq!(client.get_product(&scarf.id).remaining, 1);
+    assert_eq!(client.get_order(&2), None);
+
+    market.advance_time(2 * 24 * 60 * 60);
+    assert_eq!(
+        client.try_checkout(
+            &buyer,
+            &vec![&env, (hat.id, 1)],
+            &market.token_id,
+            &None,
+            &None
+        ),
+        Err(Ok(Error::DiscountExpired))
+    );
+}
+
+#[test]
+fn test_checkout_with_coupon() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().price(100).create();
+    let buyer = market.funded_buyer(100000000000);
+    let code = symbol_short!("LAUNCH25");
+    let coupon = Coupon {
+        discount_bps: 2500,
+        expiry: env.ledger().timestamp() + 100,
+        uses_left: 1,
+    };
+
+    assert_eq!(
+        client.try_set_coupon(
+            &code,
+            &Coupon {
+                discount_bps: 10001,
+                ..coupon.clone()
+            }
+        ),
+        Err(Ok(Error::InvalidCoupon))
+    );
+    client.set_coupon(&code, &coupon);
+    assert_eq!(env.auths()[0].0, market.admin);
+    assert_eq!(client.get_coupon(&code), Some(coupon.clone()));
+
+    let items = vec![&env, (product.id, 2)];
+    let order_id = client.checkout(&buyer, &items, &market.token_id, &Some(code.clone()), &None);
+    let order = client.get_order(&order_id).unwrap();
+    assert_eq!(order.discount, 500000000);
+    assert_eq!(order.total, 1500000000);
+    assert_eq!(market.token().balance(&buyer), 100000000000 - 1500000000);
+    assert_eq!(client.get_coupon(&code).unwrap().uses_left, 0);
+
+    assert_eq!(
+        client.try_checkout(&buyer, &items, &market.token_id, &Some(code.clone()), &None),
+        Err(Ok(Error::InvalidCoupon))
+    );
+    assert_eq!(
+        client.try_checkout(
+            &buyer,
+            &items,
+            &market.token_id,
+            &Some(symbol_short!("NOPE")),
+            &None
+        ),
+        Err(Ok(Error::InvalidCoupon))
+    );
+    client.set_coupon(&code, &coupon);
+    market.advance_time(101);
+    assert_eq!(
+        client.try_checkout(&buyer, &items, &market.token_id, &Some(code.clone()), &None),
+        Err(Ok(Error::InvalidCoupon))
+    );
+    assert_eq!(client.get_product(&product.id).remaining, 8);
+
+    client.remove_coupon(&code);
+    assert_eq!(client.get_coupon(&code), None);
+}
+
+#[test]
+fn test_bundles() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let hat = market.product().price(20).target(2).create();
+    let scarf = market.product().price(10).target(1).create();
+    let gloves = market
+        .product()
+        .expiry(env.ledger().timestamp() + 10)
+        .create();
+    let buyer = market.funded_buyer(10000000000);
+
+    let title = String::from_str(&env, "Winter set");
+    let bundle = client.create_bundle(&title, &vec![&env, hat.id, scarf.id], &25);
+    assert_eq!(bundle.id, 1);
+    assert_eq!(client.get_bundle(&bundle.id), Some(bundle.clone()));
+    assert_eq!(client.get_bundle(&2), None);
+
+    let shares = client.purchase_bundle(&bundle.id, &buyer, &market.token_id, &None);
+    assert_eq!(shares, (150000000, 25000000, 75000000));
+    assert_eq!(market.token().balance(&buyer), 10000000000 - 250000000);
+    assert_eq!(client.get_product(&hat.id).remaining, 1);
+    assert_eq!(client.get_product(&scarf.id).remaining, 0);
+
+    // The scarf is sold out, so nothing is taken from the hat either.
+    assert_eq!(
+        client.try_purchase_bundle(&bundle.id, &buyer, &market.token_id, &None),
+        Err(Ok(Error::TargetReached))
+    );
+    assert_eq!(client.get_product(&hat.id).remaining, 1);
+    assert_eq!(
+        client.try_purchase_bundle(&9, &buyer, &market.token_id, &None),
+        Err(Ok(Error::BundleNotExist))
+    );
+
+    let pair = client.create_bundle(&title, &vec![&env, hat.id, gloves.id], &25);
+    market.advance_time(20);
+    assert_eq!(
+        client.try_purchase_bundle(&pair.id, &buyer, &market.token_id, &None),
+        Err(Ok(Error::DiscountExpired))
+    );
+    assert_eq!(client.get_product(&hat.id).remaining, 1);
+
+    // A product given variants after being bundled can't be sold by the
+    // bundle any more.
+    let sized = market.product().create();
+    let sized_set = client.create_bundle(&title, &vec![&env, sized.id], &25);
+    client.add_variant(&sized.id, &String::from_str(&env, "Large"), &0, &1);
+    assert_eq!(
+        client.try_purchase_bundle(&sized_set.id, &buyer, &market.token_id, &None),
+        Err(Ok(Error::VariantRequired))
+    );
+}
+
+#[test]
+fn test_create_bundle_validation() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let hat = market.product().create();
+    let sized = market.product().create();
+    client.add_variant(&sized.id, &String::from_str(&env, "Large"), &0, &1);
+
+    let title = String::from_str(&env, "Set");
+    let cases = [
+        (
+            String::from_str(&env, ""),
+            vec![&env, hat.id],
+            10,
+            Error::EmptyTitle,
+        ),
+        (
+            title.clone(),
+            vec![&env, hat.id],
+            0,
+            Error::PriceMustBePositive,
+        ),
+        (title.clone(), vec![&env], 10, Error::EmptyBatch),
+        (
+            title.clone(),
+            vec![&env, hat.id, 99],
+            10,
+            Error::ProductNotExist,
+        ),
+        (
+            title.clone(),
+            vec![&env, sized.id],
+            10,
+            Error::VariantRequired,
+        ),
+    ];
+    for (title, product_ids, price, error) in cases {
+        assert_eq!(
+            client.try_create_bundle(&title, &product_ids, &price),
+            Err(Ok(error))
+        );
+    }
+    assert_eq!(client.get_bundle(&1), None);
+}
+
+#[test]
+fn test_referral_commission() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().price(1).create();
+    let buyer = market.funded_buyer(10000000000);
+    let referrer = Address::generate(&env);
+
+    // Nothing is carved out until a commission is configured.
+    assert_eq!(client.get_referral_config(), ReferralConfig::DEFAULT);
+    client.get_discount(
+        &product.id,
+        &buyer,
+        &1,
+        &market.token_id,
+        &Some(referrer.clone()),
+    );
+    assert_eq!(client.get_referral_earnings(&referrer, &market.token_id), 0);
+
+    client.set_referral_config(&ReferralConfig {
+        bps: 5000,
+        source: ReferralSource::Launchpad,
+    });
+    let shares = client.get_discount(
+        &product.id,
+        &buyer,
+        &1,
+        &market.token_id,
+        &Some(referrer.clone()),
+    );
+    assert_eq!(shares, (6000000, 500000, 3000000));
+    assert_eq!(market.token().balance(&referrer), 500000);
+    assert_eq!(market.token().balance(&market.launchpad_acc), 1500000);
+
+    client.set_referral_config(&ReferralConfig {
+        bps: 1000,
+        source: ReferralSource::Reserve,
+    });
+    client.checkout(
+        &buyer,
+        &vec![&env, (product.id, 2)],
+        &market.token_id,
+        &None,
+        &Some(referrer.clone()),
+    );
+    assert_eq!(market.token().balance(&referrer), 500000 + 1200000);
+    assert_eq!(
+        client.get_referral_earnings(&referrer, &market.token_id),
+        1700000
+    );
+    assert_eq!(client.get_referral_earnings(&buyer, &market.token_id), 0);
+
+    assert_eq!(
+        client.try_get_discount(
+            &product.id,
+            &buyer,
+            &1,
+            &market.token_id,
+            &Some(buyer.clone())
+        ),
+        Err(Ok(Error::SelfReferral))
+    );
+    assert_eq!(
+        client.try_set_referral_config(&ReferralConfig {
+            bps: 10001,
+            source: ReferralSource::Reserve,
+        }),
+        Err(Ok(Error::InvalidSplit))
+    );
+}
+
+#[test]
+fn test_referral_commission_accrues() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().price(1).create();
+    let buyer = market.funded_buyer(10000000000);
+    let referrer = Address::generate(&env);
+    client.set_payout_mode(&PayoutMode::Accrue);
+    client.set_referral_config(&ReferralConfig {
+        bps: 2000,
+        source: ReferralSource::Launchpad,
+    });
+
+    client.get_discount(
+        &product.id,
+        &buyer,
+        &1,
+        &market.token_id,
+        &Some(referrer.clone()),
+    );
+    assert_eq!(client.get_claimable(&referrer, &market.token_id), 200000);
+    for key in [
+        DataKey::Claimable(referrer.clone(), market.token_id.clone()),
+        DataKey::ReferralEarnings(referrer.clone(), market.token_id.clone()),
+    ] {
+        assert_eq!(market.persistent_ttl(&key), Some(storage::TTL));
+    }
+    assert_eq!(
+        client.get_claimable(&market.launchpad_acc, &market.token_id),
+        800000
+    );
+    assert_eq!(
+        client.get_referral_earnings(&referrer, &market.token_id),
+        200000
+    );
+    client.withdraw(&referrer, &market.token_id, &200000);
+    assert_eq!(market.token().balance(&referrer), 200000);
+}
+
+#[test]
+fn test_loyalty_points() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().create();
+    let buyer = market.funded_buyer(10000000000);
+
+    client.get_discount(&product.id, &buyer, &5, &market.token_id, &None);
+    assert_eq!(client.get_points(&buyer), 0);
+
+    client.set_loyalty_config(&LoyaltyConfig {
+        earn_rate: 10,
+        burn_rate: 100,
+        ttl: 1000,
+    });
+    client.get_discount(&product.id, &buyer, &5, &market.token_id, &None);
+    assert_eq!(client.get_points(&buyer), 50);
+
+    // 50 points take half a token off; the 4.5 tokens paid earn 45 more.
+    let shares = client.purchase_with_points(&product.id, &buyer, &5, &market.token_id, &50, &None);
+    assert_eq!(shares, (27000000, 4500000, 13500000));
+    assert_eq!(client.get_points(&buyer), 45);
+    assert_eq!(
+        client.try_purchase_with_points(&product.id, &buyer, &5, &market.token_id, &46, &None),
+        Err(Ok(Error::InsufficientPoints))
+    );
+    assert_eq!(
+        client.try_purchase_with_points(&product.id, &buyer, &5, &market.token_id, &-1, &None),
+        Err(Ok(Error::AmountMustBeGreaterThanZero))
+    );
+
+    market.advance_time(1001);
+    assert_eq!(client.get_points(&buyer), 0);
+    assert_eq!(
+        client.try_purchase_with_points(&product.id, &buyer, &5, &market.token_id, &1, &None),
+        Err(Ok(Error::InsufficientPoints))
+    );
+
+    assert_eq!(
+        client.try_set_loyalty_config(&LoyaltyConfig {
+            earn_rate: 10,
+            burn_rate: 0,
+            ttl: 0,
+        }),
+        Err(Ok(Error::InvalidLoyaltyConfig))
+    );
+}
+
+#[test]
+fn test_points_are_earned_on_every_purchase_path() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let hat = market.product().price(20).create();
+    let sized = market.product().price(20).create();
+    let buyer = market.funded_buyer(10000000000);
+    client.set_loyalty_config(&LoyaltyConfig {
+        earn_rate: 1,
+        burn_rate: 100,
+        ttl: 0,
+    });
+
+    client.checkout(
+        &buyer,
+        &vec![&env, (hat.id, 2)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+    assert_eq!(client.get_points(&buyer), 40);
+    assert_eq!(
+        market.persistent_ttl(&DataKey::Points(buyer.clone())),
+        Some(storage::TTL)
+    );
+
+    let variant = client.add_variant(&sized.id, &String::from_str(&env, "Large"), &5, &1);
+    client.purchase_variant(&sized.id, &variant.id, &buyer, &market.token_id, &None);
+    assert_eq!(client.get_points(&buyer), 65);
+
+    let bundle = client.create_bundle(&String::from_str(&env, "Set"), &vec![&env, hat.id], &15);
+    client.purchase_bundle(&bundle.id, &buyer, &market.token_id, &None);
+    assert_eq!(client.get_points(&buyer), 80);
+}
+
+#[test]
+fn test_points_cannot_cover_the_whole_price() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().create();
+    let buyer = market.funded_buyer(10000000000);
+    client.set_loyalty_config(&LoyaltyConfig {
+        earn_rate: 100,
+        burn_rate: 10,
+        ttl: 0,
+    });
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(client.get_points(&buyer), 100);
+
+    market.advance_time(365 * 24 * 60 * 60);
+    assert_eq!(client.get_points(&buyer), 100);
+    assert_eq!(
+        client.try_purchase_with_points(&product.id, &buyer, &1, &market.token_id, &10, &None),
+        Err(Ok(Error::LowAmountForSplitter))
+    );
+    assert_eq!(client.get_points(&buyer), 100);
+}
+
+#[test]
+fn test_sale_phases() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().target(10).create();
+    let buyer = market.funded_buyer(100000000000);
+    let stranger = market.funded_buyer(10000000000);
+    let now = env.ledger().timestamp();
+
+    client.set_sale_phases(
+        &product.id,
+        &vec![
+            &env,
+            SalePhase {
+                access: PhaseAccess::Allowlist,
+                start: now + 10,
+                end: now + 100,
+                cap: 3,
+            },
+            SalePhase {
+                access: PhaseAccess::Public,
+                start: now + 100,
+                end: now + 200,
+                cap: 0,
+            },
+        ],
+    );
+    client.set_allowlist(&product.id, &vec![&env, buyer.clone()], &true);
+    assert!(client.is_allowlisted(&product.id, &buyer));
+    assert!(!client.is_allowlisted(&product.id, &stranger));
+    assert_eq!(
+        market.persistent_ttl(&DataKey::Allowlist(product.id, buyer.clone())),
+        Some(storage::TTL)
+    );
+
+    assert_eq!(
+        client.try_get_discount(&product.id, &buyer, &1, &market.token_id, &None),
+        Err(Ok(Error::SaleNotActive))
+    );
+
+    market.advance_time(10);
+    assert_eq!(
+        client.try_get_discount(&product.id, &stranger, &1, &market.token_id, &None),
+        Err(Ok(Error::NotAllowlisted))
+    );
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    client.checkout(
+        &buyer,
+        &vec![&env, (product.id, 2)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+    assert_eq!(client.get_phase_sold(&product.id, &0), 3);
+    assert_eq!(
+        client.try_get_discount(&product.id, &buyer, &1, &market.token_id, &None),
+        Err(Ok(Error::TargetReached))
+    );
+
+    market.advance_time(90);
+    client.get_discount(&product.id, &stranger, &1, &market.token_id, &None);
+    assert_eq!(client.get_phase_sold(&product.id, &1), 1);
+
+    market.advance_time(100);
+    assert_eq!(
+        client.try_get_discount(&product.id, &stranger, &1, &market.token_id, &None),
+        Err(Ok(Error::SaleNotActive))
+    );
+
+    // Clearing the phases reopens the sale to anyone.
+    client.set_sale_phases(&product.id, &vec![&env]);
+    client.get_discount(&product.id, &stranger, &1, &market.token_id, &None);
+    assert_eq!(client.get_product(&product.id).remaining, 5);
+}
+
+#[test]
+fn test_set_sale_phases_validation() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().create();
+    let phase = |start, end, cap| SalePhase {
+        access: PhaseAccess::Public,
+        start,
+        end,
+        cap,
+    };
+
+    let cases = [
+        vec![&env, phase(10, 10, 0)],
+        vec![&env, phase(10, 20, -1)],
+        vec![&env, phase(10, 30, 0), phase(20, 40, 0)],
+        vec![&env, phase(30, 40, 0), phase(10, 20, 0)],
+    ];
+    for phases in cases {
+        assert_eq!(
+            client.try_set_sale_phases(&product.id, &phases),
+            Err(Ok(Error::InvalidSchedule))
+        );
+    }
+    assert_eq!(
+        client.try_set_sale_phases(&99, &vec![&env, phase(10, 20, 0)]),
+        Err(Ok(Error::ProductNotExist))
+    );
+    assert_eq!(client.get_sale_phases(&product.id), vec![&env]);
+}
+
+#[test]
+fn test_merkle_sale_phase() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let product = market.product().create();
+    let buyer = market.funded_buyer(10000000000);
+    let friend = Address::generate(&env);
+    let stranger = market.funded_buyer(10000000000);
+
+    let leaf = |account: &Address| env.crypto().sha256(&account.clone().to_xdr(&env));
+    let (a, b) = (leaf(&buyer), leaf(&friend));
+    let mut pair = Bytes::new(&env);
+    let (low, high) = if a <= b { (&a, &b) } else { (&b, &a) };
+    pair.append(&low.clone().into());
+    pair.append(&high.clone().into());
+    let root = env.crypto().sha256(&pair);
+
+    client.set_sale_phases(
+        &product.id,
+        &vec![
+            &env,
+            SalePhase {
+                access: PhaseAccess::Merkle(root),
+                start: 0,
+                end: u64::MAX,
+                cap: 0,
+            },
+        ],
+    );
+
+    client.purchase_with_proof(
+        &product.id,
+        &buyer,
+        &1,
+        &market.token_id,
+        &vec![&env, b.clone()],
+        &None,
+    );
+    assert_eq!(
+        client.try_purchase_with_proof(
+            &product.id,
+            &stranger,
+            &1,
+            &market.token_id,
+            &vec![&env, b],
+            &None,
+        ),
+        Err(Ok(Error::NotAllowlisted))
+    );
+    // Purchase paths without a proof can't get into a Merkle phase.
+    assert_eq!(
+        client.try_get_discount(&product.id, &buyer, &1, &market.token_id, &None),
+        Err(Ok(Error::NotAllowlisted))
+    );
+}
+
+#[test]
+fn test_vesting_vault() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let buyer = market.funded_buyer(100000000000);
+    let token = market.token();
+    let dev = market.dev_acc.clone();
+    let pass = |action: ProposalAction| {
+        let proposal_id = client.propose(&market.admin, &action);
+        market.advance_time(multisig::PROPOSAL_TIMELOCK);
+        client.execute(&proposal_id);
+    };
+
+    let start = env.ledger().timestamp() + multisig::PROPOSAL_TIMELOCK;
+    let schedule = VestingSchedule {
+        start,
+        cliff: 100,
+        duration: 1000,
+    };
+    pass(ProposalAction::SetVesting(dev.clone(), schedule.clone()));
+    assert_eq!(client.get_vesting_schedule(&dev), Some(schedule));
+
+    let product = market.product().create();
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(token.balance(&dev), 0);
+    assert_eq!(client.get_unvested(&dev, &market.token_id), 3000000);
+    assert_eq!(
+        client.try_claim_vested(&dev, &market.token_id),
+        Err(Ok(Error::InsufficientClaimable))
+    );
+
+    market.advance_time(500);
+    assert_eq!(client.get_vested(&dev, &market.token_id), 1500000);
+    assert_eq!(client.claim_vested(&dev, &market.token_id), 1500000);
+    assert_eq!(token.balance(&dev), 1500000);
+    assert_eq!(client.get_vested(&dev, &market.token_id), 0);
+    assert_eq!(client.get_unvested(&dev, &market.token_id), 1500000);
+
+    // Accrued shares vest too, instead of becoming withdrawable.
+    client.set_payout_mode(&PayoutMode::Accrue);
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(client.get_claimable(&dev, &market.token_id), 0);
+    assert_eq!(client.get_unvested(&dev, &market.token_id), 3000000);
+
+    // Removing the schedule unlocks the vault and pays the dev share
+    // directly again.
+    market.advance_time(100);
+    pass(ProposalAction::RemoveVesting(dev.clone()));
+    assert_eq!(client.get_unvested(&dev, &market.token_id), 0);
+    assert_eq!(client.claim_vested(&dev, &market.token_id), 4500000);
+    client.set_payout_mode(&PayoutMode::Push);
+    let product = market.product().create();
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(token.balance(&dev), 9000000);
+}
+
+#[test]
+fn test_vesting_follows_a_replaced_payout_account() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let buyer = market.funded_buyer(100000000000);
+    let schedule = VestingSchedule {
+        start: env.ledger().timestamp() + multisig::PROPOSAL_TIMELOCK,
+        cliff: 100,
+        duration: 1000,
+    };
+    let proposal_id = client.propose(
+        &market.admin,
+        &ProposalAction::SetVesting(market.dev_acc.clone(), schedule.clone()),
+    );
+    market.advance_time(multisig::PROPOSAL_TIMELOCK);
+    client.execute(&proposal_id);
+
+    let new_dev = Address::generate(&env);
+    client.set_dev_acc(&new_dev, &vec![&env, market.admin.clone()]);
+    assert_eq!(
+        client.get_vesting_schedule(&new_dev),
+        Some(schedule.clone())
+    );
+    assert_eq!(client.get_vesting_schedule(&market.dev_acc), Some(schedule));
+
+    let product = market.product().create();
+    client.get_discount(&product.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(market.token().balance(&new_dev), 0);
+    assert_eq!(client.get_unvested(&new_dev, &market.token_id), 3000000);
+}
+
+#[test]
+fn test_vesting_schedule_validation() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+
+    for (cliff, duration) in [(0, 0), (200, 100)] {
+        assert_eq!(
+            client.try_propose(
+                &market.admin,
+                &ProposalAction::SetVesting(
+                    market.dev_acc.clone(),
+                    VestingSchedule {
+                        start: 0,
+                        cliff,
+                        duration,
+                    },
+                ),
+            ),
+            Err(Ok(Error::InvalidSchedule))
+        );
+    }
+}
+
+#[test]
+fn test_receipts() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let buyer = market.funded_buyer(100000000000);
+    let friend = Address::generate(&env);
+    let spender = Address::generate(&env);
+
+    let hat = market.product().create();
+    let scarf = market.product().create();
+    assert_eq!(client.get_receipt_mode(&hat.id), ReceiptMode::Off);
+    client.set_receipt_mode(&hat.id, &ReceiptMode::Transferable);
+    client.set_receipt_mode(&scarf.id, &ReceiptMode::NonTransferable);
+
+    client.get_discount(&hat.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(
+        client.get_receipt(&1),
+        Receipt {
+            id: 1,
+            owner: buyer.clone(),
+            product_id: hat.id,
+            order_id: 0,
+        }
+    );
+    assert_eq!(client.owner_of(&1), buyer);
+    assert_eq!(
+        market.persistent_ttl(&DataKey::Receipt(1)),
+        Some(storage::TTL)
+    );
+
+    let order_id = client.checkout(
+        &buyer,
+        &vec![&env, (hat.id, 1), (scarf.id, 1)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+    assert_eq!(client.get_receipt(&2).order_id, order_id);
+    assert_eq!(client.get_receipt(&3).product_id, scarf.id);
+
+    client.transfer_receipt(&buyer, &friend, &1);
+    assert_eq!(client.owner_of(&1), friend);
+    assert_eq!(
+        client.try_transfer_receipt(&buyer, &friend, &1),
+        Err(Ok(Error::NotReceiptOwner))
+    );
+
+    // An approval is good for one transfer.
+    assert_eq!(
+        client.try_transfer_receipt_from(&spender, &buyer, &friend, &2),
+        Err(Ok(Error::NotReceiptOwner))
+    );
+    client.approve_receipt(&buyer, &2, &Some(spender.clone()));
+    assert_eq!(client.get_receipt_approval(&2), Some(spender.clone()));
+    client.transfer_receipt_from(&spender, &buyer, &friend, &2);
+    assert_eq!(client.owner_of(&2), friend);
+    assert_eq!(client.get_receipt_approval(&2), None);
+
+    client.approve_receipt(&buyer, &3, &Some(spender.clone()));
+    client.approve_receipt(&buyer, &3, &None);
+    assert_eq!(client.get_receipt_approval(&3), None);
+    assert_eq!(
+        client.try_transfer_receipt(&buyer, &friend, &3),
+        Err(Ok(Error::NotTransferable))
+    );
+    assert_eq!(
+        client.try_approve_receipt(&friend, &3, &None),
+        Err(Ok(Error::NotReceiptOwner))
+    );
+    assert_eq!(client.try_owner_of(&4), Err(Ok(Error::ReceiptNotExist)));
+
+    // Turning receipts off stops minting but leaves existing ones alone.
+    client.set_receipt_mode(&hat.id, &ReceiptMode::Off);
+    client.get_discount(&hat.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(client.try_get_receipt(&4), Err(Ok(Error::ReceiptNotExist)));
+    assert_eq!(client.owner_of(&1), friend);
+
+    // Bundles and variants mint receipts like any other purchase.
+    let bundle = client.create_bundle(
+        &String::from_str(&env, "Set"),
+        &vec![&env, hat.id, scarf.id],
+        &10,
+    );
+    client.purchase_bundle(&bundle.id, &buyer, &market.token_id, &None);
+    assert_eq!(
+        client.get_receipt(&4),
+        Receipt {
+            id: 4,
+            owner: buyer.clone(),
+            product_id: scarf.id,
+            order_id: 0,
+        }
+    );
+    let sized = market.product().create();
+    client.set_receipt_mode(&sized.id, &ReceiptMode::Transferable);
+    let large = client.add_variant(&sized.id, &String::from_str(&env, "Large"), &0, &1);
+    client.purchase_variant(&sized.id, &large.id, &buyer, &market.token_id, &None);
+    assert_eq!(client.get_receipt(&5).product_id, sized.id);
+    assert_eq!(
+        client.try_set_receipt_mode(&99, &ReceiptMode::Transferable),
+        Err(Ok(Error::ProductNotExist))
+    );
+}
+
+#[test]
+fn test_vouchers() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let buyer = market.funded_buyer(100000000000);
+    let token = market.token();
+    let door = Address::generate(&env);
+
+    let ticket = market.product().create();
+    assert_eq!(client.get_voucher_validity(&ticket.id), None);
+    client.set_voucher_validity(&ticket.id, &1000);
+    client.set_redeemer(&ticket.id, &door, &true);
+    assert!(client.is_redeemer(&ticket.id, &door));
+
+    client.get_discount(&ticket.id, &buyer, &1, &market.token_id, &None);
+    let voucher = client.get_voucher(&1);
+    assert_eq!(
+        voucher,
+        Voucher {
+            id: 1,
+            product_id: ticket.id,
+            holder: buyer.clone(),
+            order_id: 0,
+            token: market.token_id.clone(),
+            amount: 10000000,
+            expires_at: env.ledger().timestamp() + 1000,
+            status: VoucherStatus::Issued,
+        }
+    );
+
+    // A coupon discount is spread over every unit of the order.
+    let code = Symbol::new(&env, "HALF");
+    client.set_coupon(
+        &code,
+        &Coupon {
+            discount_bps: 5000,
+            expiry: u64::MAX,
+            uses_left: 1,
+        },
+    );
+    let order_id = client.checkout(
+        &buyer,
+        &vec![&env, (ticket.id, 2)],
+        &market.token_id,
+        &Some(code),
+        &None,
+    );
+    assert_eq!(client.get_voucher(&3).order_id, order_id);
+    assert_eq!(client.get_voucher(&3).amount, 5000000000);
+    assert_eq!(client.get_voucher_count(&ticket.id), 3);
+    assert_eq!(
+        client
+            .get_outstanding_vouchers(&ticket.id, &0, &MAX_BATCH_SIZE)
+            .len(),
+        3
+    );
+    let page = client.get_outstanding_vouchers(&ticket.id, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).id, 2);
+    assert!(client
+        .get_outstanding_vouchers(&ticket.id, &3, &MAX_BATCH_SIZE)
+        .is_empty());
+    assert_eq!(
+        market.persistent_ttl(&DataKey::Voucher(3)),
+        Some(storage::TTL)
+    );
+    assert_eq!(
+        market.persistent_ttl(&DataKey::ProductVoucher(ticket.id, 2)),
+        Some(storage::TTL)
+    );
+
+    client.redeem(&door, &1);
+    assert_eq!(client.get_voucher(&1).status, VoucherStatus::Redeemed);
+    assert_eq!(
+        client.try_redeem(&door, &1),
+        Err(Ok(Error::InvalidVoucherState))
+    );
+    assert_eq!(client.try_redeem(&buyer, &2), Err(Ok(Error::NotRedeemer)));
+    client.redeem(&market.admin, &2);
+
+    let before = token.balance(&buyer);
+    // Only the reserve's 60% share is paid back.
+    assert_eq!(client.refund_voucher(&3), 3000000000);
+    assert_eq!(token.balance(&buyer), before + 3000000000);
+    assert_eq!(client.get_voucher(&3).status, VoucherStatus::Refunded);
+    assert_eq!(
+        client.try_refund_voucher(&1),
+        Err(Ok(Error::InvalidVoucherState))
+    );
+    assert_eq!(
+        client
+            .get_outstanding_vouchers(&ticket.id, &0, &MAX_BATCH_SIZE)
+            .len(),
+        0
+    );
+
+    // Expired vouchers drop out of the outstanding list but can still be
+    // refunded.
+    client.get_discount(&ticket.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(
+        client
+            .get_outstanding_vouchers(&ticket.id, &0, &MAX_BATCH_SIZE)
+            .len(),
+        1
+    );
+    market.advance_time(1000);
+    assert_eq!(client.get_voucher(&4).status, VoucherStatus::Expired);
+    assert_eq!(
+        client
+            .get_outstanding_vouchers(&ticket.id, &0, &MAX_BATCH_SIZE)
+            .len(),
+        0
+    );
+    assert_eq!(
+        client.try_redeem(&door, &4),
+        Err(Ok(Error::InvalidVoucherState))
+    );
+    client.refund_voucher(&4);
+
+    // While the order's payment is in escrow the whole voucher is refunded
+    // out of it.
+    client.set_dispute_config(&DisputeConfig {
+        window: 1000,
+        response_window: 500,
+    });
+    let order_id = client.checkout(
+        &buyer,
+        &vec![&env, (ticket.id, 2)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+    let before = token.balance(&buyer);
+    assert_eq!(client.refund_voucher(&5), 10000000000);
+    assert_eq!(token.balance(&buyer), before + 10000000000);
+    assert_eq!(client.get_escrow(&order_id).unwrap().amount, 10000000000);
+
+    // Bundles and variants issue vouchers too; a bundle's price is spread
+    // over its products.
+    let pass = market.product().create();
+    client.set_voucher_validity(&pass.id, &0);
+    let bundle = client.create_bundle(
+        &String::from_str(&env, "Weekend"),
+        &vec![&env, ticket.id, pass.id],
+        &1500,
+    );
+    client.purchase_bundle(&bundle.id, &buyer, &market.token_id, &None);
+    assert_eq!(client.get_voucher(&7).product_id, ticket.id);
+    assert_eq!(client.get_voucher(&7).amount, 7500000000);
+    assert_eq!(client.get_voucher(&8).product_id, pass.id);
+    assert_eq!(client.get_voucher(&8).expires_at, 0);
+    client.add_variant(&pass.id, &String::from_str(&env, "VIP"), &500, &1);
+    client.purchase_variant(&pass.id, &1, &buyer, &market.token_id, &None);
+    assert_eq!(client.get_voucher(&9).amount, 15000000000);
+    assert_eq!(client.get_voucher_count(&pass.id), 2);
+
+    client.disable_vouchers(&ticket.id);
+    client.set_redeemer(&ticket.id, &door, &false);
+    assert!(!client.is_redeemer(&ticket.id, &door));
+    let other = market.product().create();
+    client.get_discount(&other.id, &buyer, &1, &market.token_id, &None);
+    assert_eq!(client.try_get_voucher(&10), Err(Ok(Error::VoucherNotExist)));
+    assert_eq!(
+        client.try_set_voucher_validity(&99, &0),
+        Err(Ok(Error::ProductNotExist))
+    );
+}
+
+#[test]
+fn test_reviews() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let buyer = market.funded_buyer(100000000000);
+    let hash = BytesN::from_array(&env, &[7; 32]);
+
+    let hat = market.product().create();
+    let scarf = market.product().create();
+    assert_eq!(client.get_rating(&hat.id), Rating::NONE);
+    let order_id = client.checkout(
+        &buyer,
+        &vec![&env, (hat.id, 1), (scarf.id, 1)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+    let other_order = client.checkout(
+        &buyer,
+        &vec![&env, (hat.id, 1)],
+        &market.token_id,
+        &None,
+        &None,
+    );
+
+    client.review(&order_id, &hat.id, &5, &hash);
+    assert_eq!(
+        client.get_review(&order_id, &hat.id),
+        Some(Review {
+            order_id,
+            product_id: hat.id,
+            rating: 5,
+            hash: hash.clone(),
+        })
+    );
+    client.review(&other_order, &hat.id, &2, &hash);
+    assert_eq!(
+        client.get_rating(&hat.id),
+        Rating {
+            count: 2,
+            total: 7,
+            average: 350,
+        }
+    );
+    assert_eq!(client.get_rating(&scarf.id), Rating::NONE);
+    assert_eq!(
+        client.get_product_view(&hat.id).rating,
+        client.get_rating(&hat.id)
+    );
+    assert_eq!(client.get_product_views().get_unchecked(0).rating.count, 2);
+    assert_eq!(
+        market.persistent_ttl(&DataKey::Review(order_id, hat.id)),
+        Some(storage::TTL)
+    );
+    assert_eq!(
+        market.persistent_ttl(&DataKey::Rating(hat.id)),
+        Some(storage::TTL)
+    );
+
+    // One review per product per order, from 1 to 5 stars, for products
+    // that are actually in the order.
+    assert_eq!(
+        client.try_review(&order_id, &hat.id, &4, &hash),
+        Err(Ok(OrderError::AlreadyReviewed))
+    );
+    for rating in [0, MAX_RATING + 1] {
+        assert_eq!(
+            client.try_review(&order_id, &scarf.id, &rating, &hash),
+            Err(Ok(OrderError::InvalidRating))
+        );
+    }
+    assert_eq!(
+        client.try_review(&other_order, &scarf.id, &4, &hash),
+        Err(Ok(OrderError::NotBuyer))
+    );
+    assert_eq!(
+        client.try_review(&99, &hat.id, &4, &hash),
+        Err(Ok(OrderError::OrderNotExist))
+    );
+    assert_eq!(client.get_review(&other_order, &scarf.id), None);
+
+    // Products bought outside `checkout` can be reviewed once per buyer.
+    assert_eq!(
+        client.try_review_purchase(&buyer, &scarf.id, &4, &hash),
+        Err(Ok(OrderError::NotBuyer))
+    );
+    client.get_discount(&scarf.id, &buyer, &1, &market.token_id, &None);
+    client.review_purchase(&buyer, &scarf.id, &4, &hash);
+    assert_eq!(
+        client.get_purchase_review(&buyer, &scarf.id),
+        Some(Review {
+            order_id: 0,
+            product_id: scarf.id,
+            rating: 4,
+            hash: hash.clone(),
+        })
+    );
+    assert_eq!(client.get_rating(&scarf.id).average, 400);
+    assert_eq!(
+        client.try_review_purchase(&buyer, &scarf.id, &5, &hash),
+        Err(Ok(OrderError::AlreadyReviewed))
+    );
+    assert_eq!(
+        client.try_review_purchase(&buyer, &scarf.id, &0, &hash),
+        Err(Ok(OrderError::InvalidRating))
+    );
+
+    let shawl = market.product().create();
+    client.add_variant(&shawl.id, &String::from_str(&env, "Wool"), &0, &1);
+    client.purchase_variant(&shawl.id, &1, &buyer, &market.token_id, &None);
+    client.review_purchase(&buyer, &shawl.id, &3, &hash);
+    let gloves = market.product().create();
+    let bundle = client.create_bundle(
+        &String::from_str(&env, "Winter"),
+        &vec![&env, gloves.id],
+        &500,
+    );
+    client.purchase_bundle(&bundle.id, &buyer, &market.token_id, &None);
+    client.review_purchase(&buyer, &gloves.id, &5, &hash);
+    assert_eq!(client.get_rating(&shawl.id).count, 1);
+    assert_eq!(client.get_rating(&gloves.id).count, 1);
+}
+
+#[test]
+fn test_disputes() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let token = market.token();
+    let buyer = market.funded_buyer(100000000000);
+    let arbiter = Address::generate(&env);
+    let claim = BytesN::from_array(&env, &[1; 32]);
+    let response = BytesN::from_array(&env, &[2; 32]);
+
+    assert_eq!(client.get_dispute_config(), DisputeConfig::DEFAULT);
+    assert_eq!(
+        client.try_set_dispute_config(&DisputeConfig {
+            window: 1000,
+            response_window: 0,
+        }),
+        Err(Ok(OrderError::InvalidDisputeConfig))
+    );
+    client.set_dispute_config(&DisputeConfig {
+        window: 1000,
+        response_window: 500,
+    });
+    assert_eq!(
+        client.try_resolve_dispute(&1, &Ruling::Release),
+        Err(Ok(OrderError::NoArbiter.into()))
+    );
+    client.set_arbiter(&arbiter);
+    assert_eq!(client.get_arbiter(), Some(arbiter));
+
+    // Orders are paid into escrow rather than out to the payout accounts.
+    let product = market.product().create();
+    let items = vec![&env, (product.id, 1)];
+    let released = client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    let split = client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    let refunded = client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    assert_eq!(token.balance(&market.contract_id), 30000000000);
+    assert_eq!(token.balance(&market.reserve_acc), 0);
+    assert_eq!(
+        client.get_escrow(&released),
+        Some(Escrow {
+            order_id: released,
+            amount: 10000000000,
+            release_at: env.ledger().timestamp() + 1000,
+        })
+    );
+    assert_eq!(
+        client.try_release_escrow(&released),
+        Err(Ok(OrderError::DisputeWindowOpen.into()))
+    );
+    assert_eq!(
+        client.try_respond_dispute(&released, &response),
+        Err(Ok(OrderError::NoDispute))
+    );
+
+    client.open_dispute(&split, &claim);
+    client.open_dispute(&refunded, &claim);
+    for key in [DataKey::Escrow(refunded), DataKey::Dispute(refunded)] {
+        assert_eq!(market.persistent_ttl(&key), Some(storage::TTL));
+    }
+    assert_eq!(
+        client.get_dispute(&split),
+        Some(Dispute {
+            order_id: split,
+            status: DisputeStatus::Open,
+            claim: claim.clone(),
+            response: BytesN::from_array(&env, &[0; 32]),
+            respond_by: env.ledger().timestamp() + 500,
+            refunded: 0,
+        })
+    );
+    assert_eq!(
+        client.try_open_dispute(&split, &claim),
+        Err(Ok(OrderError::AlreadyDisputed))
+    );
+    assert_eq!(
+        client.try_release_escrow(&split),
+        Err(Ok(OrderError::AlreadyDisputed.into()))
+    );
+    // The seller still has time to answer.
+    assert_eq!(
+        client.try_resolve_dispute(&refunded, &Ruling::Refund),
+        Err(Ok(OrderError::ResponsePending.into()))
+    );
+
+    client.respond_dispute(&split, &response);
+    assert_eq!(
+        client.get_dispute(&split).unwrap().status,
+        DisputeStatus::Answered
+    );
+    assert_eq!(
+        client.try_respond_dispute(&split, &response),
+        Err(Ok(OrderError::DisputeClosed))
+    );
+    let before = token.balance(&buyer);
+    assert_eq!(
+        client.resolve_dispute(&split, &Ruling::Split(2500)),
+        2500000000
+    );
+    assert_eq!(token.balance(&buyer), before + 2500000000);
+    assert_eq!(token.balance(&market.reserve_acc), 4500000000);
+    assert_eq!(client.get_escrow(&split), None);
+    assert_eq!(
+        client.try_resolve_dispute(&split, &Ruling::Release),
+        Err(Ok(OrderError::DisputeClosed.into()))
+    );
+
+    // Once the seller's time is up the arbiter rules without them.
+    market.advance_time(500);
+    assert_eq!(
+        client.try_respond_dispute(&refunded, &response),
+        Err(Ok(OrderError::ResponseWindowClosed))
+    );
+    for bps in [0, 10000] {
+        assert_eq!(
+            client.try_resolve_dispute(&refunded, &Ruling::Split(bps)),
+            Err(Ok(Error::InvalidSplit.into()))
+        );
+    }
+    assert_eq!(
+        client.resolve_dispute(&refunded, &Ruling::Refund),
+        10000000000
+    );
+    assert_eq!(
+        client.get_dispute(&refunded).unwrap().status,
+        DisputeStatus::Refunded
+    );
+    // A refunded order can't be reviewed; one split with the seller can.
+    assert_eq!(
+        client.try_review(&refunded, &product.id, &1, &claim),
+        Err(Ok(OrderError::OrderRefunded))
+    );
+    client.review(&split, &product.id, &3, &claim);
+
+    // An undisputed order is released once its window has passed.
+    market.advance_time(500);
+    assert_eq!(
+        client.try_open_dispute(&released, &claim),
+        Err(Ok(OrderError::DisputeWindowClosed))
+    );
+    assert_eq!(
+        client.release_escrow(&released),
+        (6000000000, 1000000000, 3000000000)
+    );
+    assert_eq!(token.balance(&market.reserve_acc), 10500000000);
+    assert_eq!(token.balance(&market.contract_id), 0);
+    assert_eq!(
+        client.try_release_escrow(&released),
+        Err(Ok(OrderError::NoEscrow.into()))
+    );
+
+    // In accrue mode a release credits the shares the contract already holds.
+    client.set_payout_mode(&PayoutMode::Accrue);
+    let accrued = client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    market.advance_time(1000);
+    client.release_escrow(&accrued);
+    assert_eq!(
+        client.get_claimable(&market.reserve_acc, &market.token_id),
+        6000000000
+    );
+    assert_eq!(token.balance(&market.contract_id), 10000000000);
+}
+
+#[test]
+fn test_refund_rulings_unwind_the_order() {
+    let env = Env::default();
+    let market = testutils::Marketplace::setup(&env);
+    let client = market.client();
+    let buyer = market.funded_buyer(100000000000);
+    let claim = BytesN::from_array(&env, &[1; 32]);
+    let response = BytesN::from_array(&env, &[2; 32]);
+    client.set_dispute_config(&DisputeConfig {
+        window: 1000,
+        response_window: 500,
+    });
+    client.set_arbiter(&Address::generate(&env));
+
+    let ticket = market.product().create();
+    client.set_receipt_mode(&ticket.id, &ReceiptMode::Transferable);
+    client.set_voucher_validity(&ticket.id, &0);
+    let now = env.ledger().timestamp();
+    client.set_sale_phases(
+        &ticket.id,
+        &vec![
+            &env,
+            SalePhase {
+                access: PhaseAccess::Public,
+                start: now,
+                end: now + 10000,
+                cap: 4,
+            },
+        ],
+    );
+    client.set_loyalty_config(&LoyaltyConfig {
+        earn_rate: 1,
+        burn_rate: 100,
+        ttl: 0,
+    });
+    let items = vec![&env, (ticket.id, 2)];
+    let refunded = client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    let split = client.checkout(&buyer, &items, &market.token_id, &None, &None);
+    assert_eq!(
+        client.get_product(&ticket.id).remaining,
+        ticket.remaining - 4
+    );
+    assert_eq!(client.get_phase_sold(&ticket.id, &0), 4);
+    assert_eq!(client.get_points(&buyer), 4000);
+    for order_id in [refunded, split] {
+        client.open_dispute(&order_id, &claim);
+        client.respond_dispute(&order_id, &response);
+    }
+
+    // A refund puts every unit back in stock and its phase, takes back the
+    // points, voids the vouchers and burns the receipt, so nothing bought
+    // with the order can be used afterwards.
+    client.resolve_dispute(&refunded, &Ruling::Refund);
+    assert_eq!(
+        client.get_product(&ticket.id).remaining,
+        ticket.remaining - 2
+    );
+    assert_eq!(client.get_phase_sold(&ticket.id, &0), 2);
+    assert_eq!(client.get_points(&buyer), 2000);
+    for voucher_id in [1, 2] {
+        assert_eq!(
+            client.get_voucher(&voucher_id).status,
+            VoucherStatus::Refunded
+        );
+        assert_eq!(
+            client.try_redeem(&market.admin, &voucher_id),
+            Err(Ok(Error::InvalidVoucherState))
+        );
+    }
+    assert_eq!(client.try_owner_of(&1), Err(Ok(Error::ReceiptNotExist)));
+
+    // A split undoes its share of the order and leaves the rest.
+    client.resolve_dispute(&split, &Ruling::Split(5000));
+    assert_eq!(
+        client.get_product(&ticket.id).remaining,
+        ticket.remaining - 1
+    );
+    assert_eq!(client.get_phase_sold(&ticket.id, &0), 1);
+    assert_eq!(client.get_points(&buyer), 1000);
+    assert_eq!(client.get_voucher(&3).status, VoucherStatus::Issued);
+    assert_eq!(client.get_voucher(&4).status, VoucherStatus::Refunded);
+    assert_eq!(client.owner_of(&2), buyer);
+    client.redeem(&market.admin, &3);
+}
+
+fn snapshot_market(env: &Env) -> (Address, MarketplaceClient<'_>, std::vec::Vec<Address>) {
+    env.mock_all_auths();
+    let contract_id = testutils::register_test_contract(env);
+    env.ledger().with_mut(|l| l.timestamp = 1_700_000_000);
+    let client = MarketplaceClient::new(env, &contract_id);
+    let accounts = (0..4).map(|_| Address::generate(env)).collect();
+    (contract_id, client, accounts)
+}
+
+#[test]
+fn test_snapshot_purchase_flow() {
+    let env = Env::default();
+    let (contract_id, client, accounts) = snapshot_market(&env);
+    let [reserve, dev, launchpad, admin] = [0, 1, 2, 3].map(|i| accounts[i].clone());
+    let customer = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(Address::generate(&env));
+    token::StellarAssetClient::new(&env, &token_address).mint(&customer, &10000000000);
+
+    let mut snapshot = testutils::Snapshot::new(&env, &contract_id);
+
+    client.initialize(
+        &reserve,
+        &dev,
+        &launchpad,
+        &admin,
+        &vec![&env, admin.clone()],
+        &1,
+    );
+    snapshot.record("initialize");
+
+    client.create_product(
+        &String::from_str(&env, "Product 1"),
+        &String::from_str(&env, "Description 1"),
+        &String::from_str(&env, "Category 1"),
+        &(env.ledger().timestamp() + 10000),
+        &String::from_str(&env, "image.png"),
+        &1000,
+        &10,
+    );
+    snapshot.record("create_product");
+
+    client.get_discount(&1, &customer, &1, &token_address, &None);
+    snapshot.record("get_discount push");
+
+    client.set_payout_mode(&PayoutMode::Accrue);
+    snapshot.record("set_payout_mode");
+
+    client.get_discount(&1, &customer, &2, &token_address, &None);
+    snapshot.record("get_discount accrue");
+
+    client.withdraw(&dev, &token_address, &1000000);
+    snapshot.record("withdraw");
+
+    snapshot.assert_matches("purchase_flow");
+}
+
+#[test]
+fn test_snapshot_admin_flow() {
+    let env = Env::default();
+    let (contract_id, client, accounts) = snapshot_market(&env);
+    let [admin, signer, new_dev, new_reserve] = [0, 1, 2, 3].map(|i| accounts[i].clone());
+
+    client.initialize(
+        &admin,
+        &admin,
+        &admin,
+        &admin,
+        &vec![&env, admin.clone(), signer.clone()],
+        &2,
+    );
+    let mut snapshot = testutils::Snapshot::new(&env, &contract_id);
+
+    client.set_reserve_acc(&new_reserve, &vec![&env, admin.clone(), signer.clone()]);
+    snapshot.record("set_reserve_acc");
+
+    let proposal_id = client.propose(&admin, &ProposalAction::SetDevAccount(new_dev));
+    snapshot.record("propose");
+
+    client.approve(&signer, &proposal_id);
+    snapshot.record("approve");
+
+    env.ledger()
+        .with_mut(|l| l.timestamp += multisig::PROPOSAL_TIMELOCK);
+    client.execute(&proposal_id);
+    snapshot.record("execute");
+
+    snapshot.assert_matches("admin_flow");
+}
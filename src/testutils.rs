@@ -1,45 +1,410 @@
-#![cfg(test)]
+#![cfg(any(test, feature = "testutils"))]
 
-use crate::CrowdFundClient;
+use crate::{DataKey, MarketplaceClient, Product, ProductInput};
 
-use soroban_sdk::{testutils::Ledger, testutils::LedgerInfo, Address, Env};
+use soroban_sdk::{
+    testutils::{Address as _, Events as _, Ledger, LedgerInfo},
+    token, vec,
+    xdr::{ContractDataDurability, LedgerEntryData, ScAddress, ScVal},
+    Address, Env, IntoVal, String, TryFromVal, Val,
+};
 
 extern crate std;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::format;
 
-pub fn register_test_contract(e: &Env) -> Address {
-    let current_time = SystemTime::now();
-    let current_timestamp = current_time
-        .duration_since(UNIX_EPOCH)
-        .expect("Failed to get the current timestamp")
-        .as_secs() as u64;
+/// Ledger time every test contract starts at, so that runs are repeatable.
+pub const GENESIS_TIMESTAMP: u64 = 1_700_000_000;
 
+pub fn register_test_contract(e: &Env) -> Address {
     e.ledger().set(LedgerInfo {
-        timestamp: current_timestamp.clone(),
-        protocol_version: 1,
+        timestamp: GENESIS_TIMESTAMP,
+        protocol_version: 20,
         sequence_number: 10,
         network_id: Default::default(),
         base_reserve: 10,
+        min_temp_entry_ttl: 16,
+        min_persistent_entry_ttl: 4096,
+        max_entry_ttl: 6312000,
     });
-    e.register_contract(None, crate::CrowdFund {})
+    e.register_contract(None, crate::Marketplace {})
 }
 
-pub struct CrowdFund {
-    env: Env,
-    contract_id: Address,
+/// A registered and initialized `Marketplace` with a Stellar Asset Contract
+/// token to pay with.
+///
+/// All auths are mocked and, unless built with `setup_with_signers`, the
+/// admin is the only multisig signer, so tests can call any entrypoint
+/// directly.
+pub struct Marketplace {
+    pub env: Env,
+    pub contract_id: Address,
+    pub admin: Address,
+    pub reserve_acc: Address,
+    pub dev_acc: Address,
+    pub launchpad_acc: Address,
+    pub token_id: Address,
 }
 
-impl CrowdFund {
+impl Marketplace {
     #[must_use]
-    pub fn client(&self) -> CrowdFundClient<'static> {
-        CrowdFundClient::new(&self.env, &self.contract_id)
+    pub fn setup(env: &Env) -> Self {
+        Self::setup_with_signers(env, &[], 1)
     }
 
+    /// Like `setup`, but with `extra_signers` added to the admin in the
+    /// multisig signer set and the given approval `threshold`.
     #[must_use]
-    pub fn new(env: &Env, contract_id: Address) -> Self {
-        Self {
+    pub fn setup_with_signers(env: &Env, extra_signers: &[Address], threshold: u32) -> Self {
+        env.mock_all_auths();
+
+        let contract_id = register_test_contract(env);
+        let admin = Address::generate(env);
+        let reserve_acc = Address::generate(env);
+        let dev_acc = Address::generate(env);
+        let launchpad_acc = Address::generate(env);
+        let token_id = env.register_stellar_asset_contract(Address::generate(env));
+
+        let market = Self {
             env: env.clone(),
             contract_id,
+            admin,
+            reserve_acc,
+            dev_acc,
+            launchpad_acc,
+            token_id,
+        };
+
+        let mut signers = vec![env, market.admin.clone()];
+        for signer in extra_signers {
+            signers.push_back(signer.clone());
+        }
+        market.client().initialize(
+            &market.reserve_acc,
+            &market.dev_acc,
+            &market.launchpad_acc,
+            &market.admin,
+            &signers,
+            &threshold,
+        );
+        market
+    }
+
+    #[must_use]
+    pub fn client(&self) -> MarketplaceClient<'static> {
+        MarketplaceClient::new(&self.env, &self.contract_id)
+    }
+
+    #[must_use]
+    pub fn token(&self) -> token::Client<'static> {
+        token::Client::new(&self.env, &self.token_id)
+    }
+
+    pub fn mint(&self, to: &Address, amount: i128) {
+        token::StellarAssetClient::new(&self.env, &self.token_id).mint(to, &amount);
+    }
+
+    /// Generates a new address holding `amount` of the payment token.
+    #[must_use]
+    pub fn funded_buyer(&self, amount: i128) -> Address {
+        let buyer = Address::generate(&self.env);
+        self.mint(&buyer, amount);
+        buyer
+    }
+
+    /// Starts a product with sensible defaults that expires a day from now.
+    #[must_use]
+    pub fn product(&self) -> ProductBuilder<'_> {
+        ProductBuilder {
+            market: self,
+            title: String::from_str(&self.env, "Product"),
+            description: String::from_str(&self.env, "Description"),
+            category: String::from_str(&self.env, "Category"),
+            expiry: self.env.ledger().timestamp() + 24 * 60 * 60,
+            image: String::from_str(&self.env, "image.png"),
+            price: 1000,
+            target: 10,
+        }
+    }
+
+    pub fn set_time(&self, timestamp: u64) {
+        self.env.ledger().with_mut(|li| li.timestamp = timestamp);
+    }
+
+    pub fn advance_time(&self, seconds: u64) {
+        self.env.ledger().with_mut(|li| li.timestamp += seconds);
+    }
+
+    /// Ledgers left before the contract's persistent entry under `key` is
+    /// archived, or `None` if there is no such entry.
+    #[must_use]
+    pub fn persistent_ttl(&self, key: &DataKey) -> Option<u32> {
+        let key: Val = key.into_val(&self.env);
+        let key = ScVal::try_from_val(&self.env, &key).unwrap();
+        let snapshot = self.env.to_snapshot();
+        snapshot
+            .ledger
+            .ledger_entries
+            .into_iter()
+            .find_map(|(_, (entry, live_until))| match &entry.data {
+                LedgerEntryData::ContractData(data)
+                    if data.key == key && data.durability == ContractDataDurability::Persistent =>
+                {
+                    live_until.map(|ledger| ledger - snapshot.ledger.sequence_number)
+                }
+                _ => None,
+            })
+    }
+}
+
+pub struct ProductBuilder<'a> {
+    market: &'a Marketplace,
+    title: String,
+    description: String,
+    category: String,
+    expiry: u64,
+    image: String,
+    price: i128,
+    target: i128,
+}
+
+impl ProductBuilder<'_> {
+    #[must_use]
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = String::from_str(&self.market.env, title);
+        self
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = String::from_str(&self.market.env, description);
+        self
+    }
+
+    #[must_use]
+    pub fn category(mut self, category: &str) -> Self {
+        self.category = String::from_str(&self.market.env, category);
+        self
+    }
+
+    #[must_use]
+    pub fn expiry(mut self, expiry: u64) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    #[must_use]
+    pub fn image(mut self, image: &str) -> Self {
+        self.image = String::from_str(&self.market.env, image);
+        self
+    }
+
+    #[must_use]
+    pub fn price(mut self, price: i128) -> Self {
+        self.price = price;
+        self
+    }
+
+    #[must_use]
+    pub fn target(mut self, target: i128) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// The product as an entry for `create_products_batch`.
+    pub fn input(self) -> ProductInput {
+        ProductInput {
+            title: self.title,
+            description: self.description,
+            category: self.category,
+            expiry: self.expiry,
+            image: self.image,
+            price: self.price,
+            target: self.target,
+        }
+    }
+
+    pub fn create(self) -> Product {
+        self.market.client().create_product(
+            &self.title,
+            &self.description,
+            &self.category,
+            &self.expiry,
+            &self.image,
+            &self.price,
+            &self.target,
+        )
+    }
+}
+
+/// Records the events and contract storage after each call and compares the
+/// transcript against a golden file in `snapshots/<name>.snap`.
+///
+/// Values are rendered as XDR `ScVal`s so the files are stable across runs as
+/// long as addresses and ledger time are deterministic. Set
+/// `UPDATE_SNAPSHOTS=1` to rewrite the golden files after an intended change.
+pub struct Snapshot {
+    env: Env,
+    contract_id: Address,
+    events_seen: u32,
+    transcript: std::string::String,
+}
+
+impl Snapshot {
+    /// Starts recording; events emitted before this point are not included.
+    #[must_use]
+    pub fn new(env: &Env, contract_id: &Address) -> Self {
+        Self {
+            env: env.clone(),
+            contract_id: contract_id.clone(),
+            events_seen: env.events().all().len(),
+            transcript: std::string::String::new(),
+        }
+    }
+
+    fn render(&self, val: &Val) -> std::string::String {
+        render_scval(&ScVal::try_from_val(&self.env, val).expect("value converts to ScVal"))
+    }
+
+    /// Appends the events emitted since the previous call to `record` and the
+    /// contract's storage as it is now, under the heading `label`.
+    pub fn record(&mut self, label: &str) {
+        let events = self.env.events().all();
+        let mut out = format!("== {label}\n-- events\n");
+        for (contract, topics, data) in events.slice(self.events_seen..).iter() {
+            let topics: Val = topics.into_val(&self.env);
+            out.push_str(&format!(
+                "{} {} => {}\n",
+                self.render(contract.as_val()),
+                self.render(&topics),
+                self.render(&data)
+            ));
+        }
+        self.events_seen = events.len();
+
+        let contract = match ScVal::try_from(&self.contract_id).unwrap() {
+            ScVal::Address(address) => address,
+            _ => unreachable!(),
+        };
+        let mut instance = std::vec::Vec::new();
+        let mut persistent = std::vec::Vec::new();
+        let mut temporary = std::vec::Vec::new();
+        for (_, (entry, _)) in self.env.to_snapshot().ledger.ledger_entries {
+            let LedgerEntryData::ContractData(data) = &entry.data else {
+                continue;
+            };
+            if data.contract != contract {
+                continue;
+            }
+            match (&data.key, &data.val, &data.durability) {
+                (ScVal::LedgerKeyContractInstance, ScVal::ContractInstance(inst), _) => {
+                    for e in inst.storage.iter().flat_map(|map| map.iter()) {
+                        instance.push((render_scval(&e.key), render_scval(&e.val)));
+                    }
+                }
+                (key, val, ContractDataDurability::Persistent) => {
+                    persistent.push((render_scval(key), render_scval(val)))
+                }
+                (key, val, ContractDataDurability::Temporary) => {
+                    temporary.push((render_scval(key), render_scval(val)))
+                }
+            }
+        }
+        for (kind, mut entries) in [
+            ("instance", instance),
+            ("persistent", persistent),
+            ("temporary", temporary),
+        ] {
+            entries.sort();
+            out.push_str(&format!("-- {kind} storage\n"));
+            for (key, value) in entries {
+                out.push_str(&format!("{key} => {value}\n"));
+            }
+        }
+
+        self.transcript.push_str(&out);
+    }
+
+    /// Panics with both transcripts when the recording differs from
+    /// `snapshots/<name>.snap`.
+    pub fn assert_matches(&self, name: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("snapshots")
+            .join(format!("{name}.snap"));
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &self.transcript).unwrap();
+            return;
+        }
+
+        let golden = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "missing snapshot {}, run with UPDATE_SNAPSHOTS=1",
+                path.display()
+            )
+        });
+        assert!(
+            golden == self.transcript,
+            "snapshot {name} changed\n--- expected\n{golden}\n+++ actual\n{}",
+            self.transcript
+        );
+    }
+}
+
+/// Compact, deterministic rendering of an `ScVal` for snapshot files.
+/// Addresses are shown as `@c<n>` / `@g<n>` with the leading zero bytes of
+/// their hash stripped, which keeps generated test addresses short.
+fn render_scval(val: &ScVal) -> std::string::String {
+    let join = |items: std::vec::Vec<std::string::String>| items.join(", ");
+    match val {
+        ScVal::Bool(b) => format!("{b}"),
+        ScVal::Void => "()".into(),
+        ScVal::U32(n) => format!("{n}u32"),
+        ScVal::I32(n) => format!("{n}i32"),
+        ScVal::U64(n) => format!("{n}u64"),
+        ScVal::I64(n) => format!("{n}i64"),
+        ScVal::U128(parts) => format!(
+            "{}u128",
+            (u128::from(parts.hi) << 64) | u128::from(parts.lo)
+        ),
+        ScVal::I128(parts) => format!(
+            "{}i128",
+            ((i128::from(parts.hi)) << 64) | i128::from(parts.lo)
+        ),
+        ScVal::Symbol(sym) => sym.to_utf8_string_lossy(),
+        ScVal::String(s) => format!("{:?}", s.to_utf8_string_lossy()),
+        ScVal::Bytes(bytes) => format!(
+            "0x{}",
+            bytes
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<std::string::String>()
+        ),
+        ScVal::Vec(Some(items)) => format!("[{}]", join(items.iter().map(render_scval).collect())),
+        ScVal::Map(Some(entries)) => format!(
+            "{{{}}}",
+            join(
+                entries
+                    .iter()
+                    .map(|e| format!("{}: {}", render_scval(&e.key), render_scval(&e.val)))
+                    .collect()
+            )
+        ),
+        ScVal::Address(address) => {
+            let (prefix, bytes) = match address {
+                ScAddress::Contract(hash) => ("c", hash.0),
+                ScAddress::Account(account) => {
+                    let soroban_sdk::xdr::PublicKey::PublicKeyTypeEd25519(key) = &account.0;
+                    ("g", key.0)
+                }
+            };
+            let hex: std::string::String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+            let trimmed = hex.trim_start_matches('0');
+            format!(
+                "@{prefix}{}",
+                if trimmed.is_empty() { "0" } else { trimmed }
+            )
         }
+        other => format!("{other:?}"),
     }
 }
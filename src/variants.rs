@@ -0,0 +1,180 @@
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
+
+use crate::diagnostics::diag;
+use crate::events::{self, PurchaseEvent, VariantEvent};
+use crate::{
+    admin, catalog, loyalty, payout, phases, receipts, reviews, vouchers, DataKey, Error,
+    Marketplace, Product,
+};
+
+/// Most variants a single product can have.
+pub const MAX_VARIANTS: u32 = 10;
+
+/// A size, tier or other option of a product with its own price and stock.
+/// The variant costs the product's `price` plus `price_delta`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Variant {
+    pub id: u32,
+    pub name: String,
+    pub price_delta: i128,
+    pub remaining: i128,
+}
+
+pub(crate) fn get_all(env: &Env, product_id: u32) -> Vec<Variant> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Variants(product_id))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Whether `product_id` is sold in variants and can only be bought as one.
+pub(crate) fn required(env: &Env, product_id: u32) -> bool {
+    env.storage().instance().has(&DataKey::Variants(product_id))
+}
+
+fn get_product(env: &Env, product_id: u32) -> Result<Product, Error> {
+    let product = Marketplace::get_product(env.clone(), product_id);
+    if product_id == 0 || product.id != product_id {
+        return Err(Error::ProductNotExist);
+    }
+    Ok(product)
+}
+
+/// Adds a variant to `product_id`. Variant ids start at 1 within each
+/// product. The name is held to the `title` length limit.
+pub(crate) fn add(
+    env: &Env,
+    product_id: u32,
+    name: String,
+    price_delta: i128,
+    stock: i128,
+) -> Result<Variant, Error> {
+    admin::require_admin(env);
+    let product = get_product(env, product_id)?;
+    let mut variants = get_all(env, product_id);
+
+    if name.len() == 0 {
+        return Err(Error::EmptyTitle);
+    }
+    if name.len() > catalog::get_limits(env).title {
+        return Err(Error::TitleTooLong);
+    }
+    if product.price + price_delta <= 0 {
+        return Err(Error::PriceMustBePositive);
+    }
+    if stock <= 0 {
+        return Err(Error::TargetMustBePositive);
+    }
+    if variants.len() >= MAX_VARIANTS {
+        return Err(Error::TooManyVariants);
+    }
+
+    let variant = Variant {
+        id: variants.len() + 1,
+        name,
+        price_delta,
+        remaining: stock,
+    };
+    variants.push_back(variant.clone());
+    env.storage()
+        .instance()
+        .set(&DataKey::Variants(product_id), &variants);
+
+    events::variant_updated(
+        env,
+        VariantEvent {
+            product_id,
+            variant_id: variant.id,
+            price: product.price + price_delta,
+            remaining: stock,
+        },
+    );
+
+    Ok(variant)
+}
+
+/// Buys one unit of a variant at its price. The sale counts against both the
+/// variant's and the product's remaining stock.
+pub(crate) fn purchase(
+    env: &Env,
+    product_id: u32,
+    variant_id: u32,
+    customer: Address,
+    token_id: Address,
+    referrer: Option<Address>,
+) -> Result<(i128, i128, i128), Error> {
+    customer.require_auth();
+
+    if admin::is_paused(env) {
+        return Err(Error::Paused);
+    }
+    let mut product = get_product(env, product_id)?;
+    let mut variants = get_all(env, product_id);
+    let index = variant_id.checked_sub(1).ok_or(Error::VariantNotExist)?;
+    let mut variant = variants.get(index).ok_or(Error::VariantNotExist)?;
+    if product.expiry < env.ledger().timestamp() {
+        return Err(Error::DiscountExpired);
+    }
+    if product.remaining <= 0 || variant.remaining <= 0 {
+        return Err(Error::TargetReached);
+    }
+    phases::admit(env, product_id, &customer, 1, &Vec::new(env))?;
+
+    let price = product.price + variant.price_delta;
+    diag!(
+        env,
+        "purchase.variant",
+        product = product_id,
+        variant = variant_id,
+        buyer = customer,
+        price = price,
+    );
+    let (reserve_amount, launchpad_amount, dev_amount) =
+        payout::split(env, &token_id, &customer, price * payout::UNIT, referrer)?;
+    loyalty::earn(env, customer.clone(), price * payout::UNIT);
+
+    variant.remaining -= 1;
+    product.remaining -= 1;
+    variants.set(index, variant.clone());
+    env.storage().instance().set(&product.id, &product);
+    env.storage()
+        .instance()
+        .set(&DataKey::Variants(product_id), &variants);
+    receipts::mint(env, &customer, product_id, 0);
+    reviews::record_purchase(env, &customer, product_id);
+    vouchers::issue(
+        env,
+        &customer,
+        product_id,
+        0,
+        &token_id,
+        price * payout::UNIT,
+        1,
+    );
+
+    events::purchase(
+        env,
+        PurchaseEvent {
+            buyer: customer,
+            product_id,
+            token: token_id,
+            quantity: 1,
+            reserve_amount,
+            launchpad_amount,
+            dev_amount,
+        },
+    );
+    events::product_updated(env, &product);
+    events::variant_updated(
+        env,
+        VariantEvent {
+            product_id,
+            variant_id,
+            price,
+            remaining: variant.remaining,
+        },
+    );
+
+    Ok((reserve_amount, launchpad_amount, dev_amount))
+}
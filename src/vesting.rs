@@ -0,0 +1,159 @@
+use soroban_sdk::{contracttype, token, Address, Env};
+
+use crate::diagnostics::diag;
+use crate::events::{self, VestingClaimEvent, VestingEvent};
+use crate::{DataKey, Error};
+
+/// Releases a recipient's payout shares linearly from `start` to
+/// `start + duration`, with nothing claimable until `cliff` seconds after
+/// `start`. Shares paid after the schedule ends are claimable at once.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingSchedule {
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+/// What a recipient has been paid into the vault in one token, and how much
+/// of it they've claimed.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingBalance {
+    pub deposited: i128,
+    pub claimed: i128,
+}
+
+pub(crate) fn check_schedule(schedule: &VestingSchedule) -> Result<(), Error> {
+    if schedule.duration == 0
+        || schedule.cliff > schedule.duration
+        || schedule.start.checked_add(schedule.duration).is_none()
+    {
+        return Err(Error::InvalidSchedule);
+    }
+    Ok(())
+}
+
+pub(crate) fn get_schedule(env: &Env, recipient: &Address) -> Option<VestingSchedule> {
+    env.storage()
+        .instance()
+        .get(&DataKey::VestingSchedule(recipient.clone()))
+}
+
+/// Sends `recipient`'s future shares into the vault. Balances already in
+/// the vault vest on the new schedule from now on.
+pub(crate) fn set_schedule(env: &Env, recipient: Address, schedule: VestingSchedule) {
+    env.storage()
+        .instance()
+        .set(&DataKey::VestingSchedule(recipient.clone()), &schedule);
+    events::vesting_set(
+        env,
+        VestingEvent {
+            recipient,
+            schedule,
+        },
+    );
+}
+
+/// Pays `recipient`'s future shares directly again and releases whatever
+/// is still locked in the vault.
+pub(crate) fn remove_schedule(env: &Env, recipient: Address) {
+    env.storage()
+        .instance()
+        .remove(&DataKey::VestingSchedule(recipient.clone()));
+    events::vesting_removed(env, recipient);
+}
+
+pub(crate) fn get_balance(env: &Env, recipient: &Address, token_id: &Address) -> VestingBalance {
+    env.storage()
+        .instance()
+        .get(&DataKey::Vesting(recipient.clone(), token_id.clone()))
+        .unwrap_or(VestingBalance {
+            deposited: 0,
+            claimed: 0,
+        })
+}
+
+fn set_balance(env: &Env, recipient: &Address, token_id: &Address, balance: &VestingBalance) {
+    env.storage().instance().set(
+        &DataKey::Vesting(recipient.clone(), token_id.clone()),
+        balance,
+    );
+}
+
+/// How much of the vault has vested by now, never less than what was
+/// already claimed under an earlier schedule. Without a schedule the whole
+/// vault is vested.
+fn vested_amount(env: &Env, recipient: &Address, balance: &VestingBalance) -> i128 {
+    let Some(schedule) = get_schedule(env, recipient) else {
+        return balance.deposited;
+    };
+    let now = env.ledger().timestamp();
+    let vested = if now < schedule.start + schedule.cliff {
+        0
+    } else if now >= schedule.start + schedule.duration {
+        balance.deposited
+    } else {
+        balance.deposited * (now - schedule.start) as i128 / schedule.duration as i128
+    };
+    vested.max(balance.claimed)
+}
+
+/// Vested and not yet claimed.
+pub(crate) fn get_vested(env: &Env, recipient: &Address, token_id: &Address) -> i128 {
+    let balance = get_balance(env, recipient, token_id);
+    vested_amount(env, recipient, &balance) - balance.claimed
+}
+
+/// Still locked in the vault.
+pub(crate) fn get_unvested(env: &Env, recipient: &Address, token_id: &Address) -> i128 {
+    let balance = get_balance(env, recipient, token_id);
+    balance.deposited - vested_amount(env, recipient, &balance)
+}
+
+/// Adds a share the contract already holds to `recipient`'s vault.
+pub(crate) fn deposit(env: &Env, recipient: &Address, token_id: &Address, amount: i128) {
+    let mut balance = get_balance(env, recipient, token_id);
+    balance.deposited += amount;
+    set_balance(env, recipient, token_id, &balance);
+    diag!(
+        env,
+        "payout.vest",
+        recipient = *recipient,
+        amount = amount,
+        deposited = balance.deposited,
+    );
+}
+
+/// Transfers everything vested and not yet claimed to `recipient` and
+/// returns the amount.
+pub(crate) fn claim(env: &Env, recipient: Address, token_id: Address) -> Result<i128, Error> {
+    recipient.require_auth();
+
+    let mut balance = get_balance(env, &recipient, &token_id);
+    let vested = vested_amount(env, &recipient, &balance);
+    let amount = vested - balance.claimed;
+    if amount <= 0 {
+        return Err(Error::InsufficientClaimable);
+    }
+
+    balance.claimed += amount;
+    set_balance(env, &recipient, &token_id, &balance);
+    token::Client::new(env, &token_id).transfer(
+        &env.current_contract_address(),
+        &recipient,
+        &amount,
+    );
+
+    events::vesting_claimed(
+        env,
+        VestingClaimEvent {
+            recipient,
+            token: token_id,
+            amount,
+            unvested: balance.deposited - vested,
+        },
+    );
+
+    Ok(amount)
+}
@@ -0,0 +1,302 @@
+use soroban_sdk::{contracttype, token, Address, Env, Vec};
+
+use crate::diagnostics::diag;
+use crate::events::{self, RedeemEvent, RedeemerEvent, RefundEvent, VoucherValidityEvent};
+use crate::{admin, disputes, storage, DataKey, Error, Marketplace, MAX_BATCH_SIZE};
+
+/// Where a voucher is in its life. `Expired` is never stored: an issued
+/// voucher reads as expired once its `expires_at` has passed.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VoucherStatus {
+    Issued,
+    Redeemed,
+    Expired,
+    Refunded,
+}
+
+/// One purchased unit of a voucher product. `amount` is what the holder
+/// paid for it in stroops of `token`, and what a refund pays back.
+/// `order_id` is 0 for a single purchase and `expires_at` is 0 for a
+/// voucher that never expires.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Voucher {
+    pub id: u32,
+    pub product_id: u32,
+    pub holder: Address,
+    pub order_id: u32,
+    pub token: Address,
+    pub amount: i128,
+    pub expires_at: u64,
+    pub status: VoucherStatus,
+}
+
+/// How long a product's vouchers stay redeemable, in seconds, or `None` if
+/// the product doesn't issue vouchers. 0 means they never expire.
+pub(crate) fn get_validity(env: &Env, product_id: u32) -> Option<u64> {
+    env.storage()
+        .instance()
+        .get(&DataKey::VoucherValidity(product_id))
+}
+
+/// Makes every unit sold of the product from now on issue a voucher.
+pub(crate) fn set_validity(env: &Env, product_id: u32, validity: u64) -> Result<(), Error> {
+    admin::require_admin(env);
+    check_product(env, product_id)?;
+    env.storage()
+        .instance()
+        .set(&DataKey::VoucherValidity(product_id), &validity);
+    events::voucher_validity_set(
+        env,
+        VoucherValidityEvent {
+            product_id,
+            validity,
+        },
+    );
+    Ok(())
+}
+
+/// Stops the product issuing vouchers. Vouchers already issued stay
+/// redeemable.
+pub(crate) fn disable(env: &Env, product_id: u32) {
+    admin::require_admin(env);
+    env.storage()
+        .instance()
+        .remove(&DataKey::VoucherValidity(product_id));
+    events::vouchers_disabled(env, product_id);
+}
+
+pub(crate) fn is_redeemer(env: &Env, product_id: u32, account: &Address) -> bool {
+    env.storage()
+        .instance()
+        .has(&DataKey::Redeemer(product_id, account.clone()))
+}
+
+/// Lets `redeemer` redeem the product's vouchers alongside the admin, or
+/// takes that right away again.
+pub(crate) fn set_redeemer(
+    env: &Env,
+    product_id: u32,
+    redeemer: Address,
+    allowed: bool,
+) -> Result<(), Error> {
+    admin::require_admin(env);
+    check_product(env, product_id)?;
+    let key = DataKey::Redeemer(product_id, redeemer.clone());
+    if allowed {
+        env.storage().instance().set(&key, &true);
+    } else {
+        env.storage().instance().remove(&key);
+    }
+    events::redeemer_changed(
+        env,
+        RedeemerEvent {
+            product_id,
+            redeemer,
+            allowed,
+        },
+    );
+    Ok(())
+}
+
+pub(crate) fn get(env: &Env, voucher_id: u32) -> Result<Voucher, Error> {
+    let mut voucher: Voucher =
+        storage::get(env, &DataKey::Voucher(voucher_id)).ok_or(Error::VoucherNotExist)?;
+    if voucher.status == VoucherStatus::Issued
+        && voucher.expires_at != 0
+        && voucher.expires_at <= env.ledger().timestamp()
+    {
+        voucher.status = VoucherStatus::Expired;
+    }
+    Ok(voucher)
+}
+
+/// How many vouchers the product has issued in all.
+pub(crate) fn count(env: &Env, product_id: u32) -> u32 {
+    storage::get(env, &DataKey::ProductVouchers(product_id)).unwrap_or(0)
+}
+
+/// The vouchers that can still be redeemed among the `limit` the product
+/// issued from its `start`th on, oldest first. At most `MAX_BATCH_SIZE`
+/// vouchers are looked at per call.
+pub(crate) fn get_outstanding(env: &Env, product_id: u32, start: u32, limit: u32) -> Vec<Voucher> {
+    let end = start
+        .saturating_add(limit.min(MAX_BATCH_SIZE))
+        .min(count(env, product_id));
+    let mut vouchers = Vec::new(env);
+    for index in start..end {
+        let voucher_id = storage::get(env, &DataKey::ProductVoucher(product_id, index))
+            .expect("every index below the count is stored");
+        if let Ok(voucher) = get(env, voucher_id) {
+            if voucher.status == VoucherStatus::Issued {
+                vouchers.push_back(voucher);
+            }
+        }
+    }
+    vouchers
+}
+
+/// Id the next voucher issued will get.
+pub(crate) fn next_id(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get::<DataKey, u32>(&DataKey::NoOfVouchers)
+        .unwrap_or(0)
+        + 1
+}
+
+/// Marks up to `count` of the unused vouchers among `first..end` as
+/// refunded, newest first, without paying anything back. Used when a
+/// dispute refunds the order they were issued for.
+pub(crate) fn void(env: &Env, first: u32, end: u32, count: u32) {
+    let mut left = count;
+    for voucher_id in (first..end).rev() {
+        if left == 0 {
+            break;
+        }
+        let Ok(mut voucher) = get(env, voucher_id) else {
+            continue;
+        };
+        if voucher.status != VoucherStatus::Issued && voucher.status != VoucherStatus::Expired {
+            continue;
+        }
+        voucher.status = VoucherStatus::Refunded;
+        storage::set(env, &DataKey::Voucher(voucher_id), &voucher);
+        events::voucher_voided(env, voucher_id);
+        left -= 1;
+    }
+}
+
+/// Issues `quantity` vouchers to `holder` if the product has vouchers
+/// turned on, each worth `amount`.
+pub(crate) fn issue(
+    env: &Env,
+    holder: &Address,
+    product_id: u32,
+    order_id: u32,
+    token_id: &Address,
+    amount: i128,
+    quantity: u32,
+) {
+    let Some(validity) = get_validity(env, product_id) else {
+        return;
+    };
+    let expires_at = match validity {
+        0 => 0,
+        validity => env.ledger().timestamp().saturating_add(validity),
+    };
+    let mut last_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NoOfVouchers)
+        .unwrap_or(0);
+    let mut index = count(env, product_id);
+    for _ in 0..quantity {
+        last_id += 1;
+        let voucher = Voucher {
+            id: last_id,
+            product_id,
+            holder: holder.clone(),
+            order_id,
+            token: token_id.clone(),
+            amount,
+            expires_at,
+            status: VoucherStatus::Issued,
+        };
+        storage::set(env, &DataKey::Voucher(last_id), &voucher);
+        storage::set(env, &DataKey::ProductVoucher(product_id, index), &last_id);
+        index += 1;
+        events::voucher_issued(env, voucher);
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::NoOfVouchers, &last_id);
+    storage::set(env, &DataKey::ProductVouchers(product_id), &index);
+    diag!(
+        env,
+        "voucher.issue",
+        product = product_id,
+        holder = *holder,
+        quantity = quantity,
+    );
+}
+
+/// Marks an issued voucher as used. `redeemer` must be the admin or one of
+/// the product's redeemers.
+pub(crate) fn redeem(env: &Env, redeemer: Address, voucher_id: u32) -> Result<(), Error> {
+    redeemer.require_auth();
+    let mut voucher = get(env, voucher_id)?;
+    if redeemer != Marketplace::get_admin(env.clone())
+        && !is_redeemer(env, voucher.product_id, &redeemer)
+    {
+        return Err(Error::NotRedeemer);
+    }
+    if voucher.status != VoucherStatus::Issued {
+        return Err(Error::InvalidVoucherState);
+    }
+
+    voucher.status = VoucherStatus::Redeemed;
+    storage::set(env, &DataKey::Voucher(voucher_id), &voucher);
+    events::voucher_redeemed(
+        env,
+        RedeemEvent {
+            voucher_id,
+            product_id: voucher.product_id,
+            redeemer,
+        },
+    );
+    Ok(())
+}
+
+/// Pays an unused voucher back to its holder. While its order's payment
+/// is still in escrow the refund comes out of the escrow; otherwise the
+/// reserve account pays back its own share of the voucher under the
+/// current split, and has to authorize the call along with the admin.
+/// Expired vouchers can still be refunded.
+pub(crate) fn refund(env: &Env, voucher_id: u32) -> Result<i128, Error> {
+    admin::require_admin(env);
+    let mut voucher = get(env, voucher_id)?;
+    if voucher.status != VoucherStatus::Issued && voucher.status != VoucherStatus::Expired {
+        return Err(Error::InvalidVoucherState);
+    }
+
+    let client = token::Client::new(env, &voucher.token);
+    let amount = if voucher.order_id != 0 && disputes::take(env, voucher.order_id, voucher.amount) {
+        client.transfer(
+            &env.current_contract_address(),
+            &voucher.holder,
+            &voucher.amount,
+        );
+        voucher.amount
+    } else {
+        let reserve = Marketplace::get_reserve_acc(env.clone());
+        reserve.require_auth();
+        let split = Marketplace::get_split(env.clone());
+        let amount = voucher.amount * split.reserve / (split.reserve + split.launchpad + split.dev);
+        if amount > 0 {
+            client.transfer(&reserve, &voucher.holder, &amount);
+        }
+        amount
+    };
+
+    voucher.status = VoucherStatus::Refunded;
+    storage::set(env, &DataKey::Voucher(voucher_id), &voucher);
+    events::refund(
+        env,
+        RefundEvent {
+            buyer: voucher.holder,
+            product_id: voucher.product_id,
+            token: voucher.token,
+            amount,
+        },
+    );
+    Ok(amount)
+}
+
+fn check_product(env: &Env, product_id: u32) -> Result<(), Error> {
+    if product_id == 0 || Marketplace::get_product(env.clone(), product_id).id != product_id {
+        return Err(Error::ProductNotExist);
+    }
+    Ok(())
+}